@@ -0,0 +1,96 @@
+//! Generated corpora shared by the benchmarks of `huff_coding` and `huff`,
+//! the same on every run, so the results can be compared between runs
+
+/// Sizes (in bytes) every corpus is generated with
+pub const SIZES: [usize; 3] = [4 * 1024, 256 * 1024, 4 * 1_048_576];
+
+/// Function generating a corpus of the provided size
+pub type Generator = fn(usize) -> Vec<u8>;
+
+/// Names of the corpora, paired with the functions generating them
+pub const CORPORA: [(&str, Generator); 4] = [
+    ("text", text),
+    ("skewed", skewed),
+    ("random", random),
+    ("runs", runs),
+];
+
+/// Xorshift pseudo random number generator, seeded with a constant
+struct Rng(u32);
+
+impl Rng{
+    fn new() -> Self{
+        Rng(0x9e37_79b9)
+    }
+
+    fn next(&mut self) -> u32{
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+}
+
+/// English-like text: words picked from a small vocabulary,
+/// the first ones more often than the last, split with spaces,
+/// punctuation and newlines
+pub fn text(len: usize) -> Vec<u8>{
+    const WORDS: [&str; 32] = [
+        "the", "of", "and", "a", "to", "in", "is", "it", "that", "was",
+        "for", "on", "are", "with", "as", "his", "they", "be", "at", "one",
+        "have", "this", "from", "by", "huffman", "tree", "code", "letter",
+        "weight", "compress", "bytes", "Grimgor",
+    ];
+    let mut rng = Rng::new();
+    let mut bytes = Vec::with_capacity(len + 16);
+    while bytes.len() < len{
+        // taking the lower of two picks favours the first words
+        let word = (rng.next() % 32).min(rng.next() % 32) as usize;
+        bytes.extend_from_slice(WORDS[word].as_bytes());
+        bytes.push(match rng.next() % 16{
+            0 => b'.',
+            1 => b',',
+            2 => b'\n',
+            _ => b' ',
+        });
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+/// Bytes with geometrically decreasing weights:
+/// byte 0 makes up about half of them, byte 1 a quarter, etc.
+pub fn skewed(len: usize) -> Vec<u8>{
+    let mut rng = Rng::new();
+    (0..len)
+        .map(|_| (rng.next() | 1 << 31).trailing_zeros() as u8)
+        .collect()
+}
+
+/// Uniformly random bytes, which can't be compressed
+pub fn random(len: usize) -> Vec<u8>{
+    let mut rng = Rng::new();
+    (0..len).map(|_| rng.next() as u8).collect()
+}
+
+/// Runs of a single random byte, from 1 to 256 bytes long
+pub fn runs(len: usize) -> Vec<u8>{
+    let mut rng = Rng::new();
+    let mut bytes = Vec::with_capacity(len);
+    while bytes.len() < len{
+        let byte = rng.next() as u8;
+        let run_len = (rng.next() % 256 + 1) as usize;
+        bytes.resize((bytes.len() + run_len).min(len), byte);
+    }
+    bytes
+}
+
+/// Human readable size, used in the benchmark ids
+pub fn size_name(size: usize) -> String{
+    if size >= 1_048_576{
+        format!("{}MiB", size / 1_048_576)
+    }
+    else{
+        format!("{}KiB", size / 1024)
+    }
+}
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// the first byte sets the CLI's block size, decompressing the rest must not panic
+fuzz_target!(|data: &[u8]|{
+    let (block_size, mut src) = match data.split_first(){
+        Some((block_size, src)) => (*block_size as usize + 1, src),
+        None => return,
+    };
+    let mut buf = vec![0; block_size];
+    let _ = huff::comp::decompress_to_writer(&mut src, &mut Vec::new(), &mut buf);
+});
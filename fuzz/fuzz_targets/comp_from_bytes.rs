@@ -0,0 +1,17 @@
+#![no_main]
+use huff_coding::prelude::*;
+use libfuzzer_sys::fuzz_target;
+
+// reading any bytes (and decompressing them if they're read) must not panic
+fuzz_target!(|data: &[u8]|{
+    if let Ok(comp_data) = CompressData::<u8>::try_from_bytes(data){
+        let _ = comp_data.decompress_to_vec(&mut Vec::new());
+    }
+    if let Ok(comp_data) = CompressDataRef::<u16>::try_from_bytes(data){
+        let _ = comp_data.decompress_to_vec(&mut Vec::new());
+    }
+    if let Ok(packed) = PackedData::<u8>::try_from_bytes(data){
+        let _ = packed.unpack();
+    }
+    let _ = PackedData::<u32>::try_from_bytes(data);
+});
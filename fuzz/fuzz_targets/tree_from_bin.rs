@@ -0,0 +1,24 @@
+#![no_main]
+use huff_coding::{
+    prelude::*,
+    bitvec::prelude::*,
+};
+use libfuzzer_sys::fuzz_target;
+
+// the first byte is the number of padding bits cut off the rest,
+// reading them as a tree must not panic, and a read tree must write the same bits
+fuzz_target!(|data: &[u8]|{
+    let (padding_bits, bin_bytes) = match data.split_first(){
+        Some((padding_bits, bin_bytes)) => ((padding_bits % 8) as usize, bin_bytes),
+        None => return,
+    };
+    let mut bin = BitVec::<Msb0, u8>::from_vec(bin_bytes.to_vec());
+    bin.truncate(bin.len().saturating_sub(padding_bits));
+
+    if let Ok(tree) = HuffTree::<u8>::try_from_bin(bin.clone()){
+        assert_eq!(tree.as_bin(), bin);
+    }
+    if let Ok(tree) = HuffTree::<i32>::try_from_bin(bin.clone()){
+        assert_eq!(tree.as_bin(), bin);
+    }
+});
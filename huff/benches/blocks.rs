@@ -0,0 +1,63 @@
+use bench_corpora::{CORPORA, SIZES, size_name};
+
+use huff::comp::{
+    compress_to_writer,
+    decompress_to_writer,
+};
+use huff_coding::prelude::Concurrency;
+
+use criterion::{
+    criterion_group,
+    criterion_main,
+    black_box,
+    BenchmarkId,
+    Criterion,
+    Throughput,
+};
+
+use std::io::Cursor;
+
+/// Block size passed to the CLI, smaller than its 2G default,
+/// so the largest corpora are read in several blocks
+const BLOCK_SIZE: usize = 1_048_576;
+
+/// Compress and decompress the corpora the way the CLI does with files,
+/// reading them in blocks of BLOCK_SIZE bytes from memory
+fn block_path(c: &mut Criterion){
+    let concurrency = Concurrency::default();
+    let mut buf = vec![0; BLOCK_SIZE];
+
+    let mut group = c.benchmark_group("cli_blocks");
+    for size in SIZES.iter(){
+        if *size >= 1_048_576{
+            group.sample_size(10);
+        }
+        for (corpus_name, gen) in CORPORA.iter(){
+            let bytes = gen(*size);
+            group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+            let mut comp_bytes = Cursor::new(Vec::new());
+            compress_to_writer(&mut Cursor::new(&bytes), &mut comp_bytes, bytes.len(), &mut buf, &concurrency).unwrap();
+            let comp_bytes = comp_bytes.into_inner();
+
+            group.bench_function(BenchmarkId::new(format!("compress/{}", corpus_name), size_name(*size)), |b|{
+                b.iter(||{
+                    let mut dst = Cursor::new(Vec::with_capacity(comp_bytes.len()));
+                    compress_to_writer(&mut Cursor::new(black_box(&bytes)), &mut dst, bytes.len(), &mut buf, &concurrency).unwrap();
+                    dst.into_inner()
+                })
+            });
+            group.bench_function(BenchmarkId::new(format!("decompress/{}", corpus_name), size_name(*size)), |b|{
+                b.iter(||{
+                    let mut dst = Vec::with_capacity(bytes.len());
+                    decompress_to_writer(&mut black_box(&comp_bytes[..]), &mut dst, &mut buf).unwrap();
+                    dst
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, block_path);
+criterion_main!(benches);
@@ -6,6 +6,8 @@ use super::{
     }
 };
 
+use huff_coding::prelude::Concurrency;
+
 use std::{
     fs,
     ffi::OsStr,
@@ -22,13 +24,7 @@ use std::{
 pub const EXTENSION: &str = "hff";
 
 macro_rules! parse_paths {
-    ($src_path: expr, $dst_path:expr) =>{
-        // copy file name from src if none is provided
-        if $dst_path == Path::new("./SRC_FILE.hff"){
-            $dst_path.set_file_name("");
-            $dst_path.push(Path::new($src_path.file_name().unwrap()));
-        }
-
+    (src; $src_path: expr) =>{
         // check if src is a file
         if $src_path.is_dir(){
             return Err(Error::new(
@@ -37,6 +33,24 @@ macro_rules! parse_paths {
             ))
         }
     };
+    (hff; $src_path: expr) =>{
+        // check if the src_path file has a cli::EXTENSION extension
+        if $src_path.extension() != Some(OsStr::new(EXTENSION)){
+            return Err(Error::new(
+                format!("Unrecognized file format, expected {}", EXTENSION), 
+                ErrorKind::UnrecognizedFormat
+            ))
+        }
+    };
+    ($src_path: expr, $dst_path:expr) =>{
+        // copy file name from src if none is provided
+        if $dst_path == Path::new("./SRC_FILE.hff"){
+            $dst_path.set_file_name("");
+            $dst_path.push(Path::new($src_path.file_name().unwrap()));
+        }
+
+        parse_paths!(src; $src_path);
+    };
     (comp; $src_path: expr, $dst_path:expr) =>{
         parse_paths!($src_path, $dst_path);
         
@@ -52,15 +66,13 @@ macro_rules! parse_paths {
         });
 
     };
+    (dump; $src_path: expr) =>{
+        parse_paths!(src; $src_path);
+        parse_paths!(hff; $src_path);
+    };
     (decomp; $src_path: expr, $dst_path:expr) =>{
         parse_paths!($src_path, $dst_path);
-        // check if the src_path file has a cli::EXTENSION extension
-        if $src_path.extension() != Some(OsStr::new(EXTENSION)){
-            return Err(Error::new(
-                format!("Unrecognized file format, expected {}", EXTENSION), 
-                ErrorKind::UnrecognizedFormat
-            ))
-        }
+        parse_paths!(hff; $src_path);
         // remove the cli::EXTENSION extension if the dst_path is the same as src_path
         if $dst_path == {let mut p = PathBuf::from("./"); p.push($src_path.clone()); p}{
             $dst_path.set_extension("");
@@ -113,6 +125,23 @@ macro_rules! parse_block_size {
     }};
 }
 
+macro_rules! parse_jobs {
+    ($jobs_str:expr) => {{
+        match &$jobs_str.to_lowercase()[..]{
+            "auto" => Concurrency::available(),
+            jobs => match jobs.parse::<usize>(){
+                Ok(1) => Concurrency::sequential(),
+                Ok(jobs) if jobs > 1 => Concurrency::fixed(jobs),
+                _ => 
+                    return Err(Error::new(
+                        String::from("Invalid number of jobs"), 
+                        ErrorKind::InvalidInput
+                    )),
+            }
+        }
+    }};
+}
+
 macro_rules! ask_replace {
     ($path: expr, $noask:expr) => {
         if $path.exists() && !$noask{
@@ -136,9 +165,16 @@ pub fn process_args(matches: clap::ArgMatches) -> Result<(), Error>{
     let mut dst_path = std::path::PathBuf::from(matches.value_of("DST_FILE").unwrap());
     
     let block_size = parse_block_size!(matches.value_of("block-size").unwrap());
+    let concurrency = parse_jobs!(matches.value_of("jobs").unwrap());
 
+    // the dump-tree flag is present
+    if matches.is_present("dump-tree"){
+        parse_paths!(dump; src_path);
+        // read src's tree and print it
+        comp::read_dump_tree(&src_path, matches.is_present("dot"))?;
+    }
     // the decompress flag is present
-    if matches.is_present("decompress"){
+    else if matches.is_present("decompress"){
         parse_paths!(decomp; src_path, dst_path);
         // ask if should replace dst_file
         ask_replace!(dst_path, matches.is_present("noask"));
@@ -151,7 +187,7 @@ pub fn process_args(matches: clap::ArgMatches) -> Result<(), Error>{
         // ask if should replace dst_file
         ask_replace!(dst_path, matches.is_present("noask"));
         // read src, compress it, write the results to dst
-        comp::read_compress_write(&src_path, &dst_path, block_size)?;
+        comp::read_compress_write(&src_path, &dst_path, block_size, &concurrency)?;
     }
     if matches.is_present("replace"){
         fs::remove_file(src_path).unwrap();
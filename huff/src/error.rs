@@ -1,3 +1,8 @@
+use huff_coding::stream::{
+    StreamError,
+    StreamErrorKind,
+};
+
 use std::{
     io,
     fmt,
@@ -18,6 +23,9 @@ pub enum ErrorKind{
     /// The file you want to decompress contains
     /// invalid padding info/tree
     InvalidHeaderInfo,
+    /// The file you want to decompress contains compressed
+    /// data not matching its header (e.g. the letter count)
+    InvalidData,
     /// The provided file path points to 
     /// a directory
     NotFile,
@@ -61,3 +69,15 @@ impl From<io::Error> for Error {
         Error::new(e.to_string(), ErrorKind::Io)
     }
 }
+
+impl From<StreamError> for Error {
+    fn from(e: StreamError) -> Self {
+        let kind = match e.kind(){
+            StreamErrorKind::InvalidInput => ErrorKind::InvalidInput,
+            StreamErrorKind::MissingHeader => ErrorKind::MissingHeaderInfo,
+            StreamErrorKind::InvalidHeader => ErrorKind::InvalidHeaderInfo,
+            StreamErrorKind::InvalidData => ErrorKind::InvalidData,
+        };
+        Error::new(e.message().to_string(), kind)
+    }
+}
@@ -1,5 +1,4 @@
 // when i have time:
-// TODO: add optional multithreading
 // TODO: verbose option
 // TODO: TESTS
 
@@ -9,6 +8,4 @@ pub mod cli;
 pub mod error;
 /// Functions reading file, compressing/decompressing them, 
 /// and writing the results to file
-mod comp;
-/// Various utility functions
-mod utils;
+pub mod comp;
@@ -0,0 +1,58 @@
+use huff_coding::prelude::ByteWeights;
+
+use criterion::{
+    criterion_group,
+    criterion_main,
+    black_box,
+    BenchmarkId,
+    Criterion,
+    Throughput,
+};
+
+/// Count bytes the way ByteWeights::from_bytes used to:
+/// a single table, incremented byte by byte
+fn single_table_weights(bytes: &[u8]) -> [usize; 256]{
+    let mut weights = [0; 256];
+    for byte in bytes{
+        weights[*byte as usize] += 1;
+    }
+    weights
+}
+
+/// Generate pseudo random bytes, where every byte is repeated
+/// run_len times, so that the counting gets stalled on the same counter
+fn gen_bytes(len: usize, run_len: usize) -> Vec<u8>{
+    let mut state: u32 = 0x9e37_79b9;
+    let mut bytes = Vec::with_capacity(len);
+    while bytes.len() < len{
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        for _ in 0..run_len.min(len - bytes.len()){
+            bytes.push(state as u8);
+        }
+    }
+    bytes
+}
+
+fn count_bytes(c: &mut Criterion){
+    let mut group = c.benchmark_group("byte_weights");
+    for (name, run_len) in [("random", 1), ("runs", 64)].iter(){
+        let bytes = gen_bytes(16 * 1_048_576, *run_len);
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("single_table", name), &bytes, |b, bytes|{
+            b.iter(|| single_table_weights(black_box(bytes)))
+        });
+        group.bench_with_input(BenchmarkId::new("from_bytes", name), &bytes, |b, bytes|{
+            b.iter(|| ByteWeights::from_bytes(black_box(bytes)))
+        });
+        group.bench_with_input(BenchmarkId::new("threaded_from_bytes", name), &bytes, |b, bytes|{
+            b.iter(|| ByteWeights::threaded_from_bytes(black_box(bytes), 4))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, count_bytes);
+criterion_main!(benches);
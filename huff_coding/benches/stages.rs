@@ -0,0 +1,84 @@
+use bench_corpora::{CORPORA, SIZES, size_name};
+
+use huff_coding::prelude::*;
+
+use criterion::{
+    criterion_group,
+    criterion_main,
+    black_box,
+    BatchSize,
+    BenchmarkGroup,
+    BenchmarkId,
+    Criterion,
+    Throughput,
+    measurement::WallTime,
+};
+
+/// Bench f on every corpus of every size, reporting
+/// the throughput in the bytes of the corpus
+fn bench_corpora<T, P, F>(c: &mut Criterion, name: &str, mut prepare: P, mut f: F)
+where
+    P: FnMut(&[u8]) -> T,
+    F: FnMut(&mut BenchmarkGroup<WallTime>, BenchmarkId, &T),
+{
+    let mut group = c.benchmark_group(name);
+    for size in SIZES.iter(){
+        if *size >= 1_048_576{
+            group.sample_size(10);
+        }
+        for (corpus_name, gen) in CORPORA.iter(){
+            let bytes = gen(*size);
+            group.throughput(Throughput::Bytes(bytes.len() as u64));
+            let input = prepare(&bytes);
+            f(&mut group, BenchmarkId::new(*corpus_name, size_name(*size)), &input);
+        }
+    }
+    group.finish();
+}
+
+fn byte_weights(c: &mut Criterion){
+    bench_corpora(c, "byte_weights_from_bytes", |bytes| bytes.to_vec(), |group, id, bytes|{
+        group.bench_function(id, |b| b.iter(|| ByteWeights::from_bytes(black_box(bytes))));
+    });
+}
+
+/// Building the tree only depends on the weights, so its throughput
+/// is in the bytes they were counted from, to compare it with the other stages
+fn tree_from_weights(c: &mut Criterion){
+    bench_corpora(c, "tree_from_weights", ByteWeights::from_bytes, |group, id, weights|{
+        group.bench_function(id, |b| b.iter(|| HuffTree::from_weights(black_box(*weights))));
+    });
+}
+
+fn compress_bytes(c: &mut Criterion){
+    bench_corpora(c, "compress", |bytes| bytes.to_vec(), |group, id, bytes|{
+        group.bench_function(id, |b| b.iter(|| compress(black_box(bytes))));
+    });
+}
+
+fn compress_bytes_with_tree(c: &mut Criterion){
+    let prepare = |bytes: &[u8]| (bytes.to_vec(), HuffTree::from_weights(ByteWeights::from_bytes(bytes)));
+    bench_corpora(c, "compress_with_tree", prepare, |group, id, (bytes, tree)|{
+        group.bench_function(id, |b| b.iter_batched(
+            || tree.clone(),
+            |tree| compress_with_tree(black_box(bytes), tree).unwrap(),
+            BatchSize::SmallInput,
+        ));
+    });
+}
+
+fn decompress_bytes(c: &mut Criterion){
+    bench_corpora(c, "decompress", compress, |group, id, comp_data|{
+        group.bench_function(id, |b| b.iter(|| decompress(black_box(comp_data))));
+    });
+}
+
+criterion_group!(
+    benches,
+    byte_weights,
+    tree_from_weights,
+    compress_bytes,
+    compress_bytes_with_tree,
+    decompress_bytes
+);
+criterion_main!(benches);
@@ -0,0 +1,358 @@
+use super::bitvec::prelude::{BitSlice, Msb0};
+
+use alloc::vec::Vec;
+use core::mem;
+
+/// Struct packing bits into bytes, most significant bit first.
+///
+/// Bits are gathered in a 64-bit accumulator and moved into the
+/// byte buffer a whole byte at a time. A writer can also continue
+/// already packed data, starting in the middle of its last byte
+/// (see [`from_bytes`](#method.from_bytes)).
+///
+/// Used to encode the compressed data in [`compress_with_tree`][compress_with_tree]
+/// and the [`HuffTree`'s][tree] binary representation.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::bits::BitWriter;
+///
+/// let mut writer = BitWriter::new();
+/// writer.write_bits(0b101, 3);
+/// writer.write_bit(true);
+/// writer.write_bits(0xabc, 12);
+/// writer.write_bits(0b11, 2);
+/// assert_eq!(writer.bit_len(), 18);
+///
+/// let (bytes, padding_bits) = writer.finish();
+/// assert_eq!(bytes, vec![0b1011_1010, 0b1011_1100, 0b1100_0000]);
+/// assert_eq!(padding_bits, 6);
+///
+/// // continue writing right after the last written bit
+/// let mut writer = BitWriter::from_bytes(bytes, padding_bits);
+/// writer.write_bits(0b111111, 6);
+/// assert_eq!(writer.finish(), (vec![0b1011_1010, 0b1011_1100, 0b1111_1111], 0));
+/// ```
+///
+/// [compress_with_tree]:crate::comp::compress_with_tree
+/// [tree]:crate::tree::HuffTree
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter{
+    bytes: Vec<u8>,
+    acc: u64,
+    acc_len: u32,
+}
+
+impl BitWriter{
+    /// Initialize a new, empty `BitWriter`
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// Initialize a new, empty `BitWriter`, able to store
+    /// at least byte_capacity bytes without reallocating
+    pub fn with_capacity(byte_capacity: usize) -> Self{
+        Self{
+            bytes: Vec::with_capacity(byte_capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Initialize a `BitWriter` continuing the provided packed bytes,
+    /// whose last byte has padding_bits unused bits. Next bits are
+    /// written in place of the padding.
+    ///
+    /// # Panics
+    /// When providing `padding_bits` larger than 7, or
+    /// nonzero `padding_bits` with empty `bytes`.
+    pub fn from_bytes(mut bytes: Vec<u8>, padding_bits: u8) -> Self{
+        if padding_bits > 7{
+            panic!("padding bits cannot be larger than 7")
+        }
+        if padding_bits == 0{
+            return Self{
+                bytes,
+                ..Self::default()
+            }
+        }
+        let last_byte = bytes.pop().expect("cannot use padding bits in empty bytes");
+        Self{
+            bytes,
+            acc: (last_byte >> padding_bits) as u64,
+            acc_len: 8 - padding_bits as u32,
+        }
+    }
+
+    /// Return the number of bits written so far
+    pub fn bit_len(&self) -> usize{
+        self.bytes.len() * 8 + self.acc_len as usize
+    }
+
+    /// Return true if the next bit will be written at the start of a byte
+    pub fn is_aligned(&self) -> bool{
+        self.acc_len == 0
+    }
+
+    /// Write the lowest count bits of value, most significant first.
+    ///
+    /// # Panics
+    /// When count is larger than 64.
+    pub fn write_bits(&mut self, value: u64, count: u32){
+        if count > 56{
+            if count > 64{
+                panic!("cannot write more than 64 bits at once")
+            }
+            self.write_bits(value >> 32, count - 32);
+            self.write_bits(value, 32);
+            return
+        }
+        if count == 0{
+            return
+        }
+        // acc_len < 8 and count <= 56, so the accumulator can't overflow
+        self.acc = (self.acc << count) | (value & ((1 << count) - 1));
+        self.acc_len += count;
+        while self.acc_len >= 8{
+            self.acc_len -= 8;
+            self.bytes.push((self.acc >> self.acc_len) as u8);
+        }
+        self.acc &= (1 << self.acc_len) - 1;
+    }
+
+    /// Write a single bit
+    pub fn write_bit(&mut self, bit: bool){
+        self.write_bits(bit as u64, 1);
+    }
+
+    /// Write every bit in the provided [`BitSlice`][BitSlice]
+    pub fn write_bitslice(&mut self, bits: &BitSlice<Msb0, u8>){
+        for chunk in bits.chunks(56){
+            let value = chunk.iter().fold(0, |value, bit| (value << 1) | *bit as u64);
+            self.write_bits(value, chunk.len() as u32);
+        }
+    }
+
+    /// Write every bit of the provided bytes
+    pub fn write_bytes(&mut self, bytes: &[u8]){
+        if self.is_aligned(){
+            self.bytes.extend_from_slice(bytes);
+        }
+        else{
+            for byte in bytes{
+                self.write_bits(*byte as u64, 8);
+            }
+        }
+    }
+
+    /// Append already packed bytes, whose last byte has
+    /// padding_bits unused bits, right after the last written bit
+    ///
+    /// # Panics
+    /// When providing `padding_bits` larger than 7, or
+    /// nonzero `padding_bits` with empty `bytes`.
+    pub fn append(&mut self, bytes: &[u8], padding_bits: u8){
+        if padding_bits > 7{
+            panic!("padding bits cannot be larger than 7")
+        }
+        match bytes.split_last(){
+            Some((last_byte, bytes)) =>{
+                self.write_bytes(bytes);
+                self.write_bits((last_byte >> padding_bits) as u64, 8 - padding_bits as u32);
+            }
+            None if padding_bits != 0 =>
+                panic!("cannot use padding bits in empty bytes"),
+            None => (),
+        }
+    }
+
+    /// Write 0s up to the next byte boundary, returning their number
+    pub fn align(&mut self) -> u8{
+        let padding_bits = (8 - self.acc_len) % 8;
+        self.write_bits(0, padding_bits);
+        padding_bits as u8
+    }
+
+    /// Take every completely written byte out of the writer,
+    /// leaving the bits of a partially written byte in it.
+    ///
+    /// Useful when writing a long stream in parts.
+    pub fn take_bytes(&mut self) -> Vec<u8>{
+        mem::take(&mut self.bytes)
+    }
+
+    /// [Align](#method.align) the written bits and return them as bytes,
+    /// along with the number of bits used for padding in the last byte
+    pub fn finish(mut self) -> (Vec<u8>, u8){
+        let padding_bits = self.align();
+        (self.bytes, padding_bits)
+    }
+}
+
+/// Struct reading bits from a byte slice, most significant bit first.
+///
+/// It never reads past the given bit length, so the padding
+/// in the last byte is not treated as data.
+///
+/// Used to decode the compressed data in [`decompress`][decompress]
+/// and the [`HuffTree`'s][tree] binary representation.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::bits::BitReader;
+///
+/// let bytes = [0b1011_1010, 0b1011_1100, 0b1100_0000];
+/// let mut reader = BitReader::new(&bytes, 6);
+///
+/// assert_eq!(reader.read_bits(3), Some(0b101));
+/// assert_eq!(reader.read_bit(), Some(true));
+/// assert_eq!(reader.peek_bits(12), Some(0xabc));
+/// assert_eq!(reader.read_bits(12), Some(0xabc));
+/// assert_eq!(reader.bits_left(), 2);
+///
+/// // the padding is not read
+/// assert_eq!(reader.read_bits(3), None);
+/// assert_eq!(reader.read_bits(2), Some(0b11));
+/// assert!(reader.is_empty());
+/// ```
+///
+/// [decompress]:crate::comp::decompress
+/// [tree]:crate::tree::HuffTree
+#[derive(Debug, Clone)]
+pub struct BitReader<'a>{
+    bytes: &'a [u8],
+    position: usize,
+    bit_len: usize,
+}
+
+impl<'a> BitReader<'a>{
+    /// Initialize a `BitReader` reading the provided bytes,
+    /// except for the padding_bits last bits.
+    ///
+    /// # Panics
+    /// When providing `padding_bits` larger than 7, or
+    /// nonzero `padding_bits` with empty `bytes`.
+    pub fn new(bytes: &'a [u8], padding_bits: u8) -> Self{
+        if padding_bits > 7{
+            panic!("padding bits cannot be larger than 7")
+        }
+        if bytes.is_empty() && padding_bits != 0{
+            panic!("cannot use padding bits in empty bytes")
+        }
+        Self::with_bit_len(bytes, bytes.len() * 8 - padding_bits as usize)
+    }
+
+    /// Initialize a `BitReader` reading the first bit_len bits of the provided bytes
+    ///
+    /// # Panics
+    /// When `bit_len` is larger than the number of bits in `bytes`.
+    pub fn with_bit_len(bytes: &'a [u8], bit_len: usize) -> Self{
+        if bit_len > bytes.len() * 8{
+            panic!("bit_len larger than the number of bits in bytes")
+        }
+        Self{
+            bytes,
+            position: 0,
+            bit_len,
+        }
+    }
+
+    /// Return the number of bits read so far
+    pub fn position(&self) -> usize{
+        self.position
+    }
+
+    /// Return the number of bits left to read
+    pub fn bits_left(&self) -> usize{
+        self.bit_len - self.position
+    }
+
+    /// Return true if there are no bits left to read
+    pub fn is_empty(&self) -> bool{
+        self.bits_left() == 0
+    }
+
+    /// Return true if the next bit is at the start of a byte
+    pub fn is_aligned(&self) -> bool{
+        self.position.is_multiple_of(8)
+    }
+
+    /// Read the next bit,
+    /// returning [`None`][None] if there are no bits left.
+    pub fn read_bit(&mut self) -> Option<bool>{
+        if self.is_empty(){
+            return None
+        }
+        let bit = (self.bytes[self.position / 8] >> (7 - self.position % 8)) & 1 == 1;
+        self.position += 1;
+        Some(bit)
+    }
+
+    /// Return the next count bits (in the lowest bits of the returned number)
+    /// without moving past them, or [`None`][None] if fewer bits are left.
+    ///
+    /// # Panics
+    /// When count is larger than 64.
+    pub fn peek_bits(&self, count: u32) -> Option<u64>{
+        if count > 64{
+            panic!("cannot read more than 64 bits at once")
+        }
+        if count as usize > self.bits_left(){
+            return None
+        }
+        if count == 0{
+            return Some(0)
+        }
+        // the bits span at most 9 bytes
+        let offset = (self.position % 8) as u32;
+        let first_byte = self.position / 8;
+        let byte_count = (offset + count).div_ceil(8) as usize;
+        let loaded = self.bytes[first_byte..first_byte + byte_count]
+            .iter()
+            .fold(0u128, |loaded, byte| (loaded << 8) | *byte as u128);
+        let value = loaded >> (byte_count as u32 * 8 - offset - count);
+        Some((value & ((1 << count) - 1)) as u64)
+    }
+
+    /// Read the next count bits (returned in the lowest bits of the number),
+    /// or return [`None`][None] reading nothing, if fewer bits are left.
+    ///
+    /// # Panics
+    /// When count is larger than 64.
+    pub fn read_bits(&mut self, count: u32) -> Option<u64>{
+        let value = self.peek_bits(count)?;
+        self.position += count as usize;
+        Some(value)
+    }
+
+    /// Move past the next count bits, returning false
+    /// (and moving nowhere) if fewer bits are left.
+    pub fn skip_bits(&mut self, count: usize) -> bool{
+        if count > self.bits_left(){
+            return false
+        }
+        self.position += count;
+        true
+    }
+
+    /// Move to the next byte boundary (or the end of bits),
+    /// returning the number of skipped bits
+    pub fn align(&mut self) -> u8{
+        let skipped = ((8 - self.position % 8) % 8).min(self.bits_left());
+        self.position += skipped;
+        skipped as u8
+    }
+
+    /// Read the next byte_count whole bytes without copying them, returning
+    /// [`None`][None] if the reader is not [aligned](#method.is_aligned)
+    /// or fewer bytes are left.
+    pub fn read_aligned_bytes(&mut self, byte_count: usize) -> Option<&'a [u8]>{
+        if !self.is_aligned() || byte_count * 8 > self.bits_left(){
+            return None
+        }
+        let first_byte = self.position / 8;
+        self.position += byte_count * 8;
+        Some(&self.bytes[first_byte..first_byte + byte_count])
+    }
+}
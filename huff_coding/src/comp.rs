@@ -1,23 +1,43 @@
 use super::{
     prelude::{
         HuffTree,
+        HuffCodes,
+        FlatTree,
         HuffLetter,
         HuffLetterAsBytes,
-        build_weights_map,
     },
-    utils::calc_padding_bits,
+    weights::Weights,
+    map::count_letters,
+    bits::{BitReader, BitWriter},
+    utils::{
+        calc_padding_bits,
+        size_of_bits,
+    },
     bitvec::prelude::BitVec,
 };
 use self::errors::{
     CompressError,
     CompressedDataFromBytesError,
+    DecompressError,
 };
 
-use std::{
+use alloc::{
+    borrow::Cow,
+    vec::Vec,
+};
+use core::{
     convert::TryInto,
     marker::PhantomData,
+    mem,
 };
 
+/// Flag set in the first byte of the byte representation of [`PackedData`][PackedData],
+/// marking that it stores uncompressed letters
+pub const STORED_FLAG: u8 = 0b1000_0000;
+
+/// Length of the header (padding bits, tree length and letter count) at
+/// the beginning of the [`CompressData::to_bytes`][CompressData::to_bytes] representation
+pub(crate) const HEADER_LEN: usize = 13;
 
 
 /// Data representing a slice of letters (types implementing [`HuffLetter`][letter]) 
@@ -28,12 +48,18 @@ use std::{
 /// * `L` -> generic type of the compressed letters
 /// * [`comp_bytes`](#method.comp_bytes) -> representing the compressed slice
 /// * [`padding_bits`](#method.padding_bits) -> the number of bits used for padding in the comp_bytes
+/// * [`letter_count`](#method.letter_count) -> the number of letters in the compressed slice
 /// * [`huff_tree`](#method.huff_tree) -> the [`HuffTree`][tree] used to compress the slice
 /// 
 /// If the letter type also implements [`HuffLetterAsBytes`][letter_bytes], the compressed
 /// data can be easily represented as bytes (see the [`to_bytes`](#method.to_bytes) method's 
 /// docs for more information).
 /// 
+/// With the `serde` feature enabled, it can also be serialized as a struct of the
+/// above (the tree in its compact serialized form, see [`HuffTree`][tree]).
+/// Deserializing checks that the compressed bytes decompress into exactly 
+/// letter_count letters with the tree, so that [`decompress`][decompress] never fails on it.
+/// 
 /// [tree]:crate::tree::HuffTree
 /// [letter]:crate::tree::letter::HuffLetter
 /// [letter_bytes]:crate::tree::letter::HuffLetterAsBytes
@@ -41,18 +67,23 @@ use std::{
 pub struct CompressData<L: HuffLetter>{
     comp_bytes: Vec<u8>,
     padding_bits: u8,
+    letter_count: usize,
     huff_tree: HuffTree<L>,
     _typebind: PhantomData<L>
 }
 
 impl<L: HuffLetter> CompressData<L>{
     /// Initialize a new instance of `CompressData` with the provided
-    /// compressed bytes, padding bits and [`HuffTree`][crate::tree::HuffTree].
+    /// compressed bytes, padding bits, number of compressed letters 
+    /// and [`HuffTree`][crate::tree::HuffTree].
+    /// 
+    /// Whether the compressed bytes actually store letter_count letters
+    /// is only checked while decompressing.
     /// 
     /// # Panics
     /// When providing an empty `comp_bytes` or
     /// when providing `padding_bits` larger than 7.
-    pub fn new(comp_bytes: Vec<u8>, padding_bits: u8, huff_tree: HuffTree<L>) -> Self{
+    pub fn new(comp_bytes: Vec<u8>, padding_bits: u8, letter_count: usize, huff_tree: HuffTree<L>) -> Self{
         if comp_bytes.is_empty(){
             panic!("provided comp_bytes are empty")
         }
@@ -62,8 +93,9 @@ impl<L: HuffLetter> CompressData<L>{
         Self{
             comp_bytes,
             padding_bits,
+            letter_count,
             huff_tree,
-            _typebind: PhantomData::default(),
+            _typebind: PhantomData,
         }
     }
 
@@ -77,14 +109,101 @@ impl<L: HuffLetter> CompressData<L>{
         self.padding_bits
     }
 
+    /// Return the number of letters in the compressed slice
+    pub fn letter_count(&self) -> usize{
+        self.letter_count
+    }
+
     /// Return a reference to the [`HuffTree`][crate::tree::HuffTree] used to compress the slice
     pub fn huff_tree(&self) -> &HuffTree<L>{
         &self.huff_tree
     }
 
-    /// Consume `self` returning the ownership of `comp_bytes`, `padding_bits` and `huff_tree`
-    pub fn into_inner(self) -> (Vec<u8>, u8, HuffTree<L>){
-        (self.comp_bytes, self.padding_bits, self.huff_tree)
+    /// Consume `self` returning the ownership of `comp_bytes`, `padding_bits`, `letter_count` and `huff_tree`
+    pub fn into_inner(self) -> (Vec<u8>, u8, usize, HuffTree<L>){
+        (self.comp_bytes, self.padding_bits, self.letter_count, self.huff_tree)
+    }
+
+    /// Return a [`CompressDataRef`][CompressDataRef] borrowing
+    /// the compressed bytes and the [`HuffTree`][crate::tree::HuffTree] from `self`
+    pub fn as_data_ref(&self) -> CompressDataRef<'_, L>{
+        CompressDataRef{
+            comp_bytes: &self.comp_bytes,
+            padding_bits: self.padding_bits,
+            letter_count: self.letter_count,
+            huff_tree: Cow::Borrowed(&self.huff_tree),
+        }
+    }
+
+    /// Decompress the stored letters into the provided buffer,
+    /// returning the number of letters written.
+    /// 
+    /// See [`CompressDataRef::decompress_into`][CompressDataRef::decompress_into].
+    pub fn decompress_into(&self, buf: &mut [L]) -> Result<usize, DecompressError>{
+        self.as_data_ref().decompress_into(buf)
+    }
+
+    /// Decompress the stored letters, appending them to the provided [`Vec`][Vec],
+    /// returning the number of letters appended.
+    /// 
+    /// See [`CompressDataRef::decompress_to_vec`][CompressDataRef::decompress_to_vec].
+    pub fn decompress_to_vec(&self, vec: &mut Vec<L>) -> Result<usize, DecompressError>{
+        self.as_data_ref().decompress_to_vec(vec)
+    }
+}
+
+/// Serialized form of `CompressData`, borrowing its fields
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+#[serde(rename = "CompressData")]
+struct CompressDataRepr<'a, L: HuffLetter>{
+    comp_bytes: &'a [u8],
+    padding_bits: u8,
+    letter_count: usize,
+    huff_tree: &'a HuffTree<L>,
+}
+
+/// Deserialized form of `CompressData`, to be validated
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "CompressData")]
+struct OwnedCompressDataRepr<L: HuffLetter>{
+    comp_bytes: Vec<u8>,
+    padding_bits: u8,
+    letter_count: usize,
+    huff_tree: HuffTree<L>,
+}
+
+#[cfg(feature = "serde")]
+impl<L: HuffLetter + serde::Serialize> serde::Serialize for CompressData<L>{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>{
+        CompressDataRepr{
+            comp_bytes: &self.comp_bytes,
+            padding_bits: self.padding_bits,
+            letter_count: self.letter_count,
+            huff_tree: &self.huff_tree,
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, L: HuffLetter + serde::Deserialize<'de>> serde::Deserialize<'de> for CompressData<L>{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>{
+        use serde::de::Error;
+
+        let repr = OwnedCompressDataRepr::<L>::deserialize(deserializer)?;
+        if repr.comp_bytes.is_empty(){
+            return Err(D::Error::custom("serialized CompressData's comp_bytes are empty"))
+        }
+        if repr.padding_bits > 7{
+            return Err(D::Error::custom("serialized CompressData's padding bits are larger than 7"))
+        }
+        let comp_data = CompressData::new(repr.comp_bytes, repr.padding_bits, repr.letter_count, repr.huff_tree);
+
+        // make sure that it decompresses
+        decode_letters(&comp_data.as_data_ref(), |_| ())
+            .map_err(|err| D::Error::custom(alloc::format!("serialized CompressData is invalid: {}", err)))?;
+        Ok(comp_data)
     }
 }
 
@@ -118,69 +237,16 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
     /// # Errors
     /// ---
     /// 1. When the provided slice is too short to read padding, tree length, tree
-    /// and data
+    ///    and data
     /// 2. When the stored tree length is lower than 2 (a [`HuffTree`][tree] with padding 
-    /// can't be encoded in less than 2 bytes)
+    ///    can't be encoded in less than 2 bytes), or the stored letter count doesn't fit in a `usize`
     /// 3. When the [`HuffTree`][tree] stored in the bytes is invalid or has a different letter type
-    /// than specified
+    ///    than specified
+    /// 4. When the bytes store uncompressed letters (see [`PackedData`][PackedData])
     /// 
     /// [tree]:crate::tree::HuffTree
     pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, CompressedDataFromBytesError>{
-        /// Returns DecompressError with the given message 
-        /// if the index is out of bounds of bytes
-        macro_rules! bytes_try_get {
-            [$index:expr; $message:expr] => {
-                if let Some(subslice) = bytes.get($index){
-                    Ok(subslice)
-                }
-                else{
-                    Err(CompressedDataFromBytesError::new($message))
-                }
-            };
-        }
-
-        // get padding data
-        let padding_bits = bytes_try_get![0; "slice is empty"]?;
-        let tree_padding_bits =  padding_bits >> 4;
-        let data_padding_bits = padding_bits & 0b0000_1111;
-
-        // read 4 bytes of tree length
-        let tree_len = u32::from_be_bytes(
-            bytes_try_get![1..5; "slice too short to read tree length"]?
-            .try_into()
-            .unwrap()
-        ) as usize;
-        if tree_len < 2{
-            panic!("stored tree length must be at least 2");
-        } 
-
-        // read the tree
-        let tree_from_bin_result = 
-            HuffTree::<L>::try_from_bin({
-                let mut b = BitVec::from_vec(
-                    bytes_try_get![5..5 + tree_len; "slice too short to read tree"]?
-                    .to_vec()
-                );
-                for _ in 0..tree_padding_bits{b.pop();}
-                b
-            });
-        let tree = 
-            if let Ok(tree) = tree_from_bin_result{
-                tree
-            }
-            else{
-                return Err(
-                    CompressedDataFromBytesError::new(
-                        "invalid tree in slice"
-                    )
-                )
-            };
-
-        Ok(CompressData::new(
-            bytes_try_get![5 + tree_len..; "slice does not contain compressed data"]?.to_vec(), 
-            data_padding_bits,
-            tree
-        ))
+        Ok(CompressDataRef::try_from_bytes(bytes)?.into_owned())
     }
 
     /// Convert the `CompressData` into a byte representation.
@@ -194,15 +260,17 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
     ///  * first 4 bits store the [HuffTree's][tree] padding bits
     ///  * the remaining bits store the compressed data's padding bits
     /// 2. 4 byte number representing the length (in bytes) of the stored [`HuffTree`][tree]
-    /// 3. A [`HuffTree`][tree], used to compress the file, 
-    /// represented in binary (see [`HuffTree::try_from_bin`][from_bin])
-    /// 4. The actual compressed data
+    /// 3. 8 byte number representing the number of compressed letters
+    /// 4. A [`HuffTree`][tree], used to compress the file, 
+    ///    represented in binary (see [`HuffTree::try_from_bin`][from_bin])
+    /// 5. The actual compressed data
     /// 
     /// # Example
     /// –––
     /// Here's a manual deconstruction of the compressed
     /// data:
     /// ```
+    /// # #[cfg(feature = "std")]{
     /// use huff_coding::{
     ///     prelude::{
     ///         compress,
@@ -235,6 +303,13 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
     ///     4
     /// );
     /// 
+    /// // the next 8 bytes store the number of compressed letters,
+    /// // in this case: 6
+    /// assert_eq!(
+    ///     u64::from_be_bytes(compressed_data_bytes[5..13].try_into().unwrap()), 
+    ///     6
+    /// );
+    /// 
     /// // next 4 bytes (as read from the length) store the tree,
     /// // in this case it stores the following codes:
     /// // b'a' - 10
@@ -243,7 +318,7 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
     /// let codes = 
     ///     HuffTree::<u8>::try_from_bin({
     ///         // get the next 4 bytes from compressed_data_bytes and remove the specified 3 padding bits
-    ///         let mut b = BitVec::from_vec(compressed_data_bytes[5..9].to_vec());
+    ///         let mut b = BitVec::from_vec(compressed_data_bytes[13..17].to_vec());
     ///         b.drain(29..);
     ///         b
     ///     })
@@ -258,8 +333,9 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
     /// assert_eq!(codes, cmp_codes);
     /// 
     /// // the last bytes (containing the compressed data) are:
-    /// assert_eq!(compressed_data_bytes[9], 0b10111100);
-    /// assert_eq!(compressed_data_bytes[10], 0b00000000);
+    /// assert_eq!(compressed_data_bytes[17], 0b10111100);
+    /// assert_eq!(compressed_data_bytes[18], 0b00000000);
+    /// # }
     /// ```
     /// now we could easily read the actual data:
     /// 1. 10111100:
@@ -270,7 +346,7 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
     ///  * 0  -> `b'c'`
     /// 2. 00000000:
     ///  * 0  -> `b'c'`
-    ///  * the remaining 7 bits are used for padding.
+    ///  * we've read 6 letters, so the remaining 7 bits are used for padding.
     /// 
     /// And thus we succesfully read the bytes `b"abbccc"`!
     /// 
@@ -291,6 +367,10 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
         bytes.extend(
             tree_bytes_len.to_be_bytes().iter()
         );
+        // push the letter count (8 byte num)
+        bytes.extend(
+            (self.letter_count() as u64).to_be_bytes().iter()
+        );
         // next push the tree in binary
         bytes.append(&mut tree_bin.into_vec());
         
@@ -300,16 +380,463 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
     }
 }
 
+/// A view of [`CompressData`][CompressData], borrowing the compressed bytes
+/// (and possibly the [`HuffTree`][tree]) instead of owning them.
+/// 
+/// It can be read from bytes without copying the compressed data 
+/// (see [`try_from_bytes`](#method.try_from_bytes)), or borrowed 
+/// from [`CompressData`][CompressData] with [`as_data_ref`][as_data_ref],
+/// and decompressed into caller provided buffers.
+/// 
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::prelude::{
+///     CompressDataRef,
+///     compress,
+/// };
+/// 
+/// let comp_data_bytes = compress(b"abbccc").to_bytes();
+/// 
+/// // only the tree is parsed, the compressed bytes are borrowed
+/// let comp_data_ref = CompressDataRef::<u8>::try_from_bytes(&comp_data_bytes).unwrap();
+/// 
+/// let mut buf = [0; 16];
+/// let letter_count = comp_data_ref.decompress_into(&mut buf).unwrap();
+/// assert_eq!(&buf[..letter_count], b"abbccc");
+/// 
+/// // the vec is reused
+/// let mut vec = Vec::new();
+/// for _ in 0..3{
+///     vec.clear();
+///     comp_data_ref.decompress_to_vec(&mut vec).unwrap();
+///     assert_eq!(vec, b"abbccc");
+/// }
+/// ```
+/// 
+/// [tree]:crate::tree::HuffTree
+/// [as_data_ref]:CompressData::as_data_ref
+#[derive(Debug, Clone)]
+pub struct CompressDataRef<'a, L: HuffLetter>{
+    comp_bytes: &'a [u8],
+    padding_bits: u8,
+    letter_count: usize,
+    huff_tree: Cow<'a, HuffTree<L>>,
+}
+
+impl<'a, L: HuffLetter> CompressDataRef<'a, L>{
+    /// Initialize a new instance of `CompressDataRef` with the provided
+    /// compressed bytes, padding bits, number of compressed letters 
+    /// and (borrowed or owned) [`HuffTree`][crate::tree::HuffTree].
+    /// 
+    /// # Panics
+    /// When providing an empty `comp_bytes` or
+    /// when providing `padding_bits` larger than 7.
+    pub fn new(comp_bytes: &'a [u8], padding_bits: u8, letter_count: usize, huff_tree: Cow<'a, HuffTree<L>>) -> Self{
+        if comp_bytes.is_empty(){
+            panic!("provided comp_bytes are empty")
+        }
+        if padding_bits > 7{
+            panic!("padding bits cannot be larger than 7")
+        }
+        Self{
+            comp_bytes,
+            padding_bits,
+            letter_count,
+            huff_tree,
+        }
+    }
+
+    /// Return a reference to the borrowed slice compressed into bytes
+    pub fn comp_bytes(&self) -> &'a [u8]{
+        self.comp_bytes
+    }
+
+    /// Return the number of bits used for padding in the compressed slice
+    pub fn padding_bits(&self) -> u8{
+        self.padding_bits
+    }
+
+    /// Return the number of letters in the compressed slice
+    pub fn letter_count(&self) -> usize{
+        self.letter_count
+    }
+
+    /// Return a reference to the [`HuffTree`][crate::tree::HuffTree] used to compress the slice
+    pub fn huff_tree(&self) -> &HuffTree<L>{
+        &self.huff_tree
+    }
+
+    /// Copy the borrowed data into an owned [`CompressData`][CompressData]
+    pub fn into_owned(self) -> CompressData<L>{
+        CompressData::new(
+            self.comp_bytes.to_vec(),
+            self.padding_bits,
+            self.letter_count,
+            self.huff_tree.into_owned()
+        )
+    }
+
+    /// Decompress the letters into the provided buffer, 
+    /// returning the number of letters written at its beginning
+    /// (always equal to [`letter_count`](#method.letter_count)).
+    /// 
+    /// # Errors
+    /// ---
+    /// 1. When the buffer is shorter than [`letter_count`](#method.letter_count)
+    /// 2. When the compressed bytes don't store exactly [`letter_count`](#method.letter_count)
+    ///    letters, or their last code is cut off. The letters decoded 
+    ///    before finding that out are still written into the buffer.
+    pub fn decompress_into(&self, buf: &mut [L]) -> Result<usize, DecompressError>{
+        let buf = buf.get_mut(..self.letter_count).ok_or_else(||
+            DecompressError::new("buffer too short to hold every decompressed letter")
+        )?;
+        let mut slots = buf.iter_mut();
+        decode_letters(self, |letter|{
+            // decode_letters never emits more than letter_count letters
+            *slots.next().unwrap() = letter.clone();
+        })?;
+        Ok(self.letter_count)
+    }
+
+    /// Decompress the letters, appending them to the provided [`Vec`][Vec]
+    /// and returning the number of letters appended (always equal to 
+    /// [`letter_count`](#method.letter_count)).
+    /// 
+    /// The vec is reserved space for [`letter_count`](#method.letter_count)
+    /// letters beforehand, so it's never reallocated while decompressing.
+    /// 
+    /// # Errors
+    /// ---
+    /// When the compressed bytes don't store exactly [`letter_count`](#method.letter_count)
+    /// letters, or their last code is cut off. The vec is then left as it was.
+    pub fn decompress_to_vec(&self, vec: &mut Vec<L>) -> Result<usize, DecompressError>{
+        // every code is at least 1 bit long, so a larger letter count can't be right,
+        // and reserving space for it could abort on a huge count read from bytes
+        let bit_count = (self.comp_bytes.len() as u128) * 8 - self.padding_bits as u128;
+        if self.letter_count as u128 > bit_count{
+            return Err(DecompressError::new("compressed data stores fewer letters than its letter count"))
+        }
+        let prev_len = vec.len();
+        vec.reserve(self.letter_count);
+        decode_letters(self, |letter| vec.push(letter.clone()))
+            .inspect_err(|_| vec.truncate(prev_len))?;
+        Ok(self.letter_count)
+    }
+}
+
+impl<'a, L: HuffLetterAsBytes> CompressDataRef<'a, L>{
+    /// Try to construct `CompressDataRef<L>` from the given byte representation 
+    /// (see [`CompressData::to_bytes`][CompressData::to_bytes]), borrowing 
+    /// the compressed data instead of copying it.
+    /// 
+    /// # Errors
+    /// ---
+    /// The same as in [`CompressData::try_from_bytes`][CompressData::try_from_bytes].
+    pub fn try_from_bytes(bytes: &'a [u8]) -> Result<Self, CompressedDataFromBytesError>{
+        let parts = split_bytes(bytes).map_err(CompressedDataFromBytesError::new)?;
+
+        // read the tree
+        let mut tree_bin = BitVec::from_vec(parts.tree_bytes.to_vec());
+        for _ in 0..parts.tree_padding_bits{tree_bin.pop();}
+        let tree = HuffTree::<L>::try_from_bin(tree_bin)
+            .map_err(|_| CompressedDataFromBytesError::new("invalid tree in slice"))?;
+
+        Ok(CompressDataRef::new(
+            parts.comp_bytes,
+            parts.data_padding_bits,
+            parts.letter_count,
+            Cow::Owned(tree)
+        ))
+    }
+}
+
+/// Parts of the byte representation of [`CompressData`][CompressData], 
+/// borrowed from it
+struct ByteParts<'a>{
+    tree_bytes: &'a [u8],
+    tree_padding_bits: u8,
+    comp_bytes: &'a [u8],
+    data_padding_bits: u8,
+    letter_count: usize,
+}
+
+/// Split the byte representation of [`CompressData`][CompressData] into its parts,
+/// (see [`CompressData::to_bytes`][CompressData::to_bytes]), returning the error's message
+/// if they can't be read
+fn split_bytes(bytes: &[u8]) -> Result<ByteParts<'_>, &'static str>{
+    // get padding data
+    let padding_bits = *bytes.first().ok_or("slice is empty")?;
+    if padding_bits & STORED_FLAG != 0{
+        return Err("slice stores uncompressed letters")
+    }
+    let tree_padding_bits = padding_bits >> 4;
+    let data_padding_bits = padding_bits & 0b0000_1111;
+
+    // read 4 bytes of tree length
+    let tree_len = u32::from_be_bytes(
+        bytes.get(1..5)
+            .ok_or("slice too short to read tree length")?
+            .try_into()
+            .unwrap()
+    ) as usize;
+
+    // read 8 bytes of letter count
+    let letter_count = u64::from_be_bytes(
+        bytes.get(5..HEADER_LEN)
+            .ok_or("slice too short to read letter count")?
+            .try_into()
+            .unwrap()
+    )
+    .try_into()
+    .map_err(|_| "stored letter count doesn't fit in usize")?;
+    if tree_len < 2{
+        return Err("stored tree length must be at least 2")
+    }
+
+    // checked, as the header and tree may not fit in a 32-bit usize
+    let tree_end = HEADER_LEN.checked_add(tree_len)
+        .ok_or("stored tree length doesn't fit in usize")?;
+    let tree_bytes = bytes.get(HEADER_LEN..tree_end)
+        .ok_or("slice too short to read tree")?;
+    let comp_bytes = &bytes[tree_end..];
+    if comp_bytes.is_empty(){
+        return Err("slice does not contain compressed data")
+    }
+    if data_padding_bits > 7{
+        return Err("invalid data padding bits")
+    }
+
+    Ok(ByteParts{
+        tree_bytes,
+        tree_padding_bits,
+        comp_bytes,
+        data_padding_bits,
+        letter_count,
+    })
+}
+
+/// Letters (types implementing [`HuffLetterAsBytes`][letter_bytes]) either compressed into
+/// [`CompressData`][CompressData], or stored as they are, when compressing them wouldn't make
+/// their byte representation any shorter.
+/// 
+/// Returned by the [`compress_or_store`][compress_or_store] function.
+/// 
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::prelude::{
+///     PackedData,
+///     compress_or_store,
+/// };
+/// 
+/// let skewed = b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabc";
+/// let random = [0x3d, 0xf1, 0x08, 0x9a, 0x77, 0xc2];
+/// 
+/// let packed_skewed = compress_or_store(skewed);
+/// let packed_random = compress_or_store(&random);
+/// 
+/// assert!(!packed_skewed.is_stored());
+/// assert!(packed_random.is_stored());
+/// 
+/// // both can be read back from bytes the same way
+/// for (letters, packed) in [(&skewed[..], packed_skewed), (&random[..], packed_random)].iter(){
+///     let packed_bytes = packed.to_bytes();
+///     assert_eq!(
+///         PackedData::<u8>::try_from_bytes(&packed_bytes).unwrap().unpack().unwrap(),
+///         letters.to_vec()
+///     );
+/// }
+/// ```
+/// 
+/// [letter_bytes]:crate::tree::letter::HuffLetterAsBytes
+#[derive(Debug, Clone)]
+pub enum PackedData<L: HuffLetterAsBytes>{
+    /// Letters compressed with a [`HuffTree`][crate::tree::HuffTree]
+    Compressed(CompressData<L>),
+    /// Letters stored as they are
+    Stored(Vec<L>),
+}
+
+impl<L: HuffLetterAsBytes> PackedData<L>{
+    /// Return true if the letters are stored uncompressed
+    pub fn is_stored(&self) -> bool{
+        matches!(self, PackedData::Stored(_))
+    }
+
+    /// Return the packed letters, decompressing them if needed
+    /// 
+    /// # Errors
+    /// ---
+    /// When the letters are compressed, but [`decompress_to_vec`][to_vec] fails to decompress them
+    /// (like when they were read from corrupted bytes).
+    /// 
+    /// [to_vec]:CompressData::decompress_to_vec
+    pub fn unpack(&self) -> Result<Vec<L>, DecompressError>{
+        match self{
+            PackedData::Compressed(comp_data) =>{
+                let mut letters = Vec::new();
+                comp_data.decompress_to_vec(&mut letters)?;
+                Ok(letters)
+            }
+            PackedData::Stored(letters) => Ok(letters.clone()),
+        }
+    }
+
+    /// Try to construct `PackedData<L>` from the given byte representation.
+    /// 
+    /// Use [`to_bytes`](#method.to_bytes) to get the byte representation of the `PackedData`.
+    /// 
+    /// # Errors
+    /// ---
+    /// 1. When the bytes store compressed data, which 
+    ///    [`CompressData::try_from_bytes`][from_bytes] fails to read
+    /// 2. When the stored letters' bytes cannot be split 
+    ///    into letters of type `L`
+    /// 
+    /// [from_bytes]:CompressData::try_from_bytes
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, CompressedDataFromBytesError>{
+        match bytes.first(){
+            Some(flags) if flags & STORED_FLAG != 0 =>{
+                let letter_bytes = &bytes[1..];
+                let letter_size = mem::size_of::<L>();
+                if !letter_bytes.len().is_multiple_of(letter_size){
+                    return Err(CompressedDataFromBytesError::new(
+                        "stored letters' length is not a multiple of the letter size"
+                    ))
+                }
+
+                let mut letters = Vec::with_capacity(letter_bytes.len() / letter_size);
+                for letter in letter_bytes.chunks_exact(letter_size){
+                    letters.push(
+                        L::try_from_be_bytes(letter)
+                        .map_err(|_| CompressedDataFromBytesError::new("invalid stored letter"))?
+                    );
+                }
+                Ok(PackedData::Stored(letters))
+            }
+            _ =>
+                Ok(PackedData::Compressed(CompressData::try_from_bytes(bytes)?)),
+        }
+    }
+
+    /// Convert the `PackedData` into a byte representation.
+    /// 
+    /// Use [`try_from_bytes`](#method.try_from_bytes) to convert it back into `PackedData`.
+    /// 
+    /// # Encoding scheme
+    /// ---
+    /// Compressed letters are encoded just like in [`CompressData::to_bytes`][to_bytes],
+    /// while stored letters are encoded as:
+    /// 1. A byte with only the highest bit set ([`STORED_FLAG`][STORED_FLAG]), which can't be 
+    ///    set in the [`CompressData`'s][CompressData] padding byte, as both padding bit numbers are at most 7
+    /// 2. Every letter converted into bytes (see [`HuffLetterAsBytes`][letter_bytes])
+    /// 
+    /// [to_bytes]:CompressData::to_bytes
+    /// [letter_bytes]:crate::tree::letter::HuffLetterAsBytes
+    pub fn to_bytes(&self) -> Vec<u8>{
+        match self{
+            PackedData::Compressed(comp_data) => comp_data.to_bytes(),
+            PackedData::Stored(letters) =>{
+                let mut bytes = Vec::with_capacity(stored_len::<L>(letters.len() as u128) as usize);
+                bytes.push(STORED_FLAG);
+                for letter in letters{
+                    bytes.extend(letter.as_be_bytes().iter());
+                }
+                bytes
+            }
+        }
+    }
+}
+
+/// Return the exact length (in bytes) of the [`CompressData::to_bytes`][to_bytes] 
+/// representation of the letters counted in the provided [`Weights`][weights], 
+/// if they were compressed with the provided [`HuffTree`][tree].
+/// 
+/// Returns [`None`][None] if any letter with a nonzero weight has no code in the tree.
+/// 
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::prelude::{
+///     HuffTree,
+///     ByteWeights,
+///     compress_with_tree,
+///     estimate_compressed_len,
+/// };
+/// 
+/// let bytes = b"abbccc";
+/// let weights = ByteWeights::from_bytes(bytes);
+/// let tree = HuffTree::from_weights(weights);
+/// 
+/// let estimate = estimate_compressed_len(&tree, &weights).unwrap();
+/// let comp_data = compress_with_tree(bytes, tree).unwrap();
+/// 
+/// assert_eq!(estimate, comp_data.to_bytes().len() as u128);
+/// ```
+/// 
+/// [to_bytes]:CompressData::to_bytes
+/// [tree]:crate::tree::HuffTree
+/// [weights]:crate::weights::Weights
+pub fn estimate_compressed_len<L: HuffLetterAsBytes, W: Weights<L>>(huff_tree: &HuffTree<L>, weights: &W) -> Option<u128>{
+    // every letter branch takes 1 bit + the letter's bits
+    // and every joint branch takes 1 bit 
+    let letter_count = huff_tree.letter_count() as u128;
+    let tree_bit_count = letter_count * (1 + size_of_bits::<L>() as u128) + letter_count - 1;
+    let data_bit_count = huff_tree.compressed_bit_count(weights)?;
+
+    Some(HEADER_LEN as u128 + div_ceil_8(tree_bit_count) + div_ceil_8(data_bit_count).max(1))
+}
+
+/// Return the length (in bytes) of the stored [`PackedData`][PackedData] 
+/// representation of letter_count letters
+pub fn stored_len<L: HuffLetterAsBytes>(letter_count: u128) -> u128{
+    1 + letter_count * mem::size_of::<L>() as u128
+}
+
+/// Compress the provided slice of letters (types implementing [`HuffLetterAsBytes`][letter_bytes])
+/// like [`compress`][compress], but only if the compressed data would end up shorter
+/// than the letters themselves, returning them stored as they are otherwise.
+/// 
+/// The decision is made before compressing, by comparing the [estimated length][estimate] 
+/// of the compressed data's byte representation with the length of the uncompressed letters.
+/// 
+/// See [`PackedData`][PackedData] for examples.
+/// 
+/// [letter_bytes]:crate::tree::letter::HuffLetterAsBytes
+/// [estimate]:estimate_compressed_len
+pub fn compress_or_store<L: HuffLetterAsBytes>(letters: &[L]) -> PackedData<L>{
+    if letters.is_empty(){
+        return PackedData::Stored(Vec::new())
+    }
+
+    let weights = count_letters(letters);
+    let huff_tree = HuffTree::from_weights(weights.clone());
+    match estimate_compressed_len(&huff_tree, &weights){
+        Some(comp_len) if comp_len < stored_len::<L>(letters.len() as u128) =>
+            PackedData::Compressed(compress_with_tree(letters, huff_tree).unwrap()),
+        _ =>
+            PackedData::Stored(letters.to_vec()),
+    }
+}
+
+/// Divide the bit count by 8, rounding up
+fn div_ceil_8(bit_count: u128) -> u128{
+    bit_count.div_ceil(8)
+}
+
 
 /// Compress the provided slice of letters (types implementing [`HuffLetter`][letter]), using binary
 /// codes generated with a [`HuffTree`][tree] struct, into a byte slice (returned with additional data
 /// in the form of [`CompressData`][CompressData]).
 /// 
-/// The letters are counted into a [`Weights`][weights] collection to create a [`HuffTree`][tree] using
-/// the [`build_weights_map`][weights_map] function, which can be optimized a lot for certain letter types.
+/// The letters are counted into a [`Weights`][weights] collection to create a [`HuffTree`][tree], 
+/// a table sorted by the letters' hashes (calculated with a fixed FNV-1a hasher), 
+/// which can be optimized a lot for certain letter types.
 /// Because of this fact, it's generally faster to use the [`compress_with_tree`][compress_with_tree] function,
 /// providing a [`HuffTree`][tree] built with our own [`Weights`][weights] collection (an example of such collection
 /// is implemented in the crate on `u8` in the form of [`ByteWeights`][byte_weights]).
+/// The table is always iterated in the same order, so the returned bytes are the same on every run and platform.
 /// 
 /// The returned [`CompressData`][CompressData] can be decompressed into the original letter slice with
 /// the [`decompress`][decompress] function.
@@ -348,10 +875,9 @@ impl<L: HuffLetterAsBytes> CompressData<L>{
 /// [tree]:crate::tree::HuffTree
 /// [letter]:crate::tree::letter::HuffLetter
 /// [weights]:crate::weights::Weights
-/// [weights_map]:crate::weights::build_weights_map
 /// [byte_weights]:crate::weights::ByteWeights
 pub fn compress<L: HuffLetter>(letters: &[L]) -> CompressData<L>{
-    let huff_tree = HuffTree::from_weights(build_weights_map(letters));
+    let huff_tree = HuffTree::from_weights(count_letters(letters));
     compress_with_tree(letters, huff_tree).unwrap()
 }
 
@@ -367,7 +893,8 @@ pub fn compress<L: HuffLetter>(letters: &[L]) -> CompressData<L>{
 /// 
 /// # How it works
 /// ---
-/// It just reads every letter's code in the provided [`HuffTree`][tree] and inserts them into
+/// It just reads every letter's code in the provided [`HuffTree`][tree] (into [`HuffCodes`][codes],
+/// looking the codes of bytes up in a fixed table) and inserts them into
 /// a [`Vec<u8>`][Vec]. The codes themselves are mostly not a multiple of 8 bits long, so some
 /// of them can be used as padding in the last byte. The padding information, as well as
 /// the tree used to compress the slice are included in the returned [`CompressData`][CompressData].
@@ -415,39 +942,61 @@ pub fn compress<L: HuffLetter>(letters: &[L]) -> CompressData<L>{
 /// ```
 /// 
 /// [tree]:crate::tree::HuffTree
+/// [codes]:crate::tree::HuffCodes
 /// [letter]:crate::tree::letter::HuffLetter
 pub fn compress_with_tree<L: HuffLetter>(letters: &[L], huff_tree: HuffTree<L>) -> Result<CompressData<L>, CompressError<L>>{
-    let mut comp_letters = Vec::with_capacity(letters.len());
-    let codes = huff_tree.read_codes();
-    let mut comp_byte = 0b0000_0000;
-    let mut bit_ptr = 7;
-    for letter in letters{
-        // return Err if there's no code
-        let code = 
-            if let Some(code) = codes.get(letter){Ok(code)}
-            else{
-                Err(CompressError::new(
-                    "letter not found in codes", 
-                    letter.clone()))
-            }?;
-        for bit in code{
-            // set bit on current byte
-            comp_byte |= (*bit as u8) << bit_ptr;
-            // if filled comp_byte
-            if bit_ptr == 0{
-                comp_letters.push(comp_byte);
-                comp_byte = 0b0000_0000;
-                bit_ptr = 7;
-            }
-            else{bit_ptr -= 1};
-        }
-    }
-    // calculate the compressed_letters' padding bits
-    let padding_bits = if bit_ptr == 7{0} else{bit_ptr + 1};
-    if padding_bits != 0{comp_letters.push(comp_byte);}
+    let codes = HuffCodes::from_tree(huff_tree);
+    let mut comp_letters = BitWriter::with_capacity(letters.len());
+    codes.encode(letters, &mut comp_letters)?;
+    // get the compressed letters and their padding bits
+    let (comp_letters, padding_bits) = comp_letters.finish();
 
+    Ok(CompressData::new(comp_letters, padding_bits, letters.len(), codes.into_tree()))
+}
 
-    Ok(CompressData::new(comp_letters, padding_bits, huff_tree))
+/// Compress the provided slice of letters (types implementing [`HuffLetter`][letter]) 
+/// like [`compress_with_tree`][compress_with_tree], using already read [`HuffCodes`][codes].
+/// 
+/// The codes are not read again, so it's faster when compressing many slices with the same codes,
+/// but their [`HuffTree`][tree] is cloned into every returned [`CompressData`][CompressData]
+/// (use [`HuffCodes::encode`][encode] to only get the compressed bits).
+/// 
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::prelude::{
+///     compress_with_codes,
+///     decompress,
+///     HuffTree,
+///     HuffCodes,
+///     ByteWeights,
+/// };
+/// 
+/// let codes = HuffCodes::from_tree(
+///     HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"))
+/// );
+/// 
+/// for bytes in [&b"abc"[..], &b"cba"[..], &b"ccccc"[..]].iter(){
+///     let comp_data = compress_with_codes(bytes, &codes).unwrap();
+///     assert_eq!(decompress(&comp_data), bytes.to_vec());
+/// }
+/// ```
+/// 
+/// # Errors
+/// ---
+/// When the provided codes do not contain a code 
+/// for a letter in the provided slice.
+/// 
+/// [tree]:crate::tree::HuffTree
+/// [codes]:crate::tree::HuffCodes
+/// [encode]:crate::tree::HuffCodes::encode
+/// [letter]:crate::tree::letter::HuffLetter
+pub fn compress_with_codes<L: HuffLetter>(letters: &[L], codes: &HuffCodes<L>) -> Result<CompressData<L>, CompressError<L>>{
+    let mut comp_letters = BitWriter::with_capacity(letters.len());
+    codes.encode(letters, &mut comp_letters)?;
+    let (comp_letters, padding_bits) = comp_letters.finish();
+
+    Ok(CompressData::new(comp_letters, padding_bits, letters.len(), codes.huff_tree().clone()))
 }
 
 /// Decompress the provided [`CompressData<L>`][CompressData] into a [`Vec<L>`][Vec].
@@ -457,9 +1006,17 @@ pub fn compress_with_tree<L: HuffLetter>(letters: &[L], huff_tree: HuffTree<L>)
 /// 1. Start at the root branch of the tree
 /// 2. Go bit by bit through the provided [`CompressData`'s][CompressData] comp_bytes
 /// 3. Every time a 0 is found, go to the left branch, and 
-/// every 1 means going to the right branch
+///    every 1 means going to the right branch
 /// 4. When it finally a letter branch is found, it push the letter into
-/// the vec, and return to the root branch.
+///    the vec, and return to the root branch.
+/// 5. Stop after [`letter_count`][letter_count] letters were found
+/// 
+/// # Panics
+/// ---
+/// When the [`CompressData`][CompressData] doesn't store exactly
+/// [`letter_count`][letter_count] letters, or its last code is cut off
+/// (see [`CompressData::decompress_to_vec`][CompressData::decompress_to_vec]
+/// for a non panicking version).
 /// 
 /// # Example
 /// ---
@@ -484,38 +1041,108 @@ pub fn compress_with_tree<L: HuffLetter>(letters: &[L], huff_tree: HuffTree<L>)
 /// assert_eq!(chars.to_vec(), decompress(&comp_chars));
 /// assert_eq!(strs.to_vec(), decompress(&comp_strs));
 /// ```
+/// 
+/// [letter_count]:CompressData::letter_count
 pub fn decompress<L: HuffLetter>(comp_data: &CompressData<L>) -> Vec<L>{
-    let bytes = comp_data.comp_bytes();
+    let mut decomp_letters = Vec::new();
+    if let Err(err) = comp_data.decompress_to_vec(&mut decomp_letters){
+        panic!("{}", err)
+    }
+    decomp_letters
+}
+
+/// Decompress the byte representation of [`CompressData<u8>`][CompressData] 
+/// (see [`CompressData::to_bytes`][CompressData::to_bytes]) into the provided buffer, 
+/// returning the number of bytes written at its beginning.
+/// 
+/// The tree is read into the provided table as a [`FlatTree`][flat_tree], so nothing is allocated,
+/// which makes it usable on targets without a heap. A table of 
+/// [`FlatTree::MAX_LEN`][max_len] entries is always long enough.
+/// 
+/// Bytes stored uncompressed in the byte representation of [`PackedData<u8>`][PackedData]
+/// (see [`compress_or_store`][compress_or_store]) are copied into the buffer as they are,
+/// so every `.hff` file can be decompressed.
+/// 
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::prelude::{FlatTree, compress, compress_or_store, decompress_flat};
+/// 
+/// let bytes = b"abbcccdddd";
+/// let comp_bytes = compress(bytes).to_bytes();
+/// 
+/// let mut table = [0; FlatTree::MAX_LEN];
+/// let mut buf = [0; 32];
+/// let byte_count = decompress_flat(&comp_bytes, &mut table, &mut buf).unwrap();
+/// assert_eq!(&buf[..byte_count], bytes);
+/// 
+/// // random bytes can't be compressed, so they're stored as they are
+/// let random = [0x3d, 0xf1, 0x08, 0x9a, 0x77, 0xc2];
+/// let packed_bytes = compress_or_store(&random).to_bytes();
+/// let byte_count = decompress_flat(&packed_bytes, &mut table, &mut buf).unwrap();
+/// assert_eq!(&buf[..byte_count], random);
+/// ```
+/// 
+/// # Errors
+/// ---
+/// 1. When the bytes can't be read like in [`CompressData::try_from_bytes`][CompressData::try_from_bytes],
+///    or the tree doesn't fit in the table
+/// 2. The same as in [`FlatTree::decode_into`][decode_into]
+/// 3. When the bytes are stored uncompressed, but don't fit in the buffer
+/// 
+/// [flat_tree]:crate::tree::FlatTree
+/// [max_len]:crate::tree::FlatTree::MAX_LEN
+/// [decode_into]:crate::tree::FlatTree::decode_into
+pub fn decompress_flat(bytes: &[u8], table: &mut [u16], buf: &mut [u8]) -> Result<usize, DecompressError>{
+    if let Some((&STORED_FLAG, stored_bytes)) = bytes.split_first(){
+        buf.get_mut(..stored_bytes.len())
+            .ok_or_else(|| DecompressError::new("buffer too short to hold every decompressed letter"))?
+            .copy_from_slice(stored_bytes);
+        return Ok(stored_bytes.len())
+    }
+    let parts = split_bytes(bytes).map_err(DecompressError::new)?;
+    let flat_tree = FlatTree::from_bin(parts.tree_bytes, parts.tree_padding_bits, table)
+        .map_err(|_| DecompressError::new("invalid tree in slice"))?;
+    flat_tree.decode_into(parts.comp_bytes, parts.data_padding_bits, parts.letter_count, buf)
+}
+
+/// Go through the compressed bytes bit by bit, walking the tree 
+/// (see [`decompress`][decompress]), and call emit on every found letter,
+/// stopping after exactly letter_count letters
+fn decode_letters<L, F>(comp_data: &CompressDataRef<L>, mut emit: F) -> Result<(), DecompressError>
+where L: HuffLetter, F: FnMut(&L){
+    let mut bits = BitReader::new(comp_data.comp_bytes(), comp_data.padding_bits());
     let tree = comp_data.huff_tree();
 
-    let mut decomp_letters = Vec::new();
+    let mut letters_left = comp_data.letter_count();
     let mut current_branch = tree.root();
-    macro_rules! read_codes_in_byte {
-        ($byte: expr;[$bitrange:expr]) => {
-            for bit_ptr in $bitrange{
-                if current_branch.has_children(){
-                    match ($byte >> (7 - bit_ptr)) & 1 == 1{
-                        true =>{
-                            current_branch = current_branch.right_child().unwrap();
-                        }
-                        false =>{
-                            current_branch = current_branch.left_child().unwrap();
-                        }
-                    }
-                }
-                if !current_branch.has_children(){
-                    decomp_letters.push(current_branch.leaf().letter().unwrap().clone());
-                    current_branch = tree.root();
-                }
-            }
-        };
+    let mut inside_code = false;
+    while let Some(bit) = bits.read_bit(){
+        if letters_left == 0{
+            return Err(DecompressError::new("compressed data stores more letters than its letter count"))
+        }
+        if current_branch.has_children(){
+            current_branch = match bit{
+                true => current_branch.right_child().unwrap(),
+                false => current_branch.left_child().unwrap(),
+            };
+            inside_code = true;
+        }
+        if !current_branch.has_children(){
+            emit(current_branch.leaf().letter().unwrap());
+            letters_left -= 1;
+            current_branch = tree.root();
+            inside_code = false;
+        }
+    }
+    if inside_code{
+        return Err(DecompressError::new("compressed data ends in the middle of a letter's code"))
     }
-    for byte in &bytes[..bytes.len() - 1]{
-       read_codes_in_byte!(byte;[0..8]);
+    if letters_left != 0{
+        return Err(DecompressError::new("compressed data stores fewer letters than its letter count"))
     }
-    read_codes_in_byte!(bytes[bytes.len() - 1];[0..8 - comp_data.padding_bits()]);
 
-    decomp_letters
+    Ok(())
 }
 
 
@@ -523,14 +1150,16 @@ pub fn decompress<L: HuffLetter>(comp_data: &CompressData<L>) -> Vec<L>{
 pub mod errors{
     use super::super::prelude::HuffLetter;
 
-    use std::fmt;
+    use alloc::borrow::Cow;
+    use core::fmt;
 
 
 
     /// Error encountered while trying to create [`CompressData`][super::CompressData] from bytes.
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct CompressedDataFromBytesError{
-        message: &'static str,
+        message: Cow<'static, str>,
     }
 
     impl fmt::Display for CompressedDataFromBytesError{
@@ -539,17 +1168,49 @@ pub mod errors{
         }
     }
 
-    impl std::error::Error for CompressedDataFromBytesError{}
+    impl core::error::Error for CompressedDataFromBytesError{}
 
     impl CompressedDataFromBytesError{
         pub fn new(message: &'static str) -> Self{
             Self{
-                message,
+                message: Cow::Borrowed(message),
+            }
+        }
+
+        pub fn message(&self) -> &str{
+            &self.message
+        }
+    }
+
+
+    /// Error encountered while decompressing, meaning that the compressed data
+    /// is invalid or the provided buffer is too short.
+    /// 
+    /// Returned by [`CompressDataRef::decompress_into`][super::CompressDataRef::decompress_into] 
+    /// and [`CompressDataRef::decompress_to_vec`][super::CompressDataRef::decompress_to_vec]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct DecompressError{
+        message: Cow<'static, str>,
+    }
+
+    impl fmt::Display for DecompressError{
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    impl core::error::Error for DecompressError{}
+
+    impl DecompressError{
+        pub fn new(message: &'static str) -> Self{
+            Self{
+                message: Cow::Borrowed(message),
             }
         }
 
         pub fn message(&self) -> &str{
-            self.message
+            &self.message
         }
     }
 
@@ -559,8 +1220,9 @@ pub mod errors{
     /// 
     /// Returned by [`compress_with_tree`][super::compress_with_tree] 
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct CompressError<L: HuffLetter>{
-        message: &'static str,
+        message: Cow<'static, str>,
         missing_letter: L,
     }
 
@@ -570,18 +1232,18 @@ pub mod errors{
         }
     }
 
-    impl<L: HuffLetter> std::error::Error for CompressError<L>{}
+    impl<L: HuffLetter> core::error::Error for CompressError<L>{}
 
     impl<L: HuffLetter> CompressError<L>{
         pub fn new(message: &'static str, missing_letter: L) -> Self{
             Self{
-                message,
+                message: Cow::Borrowed(message),
                 missing_letter,
             }
         }
 
         pub fn message(&self) -> &str{
-            self.message
+            &self.message
         }
 
         pub fn missing_letter(&self) -> &L{
@@ -0,0 +1,235 @@
+use crate::utils::chunk_len;
+
+use std::{
+    fmt,
+    sync::Arc,
+    thread,
+};
+
+/// Default number of letters below which [`Concurrency`][Concurrency]
+/// doesn't bother with spawning threads (1 MiB of bytes)
+pub const DEFAULT_SEQUENTIAL_THRESHOLD: usize = 1 << 20;
+
+/// Whether the target can spawn threads. `wasm32-unknown-unknown` can't,
+/// so only [pools](Concurrency::pool) split the work there.
+const CAN_SPAWN_THREADS: bool = !cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+
+/// Trait signifying that the struct is a thread pool able to run
+/// jobs borrowing data from the caller's stack.
+///
+/// Implement it on your own pool to make [`Concurrency`][Concurrency] use it
+/// instead of spawning new OS threads.
+///
+/// # Example
+/// ---
+/// A "pool" running every job on the calling thread:
+/// ```
+/// use huff_coding::{
+///     prelude::{ByteWeights, Concurrency},
+///     concurrency::ThreadPool,
+/// };
+/// use std::sync::Arc;
+///
+/// struct InlinePool;
+///
+/// impl ThreadPool for InlinePool{
+///     fn run_scoped<'a>(&self, jobs: Vec<Box<dyn FnOnce() + Send + 'a>>){
+///         for job in jobs{
+///             job();
+///         }
+///     }
+/// }
+///
+/// let concurrency = Concurrency::pool(Arc::new(InlinePool), 4)
+///     .with_sequential_threshold(0);
+///
+/// let byte_weights = ByteWeights::concurrent_from_bytes(b"abbccc", &concurrency);
+/// assert_eq!(*byte_weights.get(&b'c').unwrap(), 3);
+/// ```
+pub trait ThreadPool: Send + Sync{
+    /// Run every provided job, returning only after all of them have finished.
+    fn run_scoped<'a>(&self, jobs: Vec<Box<dyn FnOnce() + Send + 'a>>);
+}
+
+/// Struct configuring how many threads are used to process data
+/// (e.g. in [`ByteWeights::concurrent_from_bytes`][concurrent_from_bytes]).
+///
+/// It can:
+/// * run [`sequential`](#method.sequential)ly on the calling thread
+/// * spawn a [`fixed`](#method.fixed) number of scoped threads
+/// * spawn as many scoped threads as there is [`available`](#method.available) parallelism
+/// * run the jobs on a caller provided [`pool`](#method.pool)
+///
+/// Inputs shorter than the [sequential threshold](#method.with_sequential_threshold)
+/// (by default [`DEFAULT_SEQUENTIAL_THRESHOLD`][DEFAULT_SEQUENTIAL_THRESHOLD]) are always
+/// processed on the calling thread, as spawning threads for them costs more than it gains.
+///
+/// On targets without threads (`wasm32-unknown-unknown`) everything not run
+/// on a pool is processed on the calling thread as well.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::prelude::{ByteWeights, Concurrency};
+///
+/// let bytes = vec![b'a'; 100_000];
+///
+/// // use every available core, but only for inputs longer than 64KiB
+/// let concurrency = Concurrency::available()
+///     .with_sequential_threshold(1 << 16);
+///
+/// let byte_weights = ByteWeights::concurrent_from_bytes(&bytes, &concurrency);
+/// assert_eq!(*byte_weights.get(&b'a').unwrap(), 100_000);
+/// ```
+///
+/// [concurrent_from_bytes]:crate::weights::ByteWeights::concurrent_from_bytes
+#[derive(Clone)]
+pub struct Concurrency{
+    threads: Threads,
+    sequential_threshold: usize,
+}
+
+/// Where the jobs should be run
+#[derive(Clone)]
+enum Threads{
+    Sequential,
+    Fixed(usize),
+    Available,
+    Pool(Arc<dyn ThreadPool>, usize),
+}
+
+impl fmt::Debug for Concurrency{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let threads = match self.threads{
+            Threads::Sequential => String::from("Sequential"),
+            Threads::Fixed(n) => format!("Fixed({})", n),
+            Threads::Available => String::from("Available"),
+            Threads::Pool(_, n) => format!("Pool({})", n),
+        };
+        f.debug_struct("Concurrency")
+            .field("threads", &threads)
+            .field("sequential_threshold", &self.sequential_threshold)
+            .finish()
+    }
+}
+
+impl Default for Concurrency{
+    fn default() -> Self{
+        Self::available()
+    }
+}
+
+impl Concurrency{
+    /// Initialize a `Concurrency` processing everything on the calling thread
+    pub fn sequential() -> Self{
+        Self::new(Threads::Sequential)
+    }
+
+    /// Initialize a `Concurrency` spawning `thread_num` scoped threads.
+    ///
+    /// A `thread_num` of 0 or 1 means processing everything on the calling thread
+    pub fn fixed(thread_num: usize) -> Self{
+        Self::new(Threads::Fixed(thread_num))
+    }
+
+    /// Initialize a `Concurrency` spawning as many scoped threads as
+    /// [`std::thread::available_parallelism`][std::thread::available_parallelism] returns
+    pub fn available() -> Self{
+        Self::new(Threads::Available)
+    }
+
+    /// Initialize a `Concurrency` splitting the work into `job_num` jobs,
+    /// run on the provided [`ThreadPool`][ThreadPool]
+    pub fn pool(pool: Arc<dyn ThreadPool>, job_num: usize) -> Self{
+        Self::new(Threads::Pool(pool, job_num))
+    }
+
+    /// Set the number of letters below which everything
+    /// is processed on the calling thread
+    pub fn with_sequential_threshold(mut self, sequential_threshold: usize) -> Self{
+        self.sequential_threshold = sequential_threshold;
+        self
+    }
+
+    /// Return the number of letters below which everything
+    /// is processed on the calling thread
+    pub fn sequential_threshold(&self) -> usize{
+        self.sequential_threshold
+    }
+
+    /// Return the number of jobs an input of the given length would be split into
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::Concurrency;
+    ///
+    /// let concurrency = Concurrency::fixed(8).with_sequential_threshold(1000);
+    ///
+    /// assert_eq!(concurrency.job_count(999), 1);
+    /// assert_eq!(concurrency.job_count(1000), 8);
+    /// assert_eq!(Concurrency::sequential().job_count(usize::MAX), 1);
+    /// ```
+    pub fn job_count(&self, len: usize) -> usize{
+        if len < self.sequential_threshold || len <= 1{
+            return 1
+        }
+        let job_count = match self.threads{
+            Threads::Sequential => 1,
+            Threads::Fixed(_) | Threads::Available if !CAN_SPAWN_THREADS => 1,
+            Threads::Fixed(n) | Threads::Pool(_, n) => n,
+            Threads::Available =>
+                thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+        };
+        job_count.clamp(1, len)
+    }
+
+    /// Split the letters into [`job_count`](#method.job_count) subslices,
+    /// map every one of them with `f` and return the results in order
+    pub(crate) fn map_chunks<T, R, F>(&self, letters: &[T], f: F) -> Vec<R>
+    where T: Sync, R: Send, F: Fn(&[T]) -> R + Sync{
+        let job_count = self.job_count(letters.len());
+        if job_count == 1{
+            return vec![f(letters)]
+        }
+        let chunks = letters.chunks(chunk_len(letters.len(), job_count));
+        let f = &f;
+
+        match &self.threads{
+            Threads::Pool(pool, _) =>{
+                let mut results: Vec<Option<R>> = chunks.clone().map(|_| None).collect();
+                let jobs = chunks
+                    .zip(results.iter_mut())
+                    .map(|(chunk, result)|{
+                        Box::new(move || *result = Some(f(chunk))) as Box<dyn FnOnce() + Send + '_>
+                    })
+                    .collect();
+                pool.run_scoped(jobs);
+
+                results
+                    .into_iter()
+                    .map(|result| result.expect("ThreadPool::run_scoped returned before running every job"))
+                    .collect()
+            }
+            _ =>
+                thread::scope(|scope|{
+                    let handles: Vec<_> = chunks
+                        .map(|chunk| scope.spawn(move || f(chunk)))
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap())
+                        .collect()
+                })
+        }
+    }
+
+    fn new(threads: Threads) -> Self{
+        Self{
+            threads,
+            sequential_threshold: DEFAULT_SEQUENTIAL_THRESHOLD,
+        }
+    }
+}
@@ -11,6 +11,7 @@
 //! # Examples
 //! 
 //! ```
+//! # #[cfg(feature = "std")]{
 //! use huff_coding::{
 //!     prelude::*,
 //!     bitvec::prelude::*,
@@ -57,6 +58,7 @@
 //! // reading a HuffTree from a binary representation
 //! let tree_bytes_from_bin = HuffTree::<u8>::try_from_bin(tree_bytes_bin).unwrap();
 //! assert_eq!(tree_bytes.read_codes(), tree_bytes_from_bin.read_codes());
+//! # }
 //! ```  
 //! 
 //! Included are also example [compression][compress]/[decompression][decompress] functions using my implementation
@@ -72,6 +74,22 @@
 //! assert_eq!(bytes.to_vec(), decomp_bytes);
 //! ```
 //! 
+//! # Cargo features
+//! 
+//! * `std` *(default)* - without it the crate is `no_std`, only needing `alloc`. 
+//!   Building trees, the codes, compressing and decompressing work the same way,
+//!   except that `HashMap`s aren't available (so the functions returning them, like
+//!   [`HuffTree::read_codes`][read_codes] or [`build_weights_map`][weights_map], are left out,
+//!   and letters are looked up in a table sorted by their hashes instead).
+//!   Threading ([`concurrency`][concurrency] and the threaded weights constructors) and
+//!   [`Weights::entropy`][entropy] need `std` as well. On `wasm32-unknown-unknown`, which has `std`
+//!   but no threads, the threaded constructors count everything on the calling thread.
+//! * `serde` - implements `Serialize` and `Deserialize` for [`HuffTree`][tree] (in a compact
+//!   shape + letters form), [`ByteWeights`][byte_weights], [`CompressData`][comp_data] and the error types.
+//!   Deserialized trees and compressed data are validated, so they can be used like any other.
+//! * `tokio` - adds [`AsyncEncoder`][async_encoder] and [`AsyncDecoder`][async_decoder], adapting tokio's
+//!   `AsyncWrite` and `AsyncRead` to compress and decompress streams in the `.hff` format. Needs `std`.
+//! 
 //! Every binary representation in the crate is made thanks to the [`bitvec`][bitvec] crate which
 //! I've re-exported for convenience.
 //! 
@@ -79,14 +97,25 @@
 //! [letter]:tree::letter::HuffLetter
 //! [letter_bytes]:tree::letter::HuffLetterAsBytes
 //! [weights]:weights::Weights
+//! [byte_weights]:weights::byte_weights::ByteWeights
+//! [comp_data]:comp::CompressData
+//! [read_codes]:tree::HuffTree::read_codes
+//! [weights_map]:weights::build_weights_map
+//! [entropy]:weights::Weights::entropy
+//! [concurrency]:concurrency
+//! [async_encoder]:stream::AsyncEncoder
+//! [async_decoder]:stream::AsyncDecoder
 //! [compress]:crate::comp::compress
 //! [decompress]:crate::comp::decompress
 //! [huff_wiki]:https://en.wikipedia.org/wiki/Huffman_coding
 //! [huff_wiki_codes]:https://en.wikipedia.org/wiki/Prefix_code
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 // when i have time:
 // TODO: add some abstraction over returned BitVecs (Deref 'n stuff)
-// TODO: look at serde
 
 /// Structs and traits used to represent and construct Huffman trees.
 pub mod tree;
@@ -95,6 +124,16 @@ pub mod tree;
 pub mod weights;
 /// Example compression/decompression functions using the [`HuffTree`][crate::tree::HuffTree] struct.
 pub mod comp;
+/// Bit-level writer and reader, packing bits into bytes most significant bit first.
+pub mod bits;
+/// Encoder and decoder of the `.hff` format (the [`PackedData`][crate::comp::PackedData] byte representation)
+/// working on a stream one part at a time, async adapters built on them,
+/// and a codec for streams of framed messages.
+pub mod stream;
+/// Configuration of the threads used to process data, 
+/// able to size itself from the available parallelism or use a provided thread pool.
+#[cfg(feature = "std")]
+pub mod concurrency;
 /// `huff_coding` prelude.
 ///
 /// This collects the general public API into a single spot for inclusion, as
@@ -102,6 +141,9 @@ pub mod comp;
 pub mod prelude;
 
 mod utils;
+/// Map from letters to values used inside the crate: a `HashMap` with `std`, 
+/// and a vec sorted by the letters' hashes with only `alloc`.
+mod map;
 
 
 // `bitvec` re-export
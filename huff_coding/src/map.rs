@@ -0,0 +1,207 @@
+#[cfg(feature = "std")]
+pub(crate) use std::collections::HashMap as LetterMap;
+#[cfg(not(feature = "std"))]
+pub(crate) use self::sorted_map::SortedMap as LetterMap;
+pub(crate) use self::sorted_map::SortedMap;
+
+use crate::tree::letter::HuffLetter;
+
+/// Count every letter in the provided slice into a [`SortedMap`],
+/// so the weights are iterated in the same order on every run and platform
+/// (the order of the letters' hashes, then of their first occurrences)
+pub(crate) fn count_letters<L: HuffLetter>(letters: &[L]) -> SortedMap<L, usize>{
+    // inserting into the SortedMap one letter at a time moves its entries,
+    // so the letters are counted into a HashMap first, along with
+    // the position they were first found at
+    #[cfg(feature = "std")]
+    {
+        let mut counts = LetterMap::new();
+        for (pos, letter) in letters.iter().enumerate(){
+            counts.entry(letter).or_insert((pos, 0)).1 += 1;
+        }
+        SortedMap::from_unique_entries(
+            counts.into_iter().map(|(letter, (pos, weight))| (pos, letter.clone(), weight))
+        )
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let mut map = SortedMap::new();
+        for letter in letters{
+            match map.get_mut(letter){
+                Some(weight) => *weight += 1,
+                None =>{
+                    map.insert(letter.clone(), 1);
+                }
+            }
+        }
+        map
+    }
+}
+
+mod sorted_map{
+    use crate::weights::Weights;
+
+    use alloc::vec::{self, Vec};
+    use core::hash::{Hash, Hasher};
+
+    /// Map storing its entries in a vec sorted by the hashes of their keys,
+    /// found with a binary search and then compared in case of collisions.
+    ///
+    /// The hashes are calculated with FNV-1a, so the entries are always in the same order.
+    #[derive(Debug, Clone)]
+    pub(crate) struct SortedMap<K, V>{
+        entries: Vec<(u64, K, V)>,
+    }
+
+    impl<K: Hash + Eq, V> SortedMap<K, V>{
+        #[cfg(not(feature = "std"))]
+        pub(crate) fn new() -> Self{
+            Self::with_capacity(0)
+        }
+
+        #[cfg(not(feature = "std"))]
+        pub(crate) fn with_capacity(capacity: usize) -> Self{
+            SortedMap{
+                entries: Vec::with_capacity(capacity),
+            }
+        }
+
+        /// Build the map from entries with unique keys, paired with the positions they were
+        /// first found at, which order the keys with colliding hashes like inserting them would
+        pub(crate) fn from_unique_entries<I: IntoIterator<Item = (usize, K, V)>>(entries: I) -> Self{
+            let mut entries: Vec<_> = entries.into_iter()
+                .map(|(pos, key, value)| (hash(&key), pos, key, value))
+                .collect();
+            entries.sort_unstable_by_key(|(hash, pos, _, _)| (*hash, *pos));
+            SortedMap{
+                entries: entries.into_iter().map(|(hash, _, key, value)| (hash, key, value)).collect(),
+            }
+        }
+
+        pub(crate) fn len(&self) -> usize{
+            self.entries.len()
+        }
+
+        pub(crate) fn is_empty(&self) -> bool{
+            self.entries.is_empty()
+        }
+
+        pub(crate) fn get(&self, key: &K) -> Option<&V>{
+            self.position(key).ok().map(|pos| &self.entries[pos].2)
+        }
+
+        pub(crate) fn get_mut(&mut self, key: &K) -> Option<&mut V>{
+            let pos = self.position(key).ok()?;
+            Some(&mut self.entries[pos].2)
+        }
+
+        /// Insert the value under the key, returning the one
+        /// that was there before (if there was one)
+        #[cfg(not(feature = "std"))]
+        pub(crate) fn insert(&mut self, key: K, value: V) -> Option<V>{
+            match self.position(&key){
+                Ok(pos) => Some(core::mem::replace(&mut self.entries[pos].2, value)),
+                Err(pos) =>{
+                    self.entries.insert(pos, (hash(&key), key, value));
+                    None
+                }
+            }
+        }
+
+        pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)>{
+            self.entries.iter().map(|(_, key, value)| (key, value))
+        }
+
+        /// Return Ok with the position of the key,
+        /// or Err with the position it should be inserted at
+        fn position(&self, key: &K) -> Result<usize, usize>{
+            let key_hash = hash(key);
+            let start = self.entries.partition_point(|(hash, _, _)| *hash < key_hash);
+            let mut pos = start;
+            while let Some((hash, entry_key, _)) = self.entries.get(pos){
+                if *hash != key_hash{
+                    break
+                }
+                if entry_key == key{
+                    return Ok(pos)
+                }
+                pos += 1;
+            }
+            Err(pos)
+        }
+    }
+
+    impl<K: Hash + Eq, V: PartialEq> PartialEq for SortedMap<K, V>{
+        fn eq(&self, other: &Self) -> bool{
+            self.len() == other.len() &&
+            self.iter().all(|(key, value)| other.get(key) == Some(value))
+        }
+    }
+
+    impl<K: Hash + Eq, V: Eq> Eq for SortedMap<K, V>{}
+
+    impl<K, V> IntoIterator for SortedMap<K, V>{
+        type Item = (K, V);
+        type IntoIter = core::iter::Map<vec::IntoIter<(u64, K, V)>, fn((u64, K, V)) -> (K, V)>;
+
+        fn into_iter(self) -> Self::IntoIter{
+            self.entries.into_iter().map(|(_, key, value)| (key, value))
+        }
+    }
+
+    impl<K: Hash + Eq + Clone> Weights<K> for SortedMap<K, usize>{
+        fn get(&self, letter: &K) -> Option<&usize>{
+            self.get(letter)
+        }
+        fn get_mut(&mut self, letter: &K) -> Option<&mut usize>{
+            self.get_mut(letter)
+        }
+        fn len(&self) -> usize{
+            self.len()
+        }
+        fn is_empty(&self) -> bool{
+            self.is_empty()
+        }
+    }
+
+    /// Hash the key with FNV-1a
+    fn hash<K: Hash>(key: &K) -> u64{
+        let mut hasher = FnvHasher(0xcbf2_9ce4_8422_2325);
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// 64 bit FNV-1a hasher, hashing integers as little endian bytes,
+    /// so every key gets the same hash on every platform
+    struct FnvHasher(u64);
+
+    impl Hasher for FnvHasher{
+        fn finish(&self) -> u64{
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]){
+            for byte in bytes{
+                self.0 ^= *byte as u64;
+                self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+
+        fn write_u16(&mut self, i: u16){
+            self.write(&i.to_le_bytes())
+        }
+        fn write_u32(&mut self, i: u32){
+            self.write(&i.to_le_bytes())
+        }
+        fn write_u64(&mut self, i: u64){
+            self.write(&i.to_le_bytes())
+        }
+        fn write_u128(&mut self, i: u128){
+            self.write(&i.to_le_bytes())
+        }
+        // hashed as a u64, so it's the same on 32 and 64 bit platforms
+        fn write_usize(&mut self, i: usize){
+            self.write_u64(i as u64)
+        }
+    }
+}
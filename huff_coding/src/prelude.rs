@@ -1,23 +1,41 @@
 pub use super::{
     tree::{
         HuffTree,
+        HuffCodes,
+        FlatTree,
+        TieBreak,
         branch::HuffBranch,
         leaf::HuffLeaf,
         letter::{
             HuffLetter,
             HuffLetterAsBytes,
+            HuffLetterAsIndex,
         },
     },
     weights::{
         Weights,
-        build_weights_map,
-        build_weights_map_with_hasher,
+        build_weights_btree,
         byte_weights::ByteWeights,
+        index_weights::IndexWeights,
     },
     comp::{
         CompressData,
+        CompressDataRef,
+        PackedData,
         compress,
         compress_with_tree,
-        decompress
+        compress_with_codes,
+        compress_or_store,
+        estimate_compressed_len,
+        decompress,
+        decompress_flat,
+    },
+};
+#[cfg(feature = "std")]
+pub use super::{
+    weights::{
+        build_weights_map,
+        build_weights_map_with_hasher,
     },
+    concurrency::Concurrency,
 };
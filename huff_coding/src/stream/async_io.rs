@@ -0,0 +1,286 @@
+use super::{
+    StreamEncoder,
+    StreamDecoder,
+    StreamError,
+    StreamErrorKind,
+};
+use crate::prelude::ByteWeights;
+
+use tokio::io::{
+    AsyncRead,
+    AsyncWrite,
+    ReadBuf,
+};
+
+use std::{
+    io,
+    mem,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+/// Number of bytes an [`AsyncDecoder`] reads from its reader at one time
+const READ_BUF_LEN: usize = 8 * 1024;
+
+/// [`AsyncWrite`][AsyncWrite] adapter compressing everything written into it in the `.hff` format
+/// (the [`PackedData`][packed] byte representation) and writing it into the inner writer,
+/// using the same [`StreamEncoder`] as blocking code does.
+///
+/// The format stores the tree before the compressed data, so an encoder made with
+/// [`new`](#method.new) keeps every written byte in memory and only compresses them on shutdown.
+/// When the bytes' [`ByteWeights`][ByteWeights] are known up front, an encoder made with
+/// [`with_weights`](#method.with_weights) compresses them as they're written instead.
+/// Either way, the output is complete only after [`shutdown`][shutdown] is called.
+///
+/// Available with the `tokio` feature.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::{
+///     prelude::compress_or_store,
+///     stream::AsyncEncoder,
+/// };
+/// use tokio::io::AsyncWriteExt;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async{
+/// let bytes = b"abbcccddddeeeee";
+///
+/// let mut encoder = AsyncEncoder::new(Vec::new());
+/// encoder.write_all(bytes).await.unwrap();
+/// encoder.shutdown().await.unwrap();
+///
+/// assert_eq!(encoder.into_inner(), compress_or_store(bytes).to_bytes());
+/// # });
+/// ```
+///
+/// [AsyncWrite]:tokio::io::AsyncWrite
+/// [packed]:crate::comp::PackedData::to_bytes
+/// [shutdown]:tokio::io::AsyncWriteExt::shutdown
+#[derive(Debug)]
+pub struct AsyncEncoder<W>{
+    inner: W,
+    state: EncodeState,
+    /// Output not written into the inner writer yet
+    pending: Vec<u8>,
+    pending_pos: usize,
+}
+
+/// What an [`AsyncEncoder`] does with the written bytes
+#[derive(Debug)]
+enum EncodeState{
+    /// Keep them until shutdown, as their weights aren't known
+    Buffered(Vec<u8>),
+    /// Compress them right away
+    Streaming(StreamEncoder),
+    /// Shutdown has started, nothing more can be written
+    Finished,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncEncoder<W>{
+    /// Initialize an `AsyncEncoder` keeping the written bytes
+    /// in memory and compressing them into inner on shutdown
+    pub fn new(inner: W) -> Self{
+        Self{
+            inner,
+            state: EncodeState::Buffered(Vec::new()),
+            pending: Vec::new(),
+            pending_pos: 0,
+        }
+    }
+
+    /// Initialize an `AsyncEncoder` for exactly the bytes counted into the provided weights,
+    /// compressing them into inner as they're written (see [`StreamEncoder::new`])
+    ///
+    /// Writing bytes not matching the weights fails with [`io::ErrorKind::InvalidInput`][InvalidInput].
+    ///
+    /// [InvalidInput]:std::io::ErrorKind::InvalidInput
+    pub fn with_weights(inner: W, weights: &ByteWeights) -> Self{
+        Self{
+            inner,
+            state: EncodeState::Streaming(StreamEncoder::new(weights)),
+            pending: Vec::new(),
+            pending_pos: 0,
+        }
+    }
+
+    /// Return a reference to the inner writer
+    pub fn get_ref(&self) -> &W{
+        &self.inner
+    }
+
+    /// Return a mutable reference to the inner writer
+    pub fn get_mut(&mut self) -> &mut W{
+        &mut self.inner
+    }
+
+    /// Return the inner writer, discarding anything not written into it yet
+    pub fn into_inner(self) -> W{
+        self.inner
+    }
+
+    /// Write the pending output into the inner writer
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>>{
+        while self.pending_pos < self.pending.len(){
+            let written = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.pending[self.pending_pos..]))?;
+            if written == 0{
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()))
+            }
+            self.pending_pos += written;
+        }
+        self.pending.clear();
+        self.pending_pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncEncoder<W>{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>{
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        match &mut this.state{
+            EncodeState::Buffered(bytes) =>
+                bytes.extend_from_slice(buf),
+            EncodeState::Streaming(encoder) =>
+                this.pending = encoder.encode(buf).map_err(to_io_error)?,
+            EncodeState::Finished =>
+                return Poll::Ready(Err(io::Error::new(io::ErrorKind::BrokenPipe, "cannot write into a shut down AsyncEncoder"))),
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>{
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>{
+        let this = self.get_mut();
+        match mem::replace(&mut this.state, EncodeState::Finished){
+            EncodeState::Buffered(bytes) =>{
+                let mut encoder = StreamEncoder::new(&ByteWeights::from_bytes(&bytes));
+                this.pending.extend(encoder.encode(&bytes).map_err(to_io_error)?);
+                this.pending.extend(encoder.finish().map_err(to_io_error)?);
+            }
+            EncodeState::Streaming(encoder) =>
+                this.pending.extend(encoder.finish().map_err(to_io_error)?),
+            EncodeState::Finished => (),
+        }
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// [`AsyncRead`][AsyncRead] adapter decompressing the `.hff` format (the [`PackedData`][packed]
+/// byte representation) read from the inner reader, using the same [`StreamDecoder`] as blocking code does.
+///
+/// Reading fails with [`io::ErrorKind::InvalidData`][InvalidData] when the inner reader
+/// doesn't store valid `.hff` data, which can be found out only at its end.
+///
+/// Available with the `tokio` feature.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::{
+///     prelude::compress_or_store,
+///     stream::AsyncDecoder,
+/// };
+/// use tokio::io::AsyncReadExt;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async{
+/// let bytes = b"abbcccddddeeeee";
+/// let packed_bytes = compress_or_store(bytes).to_bytes();
+///
+/// let mut decoder = AsyncDecoder::new(&packed_bytes[..]);
+/// let mut decomp_bytes = Vec::new();
+/// decoder.read_to_end(&mut decomp_bytes).await.unwrap();
+///
+/// assert_eq!(decomp_bytes, bytes.to_vec());
+/// # });
+/// ```
+///
+/// [AsyncRead]:tokio::io::AsyncRead
+/// [packed]:crate::comp::PackedData::to_bytes
+/// [InvalidData]:std::io::ErrorKind::InvalidData
+#[derive(Debug)]
+pub struct AsyncDecoder<R>{
+    inner: R,
+    /// None once the inner reader has ended
+    decoder: Option<StreamDecoder>,
+    read_buf: Box<[u8]>,
+    /// Decoded bytes not read yet
+    pending: Vec<u8>,
+    pending_pos: usize,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecoder<R>{
+    /// Initialize an `AsyncDecoder` decompressing the data read from inner
+    pub fn new(inner: R) -> Self{
+        Self{
+            inner,
+            decoder: Some(StreamDecoder::new()),
+            read_buf: vec![0; READ_BUF_LEN].into_boxed_slice(),
+            pending: Vec::new(),
+            pending_pos: 0,
+        }
+    }
+
+    /// Return a reference to the inner reader
+    pub fn get_ref(&self) -> &R{
+        &self.inner
+    }
+
+    /// Return a mutable reference to the inner reader
+    pub fn get_mut(&mut self) -> &mut R{
+        &mut self.inner
+    }
+
+    /// Return the inner reader, discarding anything decoded but not read yet
+    pub fn into_inner(self) -> R{
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncDecoder<R>{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>>{
+        let this = self.get_mut();
+        if buf.remaining() == 0{
+            return Poll::Ready(Ok(()))
+        }
+        loop{
+            if this.pending_pos < this.pending.len(){
+                let len = buf.remaining().min(this.pending.len() - this.pending_pos);
+                buf.put_slice(&this.pending[this.pending_pos..this.pending_pos + len]);
+                this.pending_pos += len;
+                return Poll::Ready(Ok(()))
+            }
+            let decoder = match &mut this.decoder{
+                Some(decoder) => decoder,
+                // nothing more to read
+                None => return Poll::Ready(Ok(())),
+            };
+
+            let mut read_buf = ReadBuf::new(&mut this.read_buf);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut read_buf))?;
+            let decoded = if read_buf.filled().is_empty(){
+                this.decoder.take().unwrap().finish()
+            }
+            else{
+                decoder.decode(read_buf.filled())
+            };
+            this.pending = decoded.map_err(to_io_error)?;
+            this.pending_pos = 0;
+        }
+    }
+}
+
+/// Convert the [`StreamError`] into an [`io::Error`][io::Error] of the matching kind
+fn to_io_error(e: StreamError) -> io::Error{
+    let kind = match e.kind(){
+        StreamErrorKind::InvalidInput => io::ErrorKind::InvalidInput,
+        _ => io::ErrorKind::InvalidData,
+    };
+    io::Error::new(kind, e)
+}
@@ -0,0 +1,387 @@
+use crate::{
+    prelude::{
+        HuffTree,
+        HuffCodes,
+        FlatTree,
+    },
+    bits::{BitReader, BitWriter},
+    utils::calc_padding_bits,
+};
+
+use alloc::{
+    borrow::Cow,
+    vec::Vec,
+};
+use core::{
+    convert::TryInto,
+    fmt,
+};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+/// Codec splitting a byte stream into length-delimited frames, each carrying one message
+/// (payload) Huffman-coded with a tree shared by both ends, or with a tree sent along with the frame.
+///
+/// Meant for many short messages, where sending a tree with each of them would take more space
+/// than it saves. Both ends build the same tree up front, for example from the
+/// [`ByteWeights`][ByteWeights] of some typical messages, or one of them sends
+/// its tree's [binary representation][as_bin] to the other.
+///
+/// # Frame layout
+/// ---
+/// * 4 bytes - the length of the rest of the frame (big endian)
+/// * 1 byte - flags: the highest bit is set when the payload is stored uncompressed,
+///   the next one when the frame carries its own tree, then 3 bits of the tree's
+///   padding and 3 bits of the coded payload's padding
+/// * only with its own tree: 2 bytes of the tree's length (big endian),
+///   and the tree's binary representation
+/// * the coded (or stored) payload
+///
+/// A payload is stored uncompressed when coding it wouldn't make it any shorter,
+/// or it has a byte the shared tree doesn't store, so any payload can be sent.
+///
+/// The codec doesn't do any IO on its own: [`encode`](#method.encode) appends frames into a
+/// buffer, and [`decode`](#method.decode) reads them from the beginning of one, so it works the
+/// same with blocking and async streams. With the `std` feature, [`write_frame`](#method.write_frame)
+/// and [`read_frame`](#method.read_frame) do it with any [`Write`][Write]r or [`Read`][Read]er.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::{
+///     prelude::{HuffTree, ByteWeights},
+///     stream::FrameCodec,
+/// };
+///
+/// // both ends build the same tree
+/// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"GET /index.html, GET /about.html"));
+/// let codec = FrameCodec::new(tree);
+///
+/// let mut buf = Vec::new();
+/// codec.encode(b"GET /index.html", &mut buf).unwrap();
+/// codec.encode(b"GET /", &mut buf).unwrap();
+/// // a tree made for one frame only
+/// let other_tree = HuffTree::from_weights(ByteWeights::from_bytes(b"POST /login"));
+/// codec.encode_with_tree(b"POST /login", &other_tree, &mut buf).unwrap();
+///
+/// let mut messages = Vec::new();
+/// let mut pos = 0;
+/// while let Some((payload, frame_len)) = codec.decode(&buf[pos..]).unwrap(){
+///     messages.push(payload);
+///     pos += frame_len;
+/// }
+/// assert_eq!(messages, vec![b"GET /index.html".to_vec(), b"GET /".to_vec(), b"POST /login".to_vec()]);
+/// ```
+///
+/// [ByteWeights]:crate::weights::byte_weights::ByteWeights
+/// [as_bin]:crate::tree::HuffTree::as_bin
+/// [Write]:std::io::Write
+/// [Read]:std::io::Read
+#[derive(Debug, Clone)]
+pub struct FrameCodec{
+    tree: HuffTree<u8>,
+    codes: HuffCodes<u8>,
+    table: Vec<u16>,
+    max_frame_len: usize,
+}
+
+impl FrameCodec{
+    /// Flag set in a frame storing its payload uncompressed
+    pub const STORED_FLAG: u8 = 0b1000_0000;
+    /// Flag set in a frame carrying its own tree
+    pub const TREE_FLAG: u8 = 0b0100_0000;
+    /// Length of the part of a frame storing its length
+    pub const LEN_LEN: usize = 4;
+    /// Default maximum length of a frame (without its length), 8 MiB
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+    /// Initialize a `FrameCodec` coding payloads with the provided
+    /// shared tree, and accepting frames of at most [`DEFAULT_MAX_FRAME_LEN`](#associatedconstant.DEFAULT_MAX_FRAME_LEN) bytes
+    pub fn new(tree: HuffTree<u8>) -> Self{
+        Self::with_max_frame_len(tree, Self::DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Initialize a `FrameCodec` coding payloads with the provided shared tree,
+    /// and accepting frames of at most max_frame_len bytes (not counting their length)
+    pub fn with_max_frame_len(tree: HuffTree<u8>, max_frame_len: usize) -> Self{
+        Self{
+            table: tree.to_flat_table().expect("a tree stores every byte once, so it fits in a flat table"),
+            codes: HuffCodes::from_tree_indexed(tree.clone()),
+            tree,
+            max_frame_len: max_frame_len.min(u32::MAX as usize),
+        }
+    }
+
+    /// Return the shared tree
+    pub fn tree(&self) -> &HuffTree<u8>{
+        &self.tree
+    }
+
+    /// Return the maximum length of a frame (not counting its length)
+    pub fn max_frame_len(&self) -> usize{
+        self.max_frame_len
+    }
+
+    /// Code the payload with the shared tree, and append it as a frame to dst
+    /// (storing it uncompressed if coding wouldn't make it shorter,
+    /// or it has a byte the shared tree doesn't store).
+    ///
+    /// # Errors
+    /// ---
+    /// When the frame would be longer than [`max_frame_len`](#method.max_frame_len).
+    pub fn encode(&self, payload: &[u8], dst: &mut Vec<u8>) -> Result<(), FrameError>{
+        let mut bit_writer = BitWriter::new();
+        match self.codes.encode(payload, &mut bit_writer){
+            Ok(()) =>{
+                let (data, padding_bits) = bit_writer.finish();
+                self.push_frame(payload, None, &data, padding_bits, dst)
+            }
+            Err(_) =>
+                self.push_frame(payload, None, payload, 0, dst),
+        }
+    }
+
+    /// Code the payload with the provided tree instead of the shared one, and append it
+    /// as a frame to dst, along with the tree (storing the payload uncompressed
+    /// if coding it wouldn't make the frame shorter).
+    ///
+    /// # Errors
+    /// ---
+    /// 1. When the payload has a byte the provided tree doesn't store
+    /// 2. When the frame would be longer than [`max_frame_len`](#method.max_frame_len)
+    pub fn encode_with_tree(&self, payload: &[u8], tree: &HuffTree<u8>, dst: &mut Vec<u8>) -> Result<(), FrameError>{
+        let mut bit_writer = BitWriter::new();
+        HuffCodes::from_tree_indexed(tree.clone())
+            .encode(payload, &mut bit_writer)
+            .map_err(|_| FrameError::new("payload stores a byte not found in the provided tree"))?;
+        let (data, padding_bits) = bit_writer.finish();
+        self.push_frame(payload, Some(tree), &data, padding_bits, dst)
+    }
+
+    /// Append a frame storing the coded data (and the tree it was coded with, if it's not the
+    /// shared one) to dst, or the payload itself, if it's not longer
+    fn push_frame(&self, payload: &[u8], tree: Option<&HuffTree<u8>>, data: &[u8], padding_bits: u8, dst: &mut Vec<u8>) -> Result<(), FrameError>{
+        let tree = tree.map(|tree|{
+            let tree_bin = tree.as_bin();
+            (calc_padding_bits(tree_bin.len()), tree_bin.into_vec())
+        });
+        let tree_len = tree.as_ref().map_or(0, |(_, tree_bytes)| 2 + tree_bytes.len());
+
+        let frame_len = 1 + tree_len + data.len();
+        let stored_frame_len = 1 + payload.len();
+        let stored = payload.is_empty() || frame_len >= stored_frame_len;
+        let frame_len = if stored{stored_frame_len} else{frame_len};
+        if frame_len > self.max_frame_len{
+            return Err(FrameError::new("frame longer than the maximum frame length"))
+        }
+
+        dst.reserve(Self::LEN_LEN + frame_len);
+        dst.extend_from_slice(&(frame_len as u32).to_be_bytes());
+        if stored{
+            dst.push(Self::STORED_FLAG);
+            dst.extend_from_slice(payload);
+            return Ok(())
+        }
+        match tree{
+            Some((tree_padding_bits, tree_bytes)) =>{
+                dst.push(Self::TREE_FLAG | (tree_padding_bits << 3) | padding_bits);
+                dst.extend_from_slice(&(tree_bytes.len() as u16).to_be_bytes());
+                dst.extend_from_slice(&tree_bytes);
+            }
+            None =>
+                dst.push(padding_bits),
+        }
+        dst.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Try to read a frame from the beginning of src, returning its payload and the
+    /// number of bytes it took up, or `None` if src doesn't store the whole frame yet.
+    ///
+    /// # Errors
+    /// ---
+    /// 1. When the frame is empty or longer than [`max_frame_len`](#method.max_frame_len)
+    /// 2. When it has invalid flags, or carries an invalid tree
+    /// 3. When the coded payload ends in the middle of a byte's code
+    pub fn decode(&self, src: &[u8]) -> Result<Option<(Vec<u8>, usize)>, FrameError>{
+        let frame_len = match src.get(..Self::LEN_LEN){
+            Some(len_bytes) => self.frame_len(len_bytes.try_into().unwrap())?,
+            None => return Ok(None),
+        };
+        match src.get(Self::LEN_LEN..Self::LEN_LEN + frame_len){
+            Some(frame) => Ok(Some((self.decode_frame(frame)?, Self::LEN_LEN + frame_len))),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the frame's length, checking that it's valid
+    fn frame_len(&self, len_bytes: [u8; 4]) -> Result<usize, FrameError>{
+        let frame_len = u32::from_be_bytes(len_bytes) as usize;
+        if frame_len == 0{
+            return Err(FrameError::new("frame is empty"))
+        }
+        if frame_len > self.max_frame_len{
+            return Err(FrameError::new("frame longer than the maximum frame length"))
+        }
+        Ok(frame_len)
+    }
+
+    /// Decode the payload from the frame (without its length)
+    fn decode_frame(&self, frame: &[u8]) -> Result<Vec<u8>, FrameError>{
+        let flags = frame[0];
+        let mut data = &frame[1..];
+        if flags & Self::STORED_FLAG != 0{
+            if flags != Self::STORED_FLAG{
+                return Err(FrameError::new("frame has invalid flags"))
+            }
+            return Ok(data.to_vec())
+        }
+
+        let padding_bits = flags & 0b111;
+        if data.is_empty(){
+            return Err(FrameError::new("frame does not contain a payload"))
+        }
+        if flags & Self::TREE_FLAG == 0{
+            if flags & 0b0011_1000 != 0{
+                return Err(FrameError::new("frame has invalid flags"))
+            }
+            return decode_bits(&self.table, data, padding_bits)
+        }
+
+        let tree_padding_bits = (flags >> 3) & 0b111;
+        let tree_len = match data.get(..2){
+            Some(tree_len) => u16::from_be_bytes(tree_len.try_into().unwrap()) as usize,
+            None => return Err(FrameError::new("frame too short to read its tree's length")),
+        };
+        let tree_bytes = data.get(2..2 + tree_len)
+            .ok_or_else(|| FrameError::new("frame too short to read its tree"))?;
+        data = &data[2 + tree_len..];
+        if data.is_empty(){
+            return Err(FrameError::new("frame does not contain a payload"))
+        }
+        let mut table = [0; FlatTree::MAX_LEN];
+        let flat_tree = FlatTree::from_bin(tree_bytes, tree_padding_bits, &mut table)
+            .map_err(|_| FrameError::new("frame carries an invalid tree"))?;
+        decode_bits(flat_tree.table(), data, padding_bits)
+    }
+
+    /// Code the payload with the shared tree, and write it as a frame into writer
+    /// (see [`encode`](#method.encode)).
+    ///
+    /// # Errors
+    /// ---
+    /// With [`io::ErrorKind::InvalidInput`][InvalidInput] when the frame would be longer
+    /// than [`max_frame_len`](#method.max_frame_len), or any error returned by the writer.
+    ///
+    /// [InvalidInput]:std::io::ErrorKind::InvalidInput
+    #[cfg(feature = "std")]
+    pub fn write_frame<W: Write>(&self, writer: &mut W, payload: &[u8]) -> io::Result<()>{
+        let mut frame = Vec::new();
+        self.encode(payload, &mut frame)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        writer.write_all(&frame)
+    }
+
+    /// Code the payload with the provided tree, and write it as a frame into writer,
+    /// along with the tree (see [`encode_with_tree`](#method.encode_with_tree)).
+    ///
+    /// # Errors
+    /// ---
+    /// With [`io::ErrorKind::InvalidInput`][InvalidInput] when the payload has a byte the tree
+    /// doesn't store, or the frame would be longer than [`max_frame_len`](#method.max_frame_len),
+    /// or any error returned by the writer.
+    ///
+    /// [InvalidInput]:std::io::ErrorKind::InvalidInput
+    #[cfg(feature = "std")]
+    pub fn write_frame_with_tree<W: Write>(&self, writer: &mut W, payload: &[u8], tree: &HuffTree<u8>) -> io::Result<()>{
+        let mut frame = Vec::new();
+        self.encode_with_tree(payload, tree, &mut frame)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        writer.write_all(&frame)
+    }
+
+    /// Read the next frame from reader, returning its payload,
+    /// or `None` if the reader ended before it.
+    ///
+    /// # Errors
+    /// ---
+    /// 1. With [`io::ErrorKind::UnexpectedEof`][UnexpectedEof] when the reader ends in the middle of a frame
+    /// 2. With [`io::ErrorKind::InvalidData`][InvalidData] when the frame is invalid (see [`decode`](#method.decode))
+    /// 3. Any other error returned by the reader
+    ///
+    /// [UnexpectedEof]:std::io::ErrorKind::UnexpectedEof
+    /// [InvalidData]:std::io::ErrorKind::InvalidData
+    #[cfg(feature = "std")]
+    pub fn read_frame<R: Read>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>>{
+        let invalid_data = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        // a reader ending right before a frame is the end of the stream
+        let mut len_bytes = [0; Self::LEN_LEN];
+        let mut read_len = 0;
+        while read_len < len_bytes.len(){
+            match reader.read(&mut len_bytes[read_len..]){
+                Ok(0) if read_len == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read_len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+        let frame_len = self.frame_len(len_bytes).map_err(invalid_data)?;
+
+        let mut frame = vec![0; frame_len];
+        reader.read_exact(&mut frame)?;
+        self.decode_frame(&frame).map(Some).map_err(invalid_data)
+    }
+}
+
+/// Decode every code in bytes (except for their padding_bits last bits)
+/// with the tree flattened into the table (see [`FlatTree`])
+fn decode_bits(table: &[u16], bytes: &[u8], padding_bits: u8) -> Result<Vec<u8>, FrameError>{
+    let mut bits = BitReader::new(bytes, padding_bits);
+    let mut payload = Vec::with_capacity(bytes.len() * 2);
+    let mut index = 0;
+    while let Some(bit) = bits.read_bit(){
+        let entry = table[index + bit as usize];
+        if entry & FlatTree::LEAF_FLAG != 0{
+            payload.push(entry as u8);
+            index = 0;
+        }
+        else{
+            index = entry as usize;
+        }
+    }
+    if index != 0{
+        return Err(FrameError::new("frame's payload ends in the middle of a byte's code"))
+    }
+    Ok(payload)
+}
+
+/// Error encountered while encoding or decoding a frame with a [`FrameCodec`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameError{
+    message: Cow<'static, str>,
+}
+
+impl fmt::Display for FrameError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl core::error::Error for FrameError{}
+
+impl FrameError{
+    /// Initialize a new `FrameError` with the given message
+    pub fn new(message: &'static str) -> Self{
+        Self{
+            message: Cow::Borrowed(message),
+        }
+    }
+
+    /// Return the message
+    pub fn message(&self) -> &str{
+        &self.message
+    }
+}
@@ -0,0 +1,20 @@
+mod stream_inner;
+mod frame;
+#[cfg(feature = "tokio")]
+mod async_io;
+
+pub use stream_inner::{
+    StreamEncoder,
+    StreamDecoder,
+    StreamError,
+    StreamErrorKind,
+};
+pub use frame::{
+    FrameCodec,
+    FrameError,
+};
+#[cfg(feature = "tokio")]
+pub use async_io::{
+    AsyncEncoder,
+    AsyncDecoder,
+};
@@ -0,0 +1,541 @@
+use crate::{
+    prelude::{
+        HuffTree,
+        HuffCodes,
+        FlatTree,
+        ByteWeights,
+        estimate_compressed_len,
+    },
+    comp::{
+        stored_len,
+        STORED_FLAG,
+        HEADER_LEN,
+    },
+    bits::{BitReader, BitWriter},
+    map::SortedMap,
+    utils::calc_padding_bits,
+    bitvec::prelude::BitVec,
+};
+
+use alloc::{
+    borrow::Cow,
+    vec,
+    vec::Vec,
+};
+use core::{
+    convert::TryInto,
+    fmt,
+};
+
+/// Encoder writing bytes in the [`PackedData`][packed] byte representation
+/// (the `.hff` format) one part at a time, without holding all of them in memory.
+///
+/// The format stores the tree and the number of bytes before the compressed data,
+/// so the encoder needs the [`ByteWeights`][ByteWeights] of every byte it's going
+/// to encode up front (read the bytes twice, or keep them around). Then they can be
+/// passed in parts of any length to [`encode`](#method.encode), which returns the
+/// output ready to be written (starting with the header), and [`finish`](#method.finish)
+/// returns what's left.
+///
+/// Like [`compress_or_store`][compress_or_store], the bytes are stored uncompressed
+/// when compressing them wouldn't make them any shorter, and the tree breaks ties between
+/// equal weights in the same order, so both return the same output for the same bytes.
+///
+/// When the bytes are too many to count exactly, the encoder can be built from weights
+/// scaled down with [`Weights::scale_to`][scale_to] and the real number of bytes passed separately
+/// ([`with_byte_count`](#method.with_byte_count)). The padding the header states is then
+/// only known once every byte is encoded, and [`finish_with_header_byte`](#method.finish_with_header_byte)
+/// returns the first byte of the header to write over the one written at the start.
+///
+/// It doesn't do any IO on its own, so the same encoder is used with blocking and async writers.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::{
+///     prelude::{ByteWeights, compress_or_store},
+///     stream::StreamEncoder,
+/// };
+///
+/// let bytes = b"abbcccddddeeeee";
+/// let mut encoder = StreamEncoder::new(&ByteWeights::from_bytes(bytes));
+///
+/// let mut output = Vec::new();
+/// for part in bytes.chunks(4){
+///     output.extend(encoder.encode(part).unwrap());
+/// }
+/// output.extend(encoder.finish().unwrap());
+///
+/// assert_eq!(output, compress_or_store(bytes).to_bytes());
+/// ```
+///
+/// [packed]:crate::comp::PackedData::to_bytes
+/// [compress_or_store]:crate::comp::compress_or_store
+/// [scale_to]:crate::weights::Weights::scale_to
+#[derive(Debug, Clone)]
+pub struct StreamEncoder{
+    codes: Option<HuffCodes<u8>>,
+    bit_writer: BitWriter,
+    bytes_left: u64,
+    tree_padding_bits: u8,
+    data_padding_bits: u8,
+}
+
+impl StreamEncoder{
+    /// Initialize a `StreamEncoder` for exactly the bytes counted into the provided weights,
+    /// deciding whether they'll be compressed or stored, and preparing the header.
+    pub fn new(weights: &ByteWeights) -> Self{
+        Self::with_byte_count(weights, weights.total() as u64)
+    }
+
+    /// Initialize a `StreamEncoder` for `byte_count` bytes, building the tree from
+    /// the provided weights, which only have to be in proportion to the bytes' counts
+    /// (every byte has to have a nonzero weight though).
+    ///
+    /// The header's padding is calculated from the weights, so unless they're the exact counts,
+    /// it has to be written over with the byte returned from [`finish_with_header_byte`](#method.finish_with_header_byte).
+    pub fn with_byte_count(weights: &ByteWeights, byte_count: u64) -> Self{
+        let mut bit_writer = BitWriter::new();
+
+        // if compressing wouldn't make the bytes any shorter, store them as they are
+        let tree = if weights.is_empty() || byte_count == 0{
+            None
+        }
+        else{
+            // the weights are ordered like compress_or_store counts them,
+            // so that ties between them are broken the same way
+            let tree = HuffTree::from_weights(SortedMap::from_unique_entries(
+                weights.into_iter().enumerate().map(|(pos, (byte, weight))| (pos, byte, weight))
+            ));
+            // the data's share of the estimate is scaled up from the weights to byte_count bytes
+            let comp_len = estimate_compressed_len(&tree, weights).and_then(|comp_len|{
+                let data_len = tree.compressed_bit_count(weights)?.div_ceil(8).max(1);
+                let byte_count_data_len = data_len.checked_mul(byte_count as u128)?.div_ceil(weights.total());
+                Some(comp_len - data_len + byte_count_data_len)
+            });
+            match comp_len{
+                Some(comp_len) if comp_len < stored_len::<u8>(byte_count as u128) => Some(tree),
+                _ => None,
+            }
+        };
+        let tree = match tree{
+            Some(tree) => tree,
+            None =>{
+                bit_writer.write_bytes(&[STORED_FLAG]);
+                return Self{
+                    codes: None,
+                    bit_writer,
+                    bytes_left: byte_count,
+                    tree_padding_bits: 0,
+                    data_padding_bits: 0,
+                }
+            }
+        };
+
+        // the padding is known up front when the weights are the exact counts,
+        // as they say how many bits the data will take
+        let data_bit_count = tree.compressed_bit_count(weights).unwrap();
+        let data_padding_bits = calc_padding_bits((data_bit_count % 8) as usize);
+        let tree_bin = tree.as_bin();
+        let tree_padding_bits = calc_padding_bits(tree_bin.len());
+        let tree_bytes = tree_bin.into_vec();
+
+        bit_writer.write_bytes(&[(tree_padding_bits << 4) + data_padding_bits]);
+        bit_writer.write_bytes(&(tree_bytes.len() as u32).to_be_bytes());
+        bit_writer.write_bytes(&byte_count.to_be_bytes());
+        bit_writer.write_bytes(&tree_bytes);
+        Self{
+            codes: Some(HuffCodes::from_tree_indexed(tree)),
+            bit_writer,
+            bytes_left: byte_count,
+            tree_padding_bits,
+            data_padding_bits,
+        }
+    }
+
+    /// Return true if the bytes are stored uncompressed
+    pub fn is_stored(&self) -> bool{
+        self.codes.is_none()
+    }
+
+    /// Encode the next part of the bytes, returning the output ready to be written
+    /// (with the header before the first part).
+    ///
+    /// The bits of a code cut off at the end of the part are kept until the next one.
+    ///
+    /// # Errors
+    /// ---
+    /// With [`StreamErrorKind::InvalidInput`][InvalidInput] when there are more bytes than
+    /// the header counts, or a byte the weights didn't count at all.
+    ///
+    /// [InvalidInput]:StreamErrorKind::InvalidInput
+    pub fn encode(&mut self, bytes: &[u8]) -> Result<Vec<u8>, StreamError>{
+        if bytes.len() as u64 > self.bytes_left{
+            return Err(StreamError::new("stream stores more bytes than the header counts", StreamErrorKind::InvalidInput))
+        }
+        self.bytes_left -= bytes.len() as u64;
+        match &self.codes{
+            Some(codes) =>
+                codes.encode(bytes, &mut self.bit_writer)
+                    .map_err(|_| StreamError::new("stream stores a byte the weights didn't count", StreamErrorKind::InvalidInput))?,
+            None =>
+                self.bit_writer.write_bytes(bytes),
+        }
+        Ok(self.bit_writer.take_bytes())
+    }
+
+    /// Return the rest of the output (the last, padded byte of the compressed data).
+    ///
+    /// # Errors
+    /// ---
+    /// With [`StreamErrorKind::InvalidInput`][InvalidInput] when there were fewer bytes
+    /// than the header counts, or they were compressed into a different number of bits
+    /// than the header states (they were different bytes than the weights counted,
+    /// or the weights weren't the exact counts, see [`finish_with_header_byte`](#method.finish_with_header_byte)).
+    ///
+    /// [InvalidInput]:StreamErrorKind::InvalidInput
+    pub fn finish(self) -> Result<Vec<u8>, StreamError>{
+        let written_header_byte = self.header_byte(self.data_padding_bits);
+        let (last_bytes, header_byte) = self.finish_with_header_byte()?;
+        if header_byte != written_header_byte{
+            return Err(StreamError::new("stream stores different bytes than the weights counted", StreamErrorKind::InvalidInput))
+        }
+        Ok(last_bytes)
+    }
+
+    /// Return the rest of the output (the last, padded byte of the compressed data),
+    /// along with the first byte of the header, stating the padding of the bits actually written.
+    ///
+    /// The output is only valid with that byte written over the first one,
+    /// which it only differs from when the weights weren't the exact counts.
+    ///
+    /// # Errors
+    /// ---
+    /// With [`StreamErrorKind::InvalidInput`][InvalidInput] when there were fewer bytes
+    /// than the header counts.
+    ///
+    /// [InvalidInput]:StreamErrorKind::InvalidInput
+    pub fn finish_with_header_byte(self) -> Result<(Vec<u8>, u8), StreamError>{
+        if self.bytes_left != 0{
+            return Err(StreamError::new("stream stores fewer bytes than the header counts", StreamErrorKind::InvalidInput))
+        }
+        // stored bytes are written whole, so they're never padded
+        let header_byte = self.header_byte(0);
+        let (last_bytes, padding_bits) = self.bit_writer.finish();
+        Ok((last_bytes, header_byte + padding_bits))
+    }
+
+    /// Return the first byte of the header with the provided data padding
+    fn header_byte(&self, data_padding_bits: u8) -> u8{
+        match self.codes{
+            Some(_) => (self.tree_padding_bits << 4) + data_padding_bits,
+            None => STORED_FLAG,
+        }
+    }
+}
+
+/// Decoder reading the [`PackedData`][packed] byte representation (the `.hff` format)
+/// one part at a time, without holding all of it in memory.
+///
+/// Parts of any length are passed to [`decode`](#method.decode), which returns the
+/// bytes decoded from them, and [`finish`](#method.finish) returns the rest and checks
+/// that the data was complete. The last byte of every part is held back until
+/// the next one, as only the very last byte of the data has padding.
+///
+/// It doesn't do any IO on its own, so the same decoder is used with blocking and async readers.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::{
+///     prelude::compress_or_store,
+///     stream::StreamDecoder,
+/// };
+///
+/// let bytes = b"abbcccddddeeeee".repeat(4);
+/// let packed_bytes = compress_or_store(&bytes).to_bytes();
+///
+/// let mut decoder = StreamDecoder::new();
+/// let mut output = Vec::new();
+/// for part in packed_bytes.chunks(3){
+///     output.extend(decoder.decode(part).unwrap());
+/// }
+/// assert!(decoder.tree().is_some());
+/// output.extend(decoder.finish().unwrap());
+///
+/// assert_eq!(output, bytes);
+/// ```
+///
+/// [packed]:crate::comp::PackedData::to_bytes
+#[derive(Debug, Clone, Default)]
+pub struct StreamDecoder{
+    state: DecodeState,
+    /// Bytes of the header (or the tree) read so far
+    header: Vec<u8>,
+}
+
+/// Part of the stream a [`StreamDecoder`] is in
+#[derive(Debug, Clone, Default)]
+enum DecodeState{
+    /// Reading the padding bits, tree length and byte count
+    #[default]
+    Header,
+    /// Reading the tree's binary representation
+    Tree{
+        tree_len: usize,
+        tree_padding_bits: u8,
+        data_padding_bits: u8,
+        byte_count: u64,
+    },
+    /// Copying the rest of the stream as it is
+    Stored,
+    /// Decoding the compressed data
+    Data(DataDecoder),
+}
+
+/// State of the compressed data's decoding, walking the tree flattened like in [`FlatTree`]
+#[derive(Debug, Clone)]
+struct DataDecoder{
+    tree: HuffTree<u8>,
+    table: Vec<u16>,
+    /// Index of the current branch's children in the table, 0 meaning the root
+    index: usize,
+    inside_code: bool,
+    padding_bits: u8,
+    bytes_left: u64,
+    /// Last byte read, held back until it's known whether it's padded
+    last_byte: Option<u8>,
+}
+
+impl DataDecoder{
+    /// Decode every bit read from bits into output
+    fn decode_bits(&mut self, mut bits: BitReader, output: &mut Vec<u8>) -> Result<(), StreamError>{
+        while let Some(bit) = bits.read_bit(){
+            if self.bytes_left == 0{
+                return Err(StreamError::new("compressed data stores more bytes than its header states", StreamErrorKind::InvalidData))
+            }
+            let entry = self.table[self.index + bit as usize];
+            if entry & FlatTree::LEAF_FLAG != 0{
+                output.push(entry as u8);
+                self.index = 0;
+                self.inside_code = false;
+                self.bytes_left -= 1;
+            }
+            else{
+                self.index = entry as usize;
+                self.inside_code = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StreamDecoder{
+    /// Initialize a new `StreamDecoder`, expecting the beginning of the stream
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// Return the tree the data is compressed with, once the whole
+    /// header has been read and the data isn't stored uncompressed
+    pub fn tree(&self) -> Option<&HuffTree<u8>>{
+        match &self.state{
+            DecodeState::Data(data) => Some(&data.tree),
+            _ => None,
+        }
+    }
+
+    /// Return true if the header has been read, and it says that
+    /// the rest of the stream is stored uncompressed
+    pub fn is_stored(&self) -> bool{
+        matches!(self.state, DecodeState::Stored)
+    }
+
+    /// Decode the next part of the stream, returning the bytes decoded from it
+    /// (which can be none, for example when the part only stores the header).
+    ///
+    /// # Errors
+    /// ---
+    /// 1. With [`StreamErrorKind::InvalidHeader`][InvalidHeader] when the header stores
+    ///    invalid padding bits or an invalid tree
+    /// 2. With [`StreamErrorKind::InvalidData`][InvalidData] when the compressed data
+    ///    stores more bytes than the header states
+    ///
+    /// [InvalidHeader]:StreamErrorKind::InvalidHeader
+    /// [InvalidData]:StreamErrorKind::InvalidData
+    pub fn decode(&mut self, mut bytes: &[u8]) -> Result<Vec<u8>, StreamError>{
+        let invalid_header = || StreamError::new("stream stores invalid header information", StreamErrorKind::InvalidHeader);
+
+        let mut output = Vec::new();
+        while !bytes.is_empty(){
+            match &mut self.state{
+                DecodeState::Header =>{
+                    // if the stream is stored uncompressed, there's nothing more in the header
+                    if self.header.is_empty() && bytes[0] & STORED_FLAG != 0{
+                        if bytes[0] != STORED_FLAG{
+                            return Err(invalid_header())
+                        }
+                        self.state = DecodeState::Stored;
+                        bytes = &bytes[1..];
+                        continue
+                    }
+                    bytes = take_into(&mut self.header, bytes, HEADER_LEN);
+                    if self.header.len() < HEADER_LEN{
+                        continue
+                    }
+
+                    let tree_padding_bits = self.header[0] >> 4;
+                    let data_padding_bits = self.header[0] & 0b0000_1111;
+                    if tree_padding_bits > 7 || data_padding_bits > 7{
+                        return Err(invalid_header())
+                    }
+                    self.state = DecodeState::Tree{
+                        tree_len: u32::from_be_bytes(self.header[1..5].try_into().unwrap()) as usize,
+                        tree_padding_bits,
+                        data_padding_bits,
+                        byte_count: u64::from_be_bytes(self.header[5..HEADER_LEN].try_into().unwrap()),
+                    };
+                    self.header.clear();
+                }
+                DecodeState::Tree{tree_len, tree_padding_bits, data_padding_bits, byte_count} =>{
+                    bytes = take_into(&mut self.header, bytes, *tree_len);
+                    if self.header.len() < *tree_len{
+                        continue
+                    }
+
+                    // the table is read from the header itself, which may store
+                    // any tree, not only one that would fit into it
+                    let tree_bytes = core::mem::take(&mut self.header);
+                    let mut table = vec![0; FlatTree::MAX_LEN];
+                    let table_len = FlatTree::from_bin(&tree_bytes, *tree_padding_bits, &mut table)
+                        .map_err(|_| invalid_header())?
+                        .table()
+                        .len();
+                    table.truncate(table_len);
+
+                    let mut tree_bin = BitVec::from_vec(tree_bytes);
+                    tree_bin.truncate(tree_bin.len().saturating_sub(*tree_padding_bits as usize));
+                    let tree = HuffTree::<u8>::try_from_bin(tree_bin).map_err(|_| invalid_header())?;
+                    self.state = DecodeState::Data(DataDecoder{
+                        table,
+                        tree,
+                        index: 0,
+                        inside_code: false,
+                        padding_bits: *data_padding_bits,
+                        bytes_left: *byte_count,
+                        last_byte: None,
+                    });
+                }
+                DecodeState::Stored =>{
+                    output.extend_from_slice(bytes);
+                    bytes = &[];
+                }
+                DecodeState::Data(data) =>{
+                    // only the last byte of the stream is padded, so hold back
+                    // the last one of this part, and decode the one held back before
+                    if let Some(last_byte) = data.last_byte.take(){
+                        data.decode_bits(BitReader::new(&[last_byte], 0), &mut output)?;
+                    }
+                    let (last_byte, rest) = bytes.split_last().unwrap();
+                    data.decode_bits(BitReader::new(rest, 0), &mut output)?;
+                    data.last_byte = Some(*last_byte);
+                    bytes = &[];
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Decode the held back last byte of the stream, returning the bytes decoded from it.
+    ///
+    /// # Errors
+    /// ---
+    /// 1. With [`StreamErrorKind::MissingHeader`][MissingHeader] when the stream
+    ///    ended before the whole header was read
+    /// 2. With [`StreamErrorKind::InvalidData`][InvalidData] when the compressed data
+    ///    doesn't store exactly as many bytes as the header states,
+    ///    or ends in the middle of a byte's code
+    ///
+    /// [MissingHeader]:StreamErrorKind::MissingHeader
+    /// [InvalidData]:StreamErrorKind::InvalidData
+    pub fn finish(self) -> Result<Vec<u8>, StreamError>{
+        let mut data = match self.state{
+            DecodeState::Header | DecodeState::Tree{..} =>
+                return Err(StreamError::new("stream too short to decompress, missing header information", StreamErrorKind::MissingHeader)),
+            DecodeState::Stored =>
+                return Ok(Vec::new()),
+            DecodeState::Data(data) => data,
+        };
+
+        let mut output = Vec::new();
+        if let Some(last_byte) = data.last_byte.take(){
+            data.decode_bits(BitReader::new(&[last_byte], data.padding_bits), &mut output)?;
+        }
+        if data.inside_code{
+            return Err(StreamError::new("compressed data ends in the middle of a byte's code", StreamErrorKind::InvalidData))
+        }
+        if data.bytes_left != 0{
+            return Err(StreamError::new("compressed data stores fewer bytes than its header states", StreamErrorKind::InvalidData))
+        }
+        Ok(output)
+    }
+}
+
+/// Move bytes into buf until it's len long,
+/// returning the ones that didn't fit
+fn take_into<'a>(buf: &mut Vec<u8>, bytes: &'a [u8], len: usize) -> &'a [u8]{
+    let taken = len.saturating_sub(buf.len()).min(bytes.len());
+    buf.extend_from_slice(&bytes[..taken]);
+    &bytes[taken..]
+}
+
+/// Every kind of [`StreamError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StreamErrorKind{
+    /// The bytes passed to a [`StreamEncoder`] don't match the weights it was initialized with
+    InvalidInput,
+    /// The stream ended before the whole header was read
+    MissingHeader,
+    /// The header stores invalid padding bits or an invalid tree
+    InvalidHeader,
+    /// The compressed data doesn't match its header (e.g. the byte count)
+    InvalidData,
+}
+
+/// Error encountered while encoding or decoding a stream with
+/// [`StreamEncoder`] or [`StreamDecoder`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamError{
+    message: Cow<'static, str>,
+    kind: StreamErrorKind,
+}
+
+impl fmt::Display for StreamError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl core::error::Error for StreamError{}
+
+impl StreamError{
+    /// Initialize a new `StreamError` with the given message and kind
+    pub fn new(message: &'static str, kind: StreamErrorKind) -> Self{
+        Self{
+            message: Cow::Borrowed(message),
+            kind,
+        }
+    }
+
+    /// Return the message
+    pub fn message(&self) -> &str{
+        &self.message
+    }
+
+    /// Return the kind of the error
+    pub fn kind(&self) -> StreamErrorKind{
+        self.kind
+    }
+}
@@ -1,313 +1,205 @@
-use crate::bitvec::prelude::{BitVec, Msb0};
 use super::{
-    leaf::HuffLeaf, 
+    leaf::HuffLeaf,
     letter::HuffLetter,
+    node::HuffNode,
 };
 
-use std::cmp::Ordering;
+use core::fmt;
+use alloc::{
+    format,
+    string::String,
+    vec,
+};
 
-/// Struct representing a branch in the [`HuffTree`][tree] struct. 
-/// It contains data stored in a [`HuffLeaf`][leaf] (letter, weight and code) and 
+/// Struct representing a branch in the [`HuffTree`][tree] struct.
+/// It gives access to data stored in a [`HuffLeaf`][leaf] (letter, weight and code) and
 /// optionally two child [`HuffBranch`es][branch] (left and right)
 ///
-/// Examples 
+/// The branches are stored by the tree in a single array, and a `HuffBranch`
+/// is only a view into it, so it's cheap to copy around
+/// (obtained with [`HuffTree::root`][root] and then the children getters)
+///
+/// Examples
 /// ---
-/// Initializing a branch "by hand":
+/// Walking down the branches:
 /// ```
-/// use huff_coding::prelude::{HuffBranch, HuffLeaf};
-/// use std::cell::RefCell;
-/// 
-/// let foo = HuffBranch::new(
-///     HuffLeaf::new(
-///         None,
-///         5
-///     ),
-///     Some((
-///         HuffBranch::new(
-///             HuffLeaf::new(
-///                 Some(0xbb),
-///                 2
-///             ),
-///             None
-///         ),
-///         HuffBranch::new(
-///             HuffLeaf::new(
-///                 Some(0xcc),
-///                 3
-///             ),
-///             None
-///         ),
-///     ))
-/// );
+/// use huff_coding::prelude::{HuffTree, ByteWeights};
+///
+/// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"));
+///
+/// let root = tree.root();
+/// assert!(root.has_children());
+/// assert_eq!(root.leaf().weight(), 6);
+///
+/// let c = root.left_child().unwrap();
+/// assert!(!c.has_children());
+/// assert_eq!(c.leaf().letter(), Some(&b'c'));
+///
+/// let b = root.right_child().unwrap().right_child().unwrap();
+/// assert_eq!(b.leaf().letter(), Some(&b'b'));
+/// assert_eq!(b.leaf().weight(), 2);
 /// ```
 /// Iterating over the children of a [`HuffBranch`][branch]:
 /// ```
-/// use huff_coding::prelude::{HuffBranch, HuffLeaf};
-/// use std::cell::RefCell;
-/// 
-/// let foo = HuffBranch::new(
-///     HuffLeaf::new(
-///         None,
-///         9
-///     ),
-///     Some((
-///         HuffBranch::new(
-///             HuffLeaf::new(
-///                 Some(-423),
-///                 7
-///             ),
-///             None
-///         ),
-///         HuffBranch::new(
-///             HuffLeaf::new(
-///                 Some(8),
-///                 2
-///             ),
-///             None
-///         ),
-///     ))
-/// );
-/// 
-/// let mut children_iter = foo.children_iter().unwrap();
-/// 
+/// use huff_coding::prelude::{HuffTree, ByteWeights};
+///
+/// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"));
+///
+/// let mut children_iter = tree.root().children_iter().unwrap();
+///
 /// assert_eq!(
 ///     children_iter.next().unwrap().leaf().letter(),
-///     Some(&-423)
+///     Some(&b'c')
 /// );
-/// 
+///
 /// assert_eq!(
 ///     children_iter.next().unwrap().leaf().letter(),
-///     Some(&8)
-/// );
-/// 
-/// assert_eq!(
-///     children_iter.next(),
-///     None
-/// );
-/// 
-/// ```
-/// Comparing different branches:
-/// ```
-/// use huff_coding::prelude::{HuffBranch, HuffLeaf};
-/// 
-/// let foo = HuffBranch::new(
-///     HuffLeaf::new(
-///         Some('t'),
-///         2137
-///     ),
-///     None
-/// );
-/// 
-///  let bar = HuffBranch::new(
-///     HuffLeaf::new(
-///         Some('x'),
-///         144
-///     ),
-///     None
-/// );
-/// 
-/// let foobar = HuffBranch::new(
-///     HuffLeaf::new(
-///         Some('t'),
-///         144
-///     ),
-///     None
-/// );
-/// 
-/// assert!(foo > bar);
-/// assert!(bar == foobar);
-/// assert!(foobar != foo);
-/// ```
-/// Setting the children after initialization:
-/// ```
-/// use huff_coding::prelude::{HuffBranch, HuffLeaf};
-/// use std::cell::RefCell;
-/// 
-/// let mut foo = HuffBranch::new(
-///     HuffLeaf::new(
-///         Some("yś"),
-///         12
-///     ),
 ///     None
 /// );
-/// 
-/// foo.set_children(
-///     Some((
-///         HuffBranch::new(
-///             HuffLeaf::new(
-///                 Some("yś"),
-///                 2
-///             ),
-///             None
-///         ),
-///         HuffBranch::new(
-///             HuffLeaf::new(
-///                 Some("omamale"),
-///                 10
-///             ),
-///             None
-///         )
-///     ))
-/// );
+///
+/// assert!(children_iter.next().is_none());
 /// ```
-/// 
+///
 /// [leaf]:crate::tree::leaf::HuffLeaf
 /// [tree]:crate::tree::HuffTree
+/// [root]:crate::tree::HuffTree::root
 /// [branch]:HuffBranch
-#[derive(Debug, Clone, Eq)]
-pub struct HuffBranch<L: HuffLetter>{
-    leaf: HuffLeaf<L>,
-    left_child: Option<Box<HuffBranch<L>>>,
-    right_child: Option<Box<HuffBranch<L>>>,
+pub struct HuffBranch<'a, L: HuffLetter>{
+    nodes: &'a [HuffNode<L>],
+    index: usize,
 }
 
-impl<L: HuffLetter> Ord for HuffBranch<L>{
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.leaf().cmp(other.leaf())
+impl<'a, L: HuffLetter> Clone for HuffBranch<'a, L>{
+    fn clone(&self) -> Self{
+        *self
     }
 }
 
-impl<L: HuffLetter> PartialOrd for HuffBranch<L>{
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
+impl<'a, L: HuffLetter> Copy for HuffBranch<'a, L>{}
+
+impl<'a, L: HuffLetter> fmt::Debug for HuffBranch<'a, L>{
+    /// Format the branch like a derived implementation would, with its children nested in it,
+    /// but without recursing into them, so that deep trees can't overflow the stack
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Part of the output left to write, the nesting level being the pretty printed indent
+        enum Part<'a, L: HuffLetter>{
+            Branch(HuffBranch<'a, L>, usize),
+            Child(&'static str, Option<HuffBranch<'a, L>>, usize),
+            Text(String),
+        }
 
-impl<L: HuffLetter> PartialEq for HuffBranch<L>{
-    fn eq(&self, other: &Self) -> bool {
-        self.leaf() == other.leaf()
+        let pretty = f.alternate();
+        let indent = |level: usize| "    ".repeat(level);
+        let mut parts = vec![Part::Branch(*self, 0)];
+        while let Some(part) = parts.pop(){
+            match part{
+                Part::Branch(branch, level) =>{
+                    if pretty{
+                        let leaf = format!("{:#?}", branch.leaf()).replace('\n', &format!("\n{}", indent(level + 1)));
+                        write!(f, "HuffBranch {{\n{}leaf: {},\n", indent(level + 1), leaf)?;
+                        parts.push(Part::Text(format!("{}}}", indent(level))));
+                    }
+                    else{
+                        write!(f, "HuffBranch {{ leaf: {:?}", branch.leaf())?;
+                        parts.push(Part::Text(String::from(" }")));
+                    }
+                    // the left child is popped (written) first
+                    parts.push(Part::Child("right_child", branch.right_child(), level + 1));
+                    parts.push(Part::Child("left_child", branch.left_child(), level + 1));
+                }
+                Part::Child(name, child, level) => match (child, pretty){
+                    (None, true) => writeln!(f, "{}{}: None,", indent(level), name)?,
+                    (None, false) => write!(f, ", {}: None", name)?,
+                    (Some(child), true) =>{
+                        write!(f, "{}{}: Some(\n{}", indent(level), name, indent(level + 1))?;
+                        parts.push(Part::Text(format!(",\n{}),\n", indent(level))));
+                        parts.push(Part::Branch(child, level + 1));
+                    }
+                    (Some(child), false) =>{
+                        write!(f, ", {}: Some(", name)?;
+                        parts.push(Part::Text(String::from(")")));
+                        parts.push(Part::Branch(child, level));
+                    }
+                },
+                Part::Text(text) => f.write_str(&text)?,
+            }
+        }
+        Ok(())
     }
 }
 
-impl<L: HuffLetter> HuffBranch<L>{
-    /// Initialize a new `HuffBranch<L>` with the given leaf and children
-    /// 
-    /// In the provided tuple:
-    /// * 0 means left_child
-    /// * 1 means right_child
-    pub fn new(leaf: HuffLeaf<L>, children: Option<(HuffBranch<L>, HuffBranch<L>)>) -> Self{
-        if let Some(children) = children{
-            HuffBranch{
-                leaf,
-                left_child: Some(Box::new(children.0)),
-                right_child: Some(Box::new(children.1)),
-            }
-        }
-        else{
-            HuffBranch{
-                leaf,
-                left_child: None,
-                right_child: None,
-            }
+impl<'a, L: HuffLetter> HuffBranch<'a, L>{
+    /// Initialize a view of the branch stored at index in the tree's nodes
+    pub(crate) fn new(nodes: &'a [HuffNode<L>], index: usize) -> Self{
+        HuffBranch{
+            nodes,
+            index,
         }
     }
 
-    /// Return a reference to the [`HuffLeaf`][crate::tree::leaf::HuffLeaf] containing the branch's
+    /// Return the [`HuffLeaf`][crate::tree::leaf::HuffLeaf] containing the branch's
     /// letter, weight and code.
-    pub fn leaf(&self) -> &HuffLeaf<L>{
-        &self.leaf
+    pub fn leaf(&self) -> HuffLeaf<'a, L>{
+        HuffLeaf::new(self.nodes, self.index)
     }
 
-    /// Return an iterator over the branch's children (`&HuffBranch<L>`])
+    /// Return an iterator over the branch's children (`HuffBranch<L>`)
     /// or [`None`][None] if it has no children
-    /// 
+    ///
     /// # Example
     /// ---
     /// ```
-    /// use huff_coding::prelude::{HuffBranch, HuffLeaf};
-    /// 
-    /// let branch = HuffBranch::new(
-    ///     HuffLeaf::new(None, 7),
-    ///     Some((
-    ///         HuffBranch::new(
-    ///             HuffLeaf::new(Some(5), 3), 
-    ///             None,
-    ///         ),
-    ///         HuffBranch::new(
-    ///             HuffLeaf::new(Some(42), 4), 
-    ///             None,
-    ///         )
-    ///     ))
-    /// );
-    /// 
-    /// let mut children_iter = branch.children_iter().unwrap();
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"));
+    ///
+    /// let mut children_iter = tree.root().children_iter().unwrap();
     /// assert_eq!(
     ///     children_iter.next().unwrap()
     ///         .leaf()
-    ///         .letter(),
-    ///     Some(&5)
+    ///         .weight(),
+    ///     3
     /// );
     /// assert_eq!(
     ///     children_iter.next().unwrap()
     ///         .leaf()
-    ///         .letter(),
-    ///     Some(&42)
+    ///         .weight(),
+    ///     3
     /// );
     /// ```
-    pub fn children_iter(&self) -> Option<ChildrenIter<L>>{
-        if self.has_children(){Some(ChildrenIter::new(self))}
+    pub fn children_iter(&self) -> Option<ChildrenIter<'a, L>>{
+        if self.has_children(){Some(ChildrenIter::new(*self))}
         else{None}
     }
 
-    /// Return a reference to the left child of the branch `HuffBranch<L>`, or 
+    /// Return the left child of the branch `HuffBranch<L>`, or
     /// [`None`][None] if it has no children
-    pub fn left_child(&self) -> Option<&HuffBranch<L>>{
-        self.left_child.as_deref()
+    pub fn left_child(&self) -> Option<HuffBranch<'a, L>>{
+        self.nodes[self.index].children
+            .map(|(left, _)| HuffBranch::new(self.nodes, left))
     }
 
-    /// Return a mutable reference to the left child of the branch `HuffBranch<L>`, or 
+    /// Return the right child of the branch `HuffBranch<L>`, or
     /// [`None`][None] if it has no children
-    pub fn left_child_mut(&mut self) -> Option<&mut HuffBranch<L>>{
-        self.left_child.as_deref_mut()
-    }
-
-    /// Return a reference to the right child of the branch `HuffBranch<L>`, or 
-    /// [`None`][None] if it has no children
-    pub fn right_child(&self) -> Option<&HuffBranch<L>>{
-        self.right_child.as_deref()
-    }
-
-    /// Return a mutable reference to the right child of the branch `HuffBranch<L>`, or 
-    /// [`None`][None] if it has no children
-    pub fn right_child_mut(&mut self) -> Option<&mut HuffBranch<L>>{
-        self.right_child.as_deref_mut()
+    pub fn right_child(&self) -> Option<HuffBranch<'a, L>>{
+        self.nodes[self.index].children
+            .map(|(_, right)| HuffBranch::new(self.nodes, right))
     }
 
     /// Return true if the branch has children
     pub fn has_children(&self) -> bool{
-        self.left_child.is_some()
-    }
-
-    /// Setter for the branch's children
-    /// 
-    /// In the provided tuple:
-    /// * 0 means left_child
-    /// * 1 means right_child
-    pub fn set_children(&mut self, children: Option<(HuffBranch<L>, HuffBranch<L>)>){
-        if let Some(children) = children{
-            self.left_child = Some(Box::new(children.0));
-            self.right_child = Some(Box::new(children.1));  
-        }
-        else{
-            self.left_child = None;
-            self.right_child = None;
-        }
-    }
-
-    /// Setter for the leaf's code 
-    pub fn set_code(&mut self, code: BitVec<Msb0, u8>){
-        self.leaf.set_code(code);
+        self.nodes[self.index].children.is_some()
     }
 }
 
 /// An iterator over a [`HuffBranch`'s][HuffBranch] children
 pub struct ChildrenIter<'a, L: HuffLetter>{
-    parent: &'a HuffBranch<L>,
+    parent: HuffBranch<'a, L>,
     child_pos: u8,
 }
 
 impl<'a, L: HuffLetter> Iterator for ChildrenIter<'a, L>{
-    type Item = &'a HuffBranch<L>;
+    type Item = HuffBranch<'a, L>;
 
     fn next(&mut self) -> Option<Self::Item>{
         match self.child_pos{
@@ -319,16 +211,16 @@ impl<'a, L: HuffLetter> Iterator for ChildrenIter<'a, L>{
                 self.child_pos += 1;
                 self.parent.right_child()
             }
-            _ => 
+            _ =>
                 None,
         }
     }
 }
 
 impl<'a, L: HuffLetter> ChildrenIter<'a, L>{
-    /// Initialize a new ```ChildrenIter``` over 
+    /// Initialize a new ```ChildrenIter``` over
     /// the children of the provided ```HuffBranch```
-    pub fn new(parent: &'a HuffBranch<L>) -> Self{
+    pub fn new(parent: HuffBranch<'a, L>) -> Self{
         ChildrenIter{
             parent,
             child_pos: 0,
@@ -1,36 +1,31 @@
-pub use crate::weights::Weights;
-use super::{
-    letter::HuffLetter,
-    branch::HuffBranch, 
-    leaf::HuffLeaf, 
-};
-
-use std::{
-    collections::BinaryHeap,
-    cmp::Ordering,
-};
+use alloc::collections::BinaryHeap;
+use core::cmp::Ordering;
 
 /// A struct used to build a `HuffTree`
 /// 
-/// Stores `HuffBranch`es inside a `std::collections::BinaryHeap`, but reversed
+/// Stores the indexes of branches in the tree's arena, along with their weights,
+/// inside a `std::collections::BinaryHeap`, but reversed
 /// (branches with the smallest weights are at the end, so they can be easily popped)
+/// 
+/// Branches with equal weights are popped according to the heap's [`TieBreak`],
+/// then in the order they were pushed, so the same weights 
+/// (iterated in the same order) always build the same tree
 #[derive(Debug, Clone)]
-pub struct HuffBranchHeap<L: HuffLetter>{
-    heap: BinaryHeap<HuffBranchHeapItem<L>>,
+pub struct HuffBranchHeap{
+    heap: BinaryHeap<HuffBranchHeapItem>,
+    pushed_count: usize,
+    tie_break: TieBreak,
 }
 
-impl<L: HuffLetter> HuffBranchHeap<L>{
-    /// Initialize a new `HuffBranchHeap` from the given weights struct
-    pub fn from_weights<W: Weights<L>>(weights: W) -> Self{
-        let mut heap = HuffBranchHeap::new();
-        heap.build(weights);
-        heap
-    }
-
-    /// Initialize an empty `HuffBranchHeap`
-    pub fn new() -> Self{
-        HuffBranchHeap::<L>{
-            heap: BinaryHeap::new(),
+impl HuffBranchHeap{
+    /// Initialize an empty `HuffBranchHeap`, able to store at least capacity
+    /// branches without reallocating, and breaking ties between 
+    /// equal weights with the given `TieBreak`
+    pub fn with_capacity(capacity: usize, tie_break: TieBreak) -> Self{
+        HuffBranchHeap{
+            heap: BinaryHeap::with_capacity(capacity),
+            pushed_count: 0,
+            tie_break,
         }
     }
 
@@ -39,52 +34,73 @@ impl<L: HuffLetter> HuffBranchHeap<L>{
         self.heap.len()
     }
 
-    /// Push a branch onto the heap
-    pub fn push(&mut self, branch: HuffBranch<L>){
-        self.heap.push(HuffBranchHeapItem(branch));
+    /// Push the index of a branch with the given weight onto the heap
+    pub fn push(&mut self, index: usize, weight: usize, is_joint: bool){
+        let rank = match self.tie_break{
+            TieBreak::CombinedLast => is_joint as u8,
+            TieBreak::CombinedFirst => !is_joint as u8,
+        };
+        self.heap.push(HuffBranchHeapItem{
+            weight,
+            rank,
+            seq: self.pushed_count,
+            index,
+        });
+        self.pushed_count += 1;
     }
 
-    /// Pop the `HuffBranch` with the smallest weight
-    pub fn pop_min(&mut self) -> HuffBranch<L>{
-        self.heap.pop().unwrap().unwrap()
+    /// Pop the index of the branch with the smallest weight
+    pub fn pop_min(&mut self) -> usize{
+        self.heap.pop().unwrap().index
     }
+}
 
-    fn build<W: Weights<L>>(&mut self, weights: W){
-        for (l, f) in weights.into_iter(){
-            let new_branch = HuffBranch::new(HuffLeaf::new(Some(l), f), None);
-    
-            self.push(new_branch);
-        }
-    }
+/// Way of choosing between [`HuffBranch`es][crate::tree::branch::HuffBranch]
+/// of equal weights while building a [`HuffTree`][crate::tree::HuffTree]
+/// (see [`HuffTree::from_weights_with`][crate::tree::HuffTree::from_weights_with])
+/// 
+/// Every choice gives codes of the same (optimal) total length,
+/// but not of the same maximum length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TieBreak{
+    /// Combine letters before already combined branches of the same weight.
+    /// 
+    /// Gives minimum-variance codes, whose maximum length 
+    /// is the smallest possible for the given weights (the default)
+    #[default]
+    CombinedLast,
+    /// Combine already combined branches before letters of the same weight,
+    /// making the tree as deep as the weights allow
+    CombinedFirst,
 }
 
-/// A wrapper for `HuffBranch` that reverses it's cmp
-/// for the smallest HuffBranches to appear at the end of the 
-/// `HuffBranchHeap`
+/// A branch's index (with its weight, rank on ties and the number of branches pushed before it) 
+/// that reverses it's cmp for the smallest (and lowest ranked, then earliest pushed on ties) 
+/// branches to appear at the end of the `HuffBranchHeap`
 #[derive(Debug, Clone, Eq)]
-struct HuffBranchHeapItem<L: HuffLetter>(HuffBranch<L>);
+struct HuffBranchHeapItem{
+    weight: usize,
+    rank: u8,
+    seq: usize,
+    index: usize,
+}
 
-impl<L: HuffLetter> Ord for HuffBranchHeapItem<L>{
+impl Ord for HuffBranchHeapItem{
     fn cmp(&self, other: &Self) -> Ordering {
-        other.0.leaf().cmp(&self.0.leaf())
+        other.weight.cmp(&self.weight)
+            .then_with(|| other.rank.cmp(&self.rank))
+            .then_with(|| other.seq.cmp(&self.seq))
     }
 }
 
-impl<L: HuffLetter> PartialOrd for HuffBranchHeapItem<L>{
+impl PartialOrd for HuffBranchHeapItem{
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<L: HuffLetter> PartialEq for HuffBranchHeapItem<L>{
+impl PartialEq for HuffBranchHeapItem{
     fn eq(&self, other: &Self) -> bool {
-        self.0.leaf().weight() == other.0.leaf().weight()
-    }
-}
-
-impl<L: HuffLetter> HuffBranchHeapItem<L>{
-    /// Consumes self and returns the wrapped branch
-    pub fn unwrap(self) -> HuffBranch<L>{
-        self.0
+        self.cmp(other) == Ordering::Equal
     }
 }
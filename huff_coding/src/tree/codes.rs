@@ -0,0 +1,236 @@
+use crate::{
+    prelude::*,
+    bits::BitWriter,
+    comp::errors::CompressError,
+    bitvec::prelude::{BitSlice, BitVec, Msb0},
+};
+
+use crate::map::LetterMap;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+/// A letter's code, packed into a `u64` if it's short enough
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Code{
+    Packed{bits: u64, len: u32},
+    Long(BitVec<Msb0, u8>),
+}
+
+impl Code{
+    fn new(code: &BitSlice<Msb0, u8>) -> Self{
+        if code.len() > 64{
+            return Code::Long(code.to_bitvec())
+        }
+        Code::Packed{
+            bits: code.iter().fold(0, |bits, bit| (bits << 1) | *bit as u64),
+            len: code.len() as u32,
+        }
+    }
+
+    fn len(&self) -> usize{
+        match self{
+            Code::Packed{len, ..} => *len as usize,
+            Code::Long(code) => code.len(),
+        }
+    }
+
+    fn to_bitvec(&self) -> BitVec<Msb0, u8>{
+        match self{
+            Code::Packed{bits, len} =>
+                (0..*len).rev().map(|i| (bits >> i) & 1 == 1).collect(),
+            Code::Long(code) => code.clone(),
+        }
+    }
+
+    fn write(&self, writer: &mut BitWriter){
+        match self{
+            Code::Packed{bits, len} => writer.write_bits(*bits, *len),
+            Code::Long(code) => writer.write_bitslice(code),
+        }
+    }
+}
+
+/// Codes indexed by the letters, looked up instead of the hashed ones
+#[derive(Debug, Clone)]
+enum CodeTable<L>{
+    /// Codes of [byte letters](HuffLetter::as_byte), in a fixed array
+    Bytes(Box<[Option<Code>; 256]>),
+    /// Codes of [`HuffLetterAsIndex`] letters, along with the function indexing them
+    Indexed(Vec<Option<Code>>, fn(&L) -> usize),
+}
+
+/// Table of every letter's code read once from a [`HuffTree`][HuffTree],
+/// to be reused for encoding many slices of letters.
+///
+/// Codes up to 64 bits long are stored packed into integers.
+/// The codes of bytes (`u8` letters) are always stored in a fixed array of 256 codes
+/// indexed by the bytes, sparing the hashing of every encoded byte, so it's used
+/// by [`compress`][compress] and [`compress_with_tree`][compress_with_tree] as well.
+/// If the letters implement [`HuffLetterAsIndex`][letter_index], the codes
+/// can be stored in an array indexed by them too (see [`from_tree_indexed`](#method.from_tree_indexed)).
+///
+/// The `HuffTree` the codes were read from is kept,
+/// and can be taken back with [`into_tree`](#method.into_tree).
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::{
+///     bits::BitWriter,
+///     prelude::{
+///         HuffTree,
+///         HuffCodes,
+///         ByteWeights,
+///         compress_with_codes,
+///         decompress,
+///     },
+/// };
+///
+/// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"));
+/// let codes = HuffCodes::from_tree_indexed(tree);
+///
+/// assert_eq!(codes.code_len(&b'c'), Some(1));
+/// assert_eq!(codes.code_len(&b'd'), None);
+///
+/// // encode many slices using the same codes
+/// let mut writer = BitWriter::new();
+/// for bytes in [&b"cab"[..], &b"bcc"[..]].iter(){
+///     codes.encode(bytes, &mut writer).unwrap();
+/// }
+/// assert_eq!(writer.finish(), (vec![0b0101_1110, 0b0000_0000], 7));
+///
+/// let comp_data = compress_with_codes(b"abbccc", &codes).unwrap();
+/// assert_eq!(decompress(&comp_data), b"abbccc".to_vec());
+///
+/// let tree = codes.into_tree();
+/// ```
+///
+/// [letter_index]:crate::tree::letter::HuffLetterAsIndex
+/// [compress]:crate::comp::compress
+/// [compress_with_tree]:crate::comp::compress_with_tree
+#[derive(Debug, Clone)]
+pub struct HuffCodes<L: HuffLetter>{
+    codes: LetterMap<L, Code>,
+    table: Option<CodeTable<L>>,
+    huff_tree: HuffTree<L>,
+}
+
+impl<L: HuffLetter> HuffCodes<L>{
+    /// Read every letter's code from the provided [`HuffTree`][HuffTree],
+    /// storing them in an array indexed by the letters as well if they're bytes
+    pub fn from_tree(huff_tree: HuffTree<L>) -> Self{
+        let mut codes = LetterMap::with_capacity(huff_tree.letter_count());
+        let mut byte_codes: Option<Box<[Option<Code>; 256]>> = None;
+        huff_tree.for_each_code(|letter, code|{
+            let code = Code::new(code);
+            if let Some(byte) = letter.as_byte(){
+                byte_codes.get_or_insert_with(|| Box::new(core::array::from_fn(|_| None)))[byte as usize] = Some(code.clone());
+            }
+            codes.insert(letter.clone(), code);
+        });
+        Self{
+            codes,
+            table: byte_codes.map(CodeTable::Bytes),
+            huff_tree,
+        }
+    }
+
+    /// Return the number of stored codes
+    pub fn len(&self) -> usize{
+        self.codes.len()
+    }
+
+    /// Return true if there are no stored codes
+    pub fn is_empty(&self) -> bool{
+        self.codes.is_empty()
+    }
+
+    /// Return true if the codes are looked up in an array indexed by the letters
+    /// (always for bytes, see [`from_tree_indexed`](#method.from_tree_indexed) for other letters)
+    pub fn is_indexed(&self) -> bool{
+        self.table.is_some()
+    }
+
+    /// Return the code of the provided letter,
+    /// or [`None`][None] if there's no code for it
+    pub fn get(&self, letter: &L) -> Option<BitVec<Msb0, u8>>{
+        self.code(letter).map(Code::to_bitvec)
+    }
+
+    /// Return the length of the provided letter's code,
+    /// or [`None`][None] if there's no code for it
+    pub fn code_len(&self, letter: &L) -> Option<usize>{
+        self.code(letter).map(Code::len)
+    }
+
+    /// Return every stored code as a [`HashMap`][HashMap],
+    /// equal to the one returned by [`HuffTree::read_codes`][read_codes]
+    ///
+    /// [read_codes]:HuffTree::read_codes
+    #[cfg(feature = "std")]
+    pub fn to_map(&self) -> HashMap<L, BitVec<Msb0, u8>>{
+        self.codes
+            .iter()
+            .map(|(letter, code)| (letter.clone(), code.to_bitvec()))
+            .collect()
+    }
+
+    /// Return a reference to the [`HuffTree`][HuffTree] the codes were read from
+    pub fn huff_tree(&self) -> &HuffTree<L>{
+        &self.huff_tree
+    }
+
+    /// Consume `self` returning the [`HuffTree`][HuffTree] the codes were read from
+    pub fn into_tree(self) -> HuffTree<L>{
+        self.huff_tree
+    }
+
+    /// Write the code of every provided letter into the [`BitWriter`][BitWriter]
+    ///
+    /// # Errors
+    /// ---
+    /// When there's no code for a letter in the slice.
+    /// The codes of the letters before it are already written by then.
+    pub fn encode(&self, letters: &[L], writer: &mut BitWriter) -> Result<(), CompressError<L>>{
+        for letter in letters{
+            self.code(letter)
+                .ok_or_else(|| CompressError::new("letter not found in codes", letter.clone()))?
+                .write(writer);
+        }
+        Ok(())
+    }
+
+    fn code(&self, letter: &L) -> Option<&Code>{
+        match &self.table{
+            Some(CodeTable::Bytes(table)) =>
+                table[letter.as_byte()? as usize].as_ref(),
+            Some(CodeTable::Indexed(table, as_index)) =>
+                table.get(as_index(letter))?.as_ref(),
+            None =>
+                self.codes.get(letter),
+        }
+    }
+}
+
+impl<L: HuffLetterAsIndex> HuffCodes<L>{
+    /// Read every letter's code from the provided [`HuffTree`][HuffTree],
+    /// storing them in an array indexed by the letters as well
+    /// (of length [`HuffLetterAsIndex::ALPHABET_LEN`][alphabet_len],
+    /// or the fixed one of 256 codes for bytes, like [`from_tree`](#method.from_tree))
+    ///
+    /// [alphabet_len]:crate::tree::letter::HuffLetterAsIndex::ALPHABET_LEN
+    pub fn from_tree_indexed(huff_tree: HuffTree<L>) -> Self{
+        let mut codes = Self::from_tree(huff_tree);
+        if codes.is_indexed(){
+            return codes
+        }
+        let mut table = vec![None; L::ALPHABET_LEN];
+        for (letter, code) in codes.codes.iter(){
+            table[letter.as_index()] = Some(code.clone());
+        }
+        codes.table = Some(CodeTable::Indexed(table, L::as_index));
+        codes
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    prelude::*,
+    bits::BitReader,
+    comp::errors::DecompressError,
+    utils::calc_padding_bits,
+};
+
+use alloc::{
+    borrow::Cow,
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+/// A [`HuffTree<u8>`][HuffTree] flattened into an array of `u16`s, able to decode
+/// compressed bytes into a provided buffer without allocating anything.
+///
+/// The array is only borrowed, so it can be a `static` generated at build time
+/// (see [`HuffTree::to_flat_table`][to_flat_table]), or a buffer filled at runtime
+/// from the tree's binary representation (see [`from_bin`](#method.from_bin)).
+///
+/// # Layout
+/// ---
+/// Every joint branch takes two consecutive entries, one for each of its children
+/// (the left one first), with the root's children at indexes 0 and 1. An entry is either:
+/// * [`LEAF_FLAG`](#associatedconstant.LEAF_FLAG) `| letter` for a letter branch
+/// * the index of the child's own children (always even, and after the entry itself)
+///   for a joint branch
+///
+/// A tree of `n` letters takes `2 * (n - 1)` entries, so at most
+/// [`MAX_LEN`](#associatedconstant.MAX_LEN). A tree with only one letter
+/// takes two entries both storing the letter.
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding::prelude::{HuffTree, ByteWeights, FlatTree, compress};
+///
+/// // generated at build time
+/// static TABLE: [u16; 4] = [0x8063, 2, 0x8061, 0x8062];
+///
+/// let bytes = b"abbccc";
+/// let tree = HuffTree::from_weights(ByteWeights::from_bytes(bytes));
+/// assert_eq!(tree.to_flat_table().unwrap(), TABLE.to_vec());
+///
+/// let comp_data = compress(bytes);
+/// let flat_tree = FlatTree::new(&TABLE).unwrap();
+/// let mut buf = [0; 16];
+/// let letter_count = flat_tree.decode_into(
+///     comp_data.comp_bytes(),
+///     comp_data.padding_bits(),
+///     comp_data.letter_count(),
+///     &mut buf
+/// ).unwrap();
+/// assert_eq!(&buf[..letter_count], bytes);
+/// ```
+///
+/// [to_flat_table]:HuffTree::to_flat_table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlatTree<'a>{
+    table: &'a [u16],
+}
+
+impl<'a> FlatTree<'a>{
+    /// Flag set in the entries storing letters
+    pub const LEAF_FLAG: u16 = 0x8000;
+    /// Maximum number of entries a `FlatTree` can take (when storing every byte)
+    pub const MAX_LEN: usize = 510;
+
+    /// Try to initialize a `FlatTree` from an array laid out like described in the
+    /// [struct's docs](#layout) (for example returned by [`HuffTree::to_flat_table`][to_flat_table])
+    ///
+    /// # Errors
+    /// ---
+    /// 1. When the table's length isn't even, or it's empty or longer than [`MAX_LEN`](#associatedconstant.MAX_LEN)
+    /// 2. When an entry stores a letter larger than a byte,
+    ///    or points outside of the table, or to itself or an earlier entry
+    ///
+    /// [to_flat_table]:HuffTree::to_flat_table
+    pub fn new(table: &'a [u16]) -> Result<Self, FlatTreeError>{
+        if table.is_empty() || table.len() > Self::MAX_LEN || !table.len().is_multiple_of(2){
+            return Err(FlatTreeError::new("invalid flat table length"))
+        }
+        for (index, entry) in table.iter().enumerate(){
+            let entry = *entry as usize;
+            if entry & Self::LEAF_FLAG as usize != 0{
+                if entry & !(Self::LEAF_FLAG as usize) > u8::MAX as usize{
+                    return Err(FlatTreeError::new("flat table stores a letter larger than a byte"))
+                }
+            }
+            // pointing forward means that decoding always ends up at a letter
+            else if entry <= index || !entry.is_multiple_of(2) || entry >= table.len(){
+                return Err(FlatTreeError::new("flat table stores an invalid index"))
+            }
+        }
+        Ok(Self{
+            table,
+        })
+    }
+
+    /// Try to read a `FlatTree` from the binary representation of a [`HuffTree<u8>`][HuffTree]
+    /// (see [`HuffTree::as_bin`][as_bin]), stored in bin except for its padding_bits last bits,
+    /// writing it into the beginning of the provided table.
+    ///
+    /// Nothing is allocated, so it's used to read trees at runtime on targets without a heap.
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights, FlatTree};
+    ///
+    /// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"));
+    /// let tree_bin = tree.as_bin();
+    /// let padding_bits = (8 - tree_bin.len() % 8) as u8 % 8;
+    ///
+    /// let mut table = [0; FlatTree::MAX_LEN];
+    /// let flat_tree = FlatTree::from_bin(tree_bin.as_raw_slice(), padding_bits, &mut table).unwrap();
+    /// assert_eq!(flat_tree.table(), &[0x8063, 2, 0x8061, 0x8062]);
+    /// ```
+    ///
+    /// # Errors
+    /// ---
+    /// 1. When padding_bits is larger than 7
+    /// 2. When the bits end before the tree does, or there are bits left after it
+    /// 3. When the table is too short to hold the tree, or the tree
+    ///    has more branches than a tree of bytes can have
+    ///
+    /// [as_bin]:HuffTree::as_bin
+    pub fn from_bin(bin: &[u8], padding_bits: u8, table: &'a mut [u16]) -> Result<Self, FlatTreeError>{
+        /// Return the next letter entry read from bits
+        fn read_letter(bits: &mut BitReader) -> Result<u16, FlatTreeError>{
+            bits.read_bits(8)
+                .map(|letter| FlatTree::LEAF_FLAG | letter as u16)
+                .ok_or_else(|| FlatTreeError::new("provided bin is too small for an encoded HuffTree"))
+        }
+
+        if padding_bits > 7 || (bin.is_empty() && padding_bits != 0){
+            return Err(FlatTreeError::new("invalid padding bits"))
+        }
+        let table_len = table.len().min(Self::MAX_LEN);
+        if table_len < 2{
+            return Err(FlatTreeError::new("provided table is too short"))
+        }
+        let mut bits = BitReader::new(bin, padding_bits);
+        let too_small = || FlatTreeError::new("provided bin is too small for an encoded HuffTree");
+
+        let len = if bits.read_bit().ok_or_else(too_small)?{
+            // indexes of the entries still to be read, the next one on top,
+            // at most one more than the joint branches on the way from the root
+            let mut pending = [0u16; Self::MAX_LEN / 2 + 1];
+            pending[..2].copy_from_slice(&[1, 0]);
+            let mut pending_len = 2;
+            let mut len = 2;
+            while pending_len != 0{
+                pending_len -= 1;
+                let index = pending[pending_len] as usize;
+                if bits.read_bit().ok_or_else(too_small)?{
+                    if len + 2 > table_len{
+                        return Err(FlatTreeError::new("provided table is too short"))
+                    }
+                    table[index] = len as u16;
+                    pending[pending_len..pending_len + 2].copy_from_slice(&[len as u16 + 1, len as u16]);
+                    pending_len += 2;
+                    len += 2;
+                }
+                else{
+                    table[index] = read_letter(&mut bits)?;
+                }
+            }
+            len
+        }
+        else{
+            // a lone letter, both of its codes lead to it
+            let letter = read_letter(&mut bits)?;
+            table[..2].copy_from_slice(&[letter, letter]);
+            2
+        };
+        if !bits.is_empty(){
+            return Err(FlatTreeError::new("provided bin is too big for an encoded HuffTree"))
+        }
+
+        Ok(Self{
+            table: &table[..len],
+        })
+    }
+
+    /// Return the array the tree is stored in
+    pub fn table(&self) -> &'a [u16]{
+        self.table
+    }
+
+    /// Decode exactly letter_count letters from comp_bytes (except for their padding_bits last bits)
+    /// into the beginning of the provided buffer, returning the number of letters written
+    /// (always equal to letter_count).
+    ///
+    /// It decodes the same data as [`CompressDataRef::decompress_into`][decompress_into]
+    /// with the tree the `FlatTree` was made from, but without allocating anything.
+    ///
+    /// # Errors
+    /// ---
+    /// 1. When padding_bits is larger than 7, or the buffer is shorter than letter_count
+    /// 2. When the compressed bytes don't store exactly letter_count letters,
+    ///    or their last code is cut off. The letters decoded
+    ///    before finding that out are still written into the buffer.
+    ///
+    /// [decompress_into]:crate::comp::CompressDataRef::decompress_into
+    pub fn decode_into(&self, comp_bytes: &[u8], padding_bits: u8, letter_count: usize, buf: &mut [u8]) -> Result<usize, DecompressError>{
+        if padding_bits > 7 || (comp_bytes.is_empty() && padding_bits != 0){
+            return Err(DecompressError::new("invalid data padding bits"))
+        }
+        let buf = buf.get_mut(..letter_count).ok_or_else(||
+            DecompressError::new("buffer too short to hold every decompressed letter")
+        )?;
+        let mut bits = BitReader::new(comp_bytes, padding_bits);
+
+        for slot in buf.iter_mut(){
+            let mut index = 0;
+            *slot = loop{
+                let bit = bits.read_bit().ok_or_else(|| DecompressError::new(
+                    if index == 0{"compressed data stores fewer letters than its letter count"}
+                    else{"compressed data ends in the middle of a letter's code"}
+                ))?;
+                let entry = self.table[index + bit as usize];
+                if entry & Self::LEAF_FLAG != 0{
+                    break entry as u8
+                }
+                index = entry as usize;
+            };
+        }
+        if !bits.is_empty(){
+            return Err(DecompressError::new("compressed data stores more letters than its letter count"))
+        }
+
+        Ok(letter_count)
+    }
+}
+
+impl HuffTree<u8>{
+    /// Return the tree flattened into an array of `u16`s, to be used by a [`FlatTree`][FlatTree]
+    /// (see its docs for the layout).
+    ///
+    /// It's meant to be generated at build time (by a build script),
+    /// and included in the source code as a `static` array, so that the tree
+    /// takes no heap memory at runtime.
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"));
+    /// let table = tree.to_flat_table().unwrap();
+    ///
+    /// // write it into a build script's output
+    /// let source = format!("static TABLE: [u16; {}] = {:?};", table.len(), table);
+    /// assert_eq!(source, "static TABLE: [u16; 4] = [32867, 2, 32865, 32866];");
+    /// ```
+    ///
+    /// # Errors
+    /// ---
+    /// When the tree doesn't fit in [`FlatTree::MAX_LEN`](struct.FlatTree.html#associatedconstant.MAX_LEN)
+    /// entries, which would take a tree storing a byte more than once
+    /// (every way of building a tree rejects those).
+    pub fn to_flat_table(&self) -> Result<Vec<u16>, FlatTreeError>{
+        let bin = self.as_bin();
+        let padding_bits = calc_padding_bits(bin.len());
+        let mut table = vec![0; FlatTree::MAX_LEN];
+        let len = FlatTree::from_bin(bin.as_raw_slice(), padding_bits, &mut table)?
+            .table()
+            .len();
+        table.truncate(len);
+        Ok(table)
+    }
+}
+
+/// [Error][std::error::Error] encountered while trying to construct a [`FlatTree`][FlatTree]
+/// with the [`FlatTree::new`](struct.FlatTree.html#method.new) or
+/// [`FlatTree::from_bin`](struct.FlatTree.html#method.from_bin) method
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlatTreeError{
+    message: Cow<'static, str>,
+}
+
+impl fmt::Display for FlatTreeError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl core::error::Error for FlatTreeError{}
+
+impl FlatTreeError{
+    /// Initialize a new `FlatTreeError` with the given message
+    pub fn new(message: &'static str) -> Self{
+        Self{
+            message: Cow::Borrowed(message),
+        }
+    }
+
+    /// Return the message
+    pub fn message(&self) -> &str{
+        &self.message
+    }
+}
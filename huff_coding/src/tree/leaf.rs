@@ -1,79 +1,95 @@
-use crate::bitvec::prelude::{BitVec, Msb0};
-use super::letter::HuffLetter;
+use crate::bitvec::prelude::{bitvec, BitVec, Msb0};
+use super::{
+    letter::HuffLetter,
+    node::HuffNode,
+};
 
-use std::cmp::Ordering;
+use core::fmt;
 
 /// Struct representing a [HuffBranch's][crate::tree::branch::HuffBranch] data.
-/// 
-/// Stores:
-/// * `letter: Option<L>`
+///
+/// Gives access to:
+/// * `letter: Option<&L>`
 ///  * type implementing [`HuffLetter`][crate::tree::letter::HuffLetter]
 ///  * if is a joint branch then `letter == None`
 /// * `weight: usize`
 /// * `code: Option<BitVec<Msb0, u8>>` (big endian)
-/// 
+///
+/// Like the [`HuffBranch`][crate::tree::branch::HuffBranch] itself,
+/// it's only a view into the tree's array of branches.
+///
 /// # Examples
 /// ---
-/// Initialization:
 /// ```
-/// use huff_coding::prelude::{HuffLeaf, HuffLetter};
-/// 
-/// let foo = HuffLeaf::new(Some("oblo"), 12);
-/// let bar = HuffLeaf::new(Some(23), 0);
+/// use huff_coding::{
+///     bitvec::prelude::*,
+///     prelude::{HuffTree, ByteWeights},
+/// };
+///
+/// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbccc"));
+/// let leaf = tree.root().right_child().unwrap().left_child().unwrap().leaf();
+///
+/// assert_eq!(leaf.letter(), Some(&b'a'));
+/// assert_eq!(leaf.weight(), 1);
+/// assert_eq!(leaf.code(), Some(bitvec![Msb0, u8; 1, 0]));
 /// ```
-#[derive(Debug, Eq, Clone)]
-pub struct HuffLeaf<L: HuffLetter>{
-    letter: Option<L>,
-    weight: usize,
-    code: Option<BitVec<Msb0, u8>>,
+pub struct HuffLeaf<'a, L: HuffLetter>{
+    nodes: &'a [HuffNode<L>],
+    index: usize,
 }
 
-impl<L: HuffLetter> Ord for HuffLeaf<L> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.weight.cmp(&other.weight)
+impl<'a, L: HuffLetter> Clone for HuffLeaf<'a, L>{
+    fn clone(&self) -> Self{
+        *self
     }
 }
 
-impl<L: HuffLetter> PartialOrd for HuffLeaf<L> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
+impl<'a, L: HuffLetter> Copy for HuffLeaf<'a, L>{}
 
-impl<L: HuffLetter> PartialEq for HuffLeaf<L>{
-    fn eq(&self, other: &Self) -> bool {
-        self.weight == other.weight
+impl<'a, L: HuffLetter> fmt::Debug for HuffLeaf<'a, L>{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HuffLeaf")
+            .field("letter", &self.letter())
+            .field("weight", &self.weight())
+            .finish()
     }
 }
 
-impl<L: HuffLetter> HuffLeaf<L>{
-    /// Initialize a `HuffLeaf` with the given letter and weight 
-    /// (code is at first set to None and can be changed with the [`set_code`](#method.set_code) method)
-    pub fn new(letter: Option<L>, weight: usize) -> Self{
+impl<'a, L: HuffLetter> HuffLeaf<'a, L>{
+    /// Initialize a view of the data of the branch stored at index in the tree's nodes
+    pub(crate) fn new(nodes: &'a [HuffNode<L>], index: usize) -> Self{
         HuffLeaf{
-            letter,
-            weight,
-            code: None,
+            nodes,
+            index,
         }
     }
 
-    /// Returns a reference to the stored letter 
-    pub fn letter(&self) -> Option<&L>{
-        self.letter.as_ref()
+    /// Returns a reference to the stored letter
+    pub fn letter(&self) -> Option<&'a L>{
+        self.nodes[self.index].letter.as_ref()
     }
 
     /// Returns the stored weight
     pub fn weight(&self) -> usize{
-        self.weight
+        self.nodes[self.index].weight
     }
 
-    /// Returns the stored code
-    pub fn code(&self) -> Option<&BitVec<Msb0, u8>>{
-        self.code.as_ref()
-    }
-    
-    /// Set the given code, consuming the original
-    pub fn set_code(&mut self, code: BitVec<Msb0, u8>){    
-        self.code = Some(code);
+    /// Returns the branch's code, read by going up the tree to its root.
+    ///
+    /// The root has no code, unless it's the only letter in the tree (then its code is `0`).
+    pub fn code(&self) -> Option<BitVec<Msb0, u8>>{
+        let mut node = &self.nodes[self.index];
+        if node.parent.is_none(){
+            return node.letter.as_ref().map(|_| bitvec![Msb0, u8; 0])
+        }
+        let mut code = BitVec::new();
+        let mut index = self.index;
+        while let Some(parent) = node.parent{
+            node = &self.nodes[parent];
+            code.push(node.children.unwrap().1 == index);
+            index = parent;
+        }
+        code.reverse();
+        Some(code)
     }
 }
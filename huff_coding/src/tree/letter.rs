@@ -1,4 +1,8 @@
-use std::{
+use alloc::{
+    boxed::Box,
+    string::String,
+};
+use core::{
     fmt::Debug,
     hash::Hash,
     mem::size_of,
@@ -10,7 +14,17 @@ use std::{
 /// 
 /// Implemented by default for every [primitive type](https://doc.rust-lang.org/stable/std/primitive), 
 /// except floats and including [String][String]
-pub trait HuffLetter: Clone + Eq + Hash + Debug{}
+pub trait HuffLetter: Clone + Eq + Hash + Debug{
+    /// Return the letter as a byte if the letters are bytes (only `u8` by default),
+    /// so that [`HuffCodes`][crate::tree::HuffCodes] look their codes up
+    /// in a fixed table of 256 codes instead of hashing them.
+    /// 
+    /// Either every letter of the type or none of them has to be returned as a byte,
+    /// and different letters as different bytes.
+    fn as_byte(&self) -> Option<u8>{
+        None
+    }
+}
 /// Trait specifying that the given HuffLetter can be converted
 /// into bytes *(returns `Box<[u8]>`)* and
 /// can be created from bytes (`&[u8]`),
@@ -18,10 +32,52 @@ pub trait HuffLetter: Clone + Eq + Hash + Debug{}
 /// 
 /// Implemented by default for every integer
 pub trait HuffLetterAsBytes: HuffLetter{
-    fn try_from_be_bytes(bytes: &[u8]) ->  Result<Self, Box<dyn std::error::Error>>;
+    fn try_from_be_bytes(bytes: &[u8]) ->  Result<Self, Box<dyn core::error::Error>>;
     fn as_be_bytes(&self) -> Box<[u8]>;
 }
 
+/// Trait specifying that the given HuffLetter belongs to a small, dense alphabet,
+/// so that every letter can be mapped to an index in `0..ALPHABET_LEN` and back.
+/// 
+/// This lets the letters be counted into an array-backed 
+/// [`IndexWeights`][crate::weights::IndexWeights] instead of a hashed collection.
+/// 
+/// Implemented by default for `u8`, `u16`, `i8` and `i16`
+/// 
+/// # Example
+/// ---
+/// Implementing `HuffLetterAsIndex` on a custom enum alphabet:
+/// ```
+/// use huff_coding::prelude::{HuffLetter, HuffLetterAsIndex, IndexWeights};
+/// 
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Base{A, C, G, T}
+/// 
+/// impl HuffLetter for Base{}
+/// impl HuffLetterAsIndex for Base{
+///     const ALPHABET_LEN: usize = 4;
+/// 
+///     fn as_index(&self) -> usize{
+///         *self as usize
+///     }
+///     fn from_index(index: usize) -> Self{
+///         [Base::A, Base::C, Base::G, Base::T][index]
+///     }
+/// }
+/// 
+/// let weights = IndexWeights::from_letters(&[Base::G, Base::A, Base::G]);
+/// assert_eq!(weights.get(&Base::G), Some(&2));
+/// assert_eq!(weights.get(&Base::T), None);
+/// ```
+pub trait HuffLetterAsIndex: HuffLetter{
+    /// Number of letters in the alphabet, every index
+    /// returned by [`as_index`](#tymethod.as_index) must be smaller than it
+    const ALPHABET_LEN: usize;
+
+    fn as_index(&self) -> usize;
+    fn from_index(index: usize) -> Self;
+}
+
 /// Implements `HuffLetter` for every provided type (without generics) 
 macro_rules! primitive_letter_impl{
     {$($type:ty),+} => {
@@ -43,7 +99,7 @@ macro_rules! integer_letter_impl{
         $(
         primitive_letter_impl!{$type}
         impl HuffLetterAsBytes for $type{
-            fn try_from_be_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>>{
+            fn try_from_be_bytes(bytes: &[u8]) -> Result<Self, Box<dyn core::error::Error>>{
                 let bytes: [u8; size_of::<$type>()] = bytes.try_into()?;
                 Ok(Self::from_be_bytes(bytes))
             }
@@ -55,6 +111,44 @@ macro_rules! integer_letter_impl{
     };
 }
 integer_letter_impl!{
-    u8, u16, u32, u64, usize, u128, 
+    u16, u32, u64, usize, u128, 
     i8, i16, i32, i64, isize, i128
 }
+
+impl HuffLetter for u8{
+    fn as_byte(&self) -> Option<u8>{
+        Some(*self)
+    }
+}
+impl HuffLetterAsBytes for u8{
+    fn try_from_be_bytes(bytes: &[u8]) -> Result<Self, Box<dyn core::error::Error>>{
+        let bytes: [u8; 1] = bytes.try_into()?;
+        Ok(bytes[0])
+    }
+    fn as_be_bytes(&self) -> Box<[u8]>{
+        Box::new([*self])
+    }
+}
+
+/// Implements `HuffLetterAsIndex` for provided primitive integer types,
+/// mapping them to indexes through their unsigned counterparts
+macro_rules! index_letter_impl{
+    {$($type:ty => $unsigned:ty),+} => {
+        $(
+        impl HuffLetterAsIndex for $type{
+            const ALPHABET_LEN: usize = <$unsigned>::MAX as usize + 1;
+
+            fn as_index(&self) -> usize{
+                *self as $unsigned as usize
+            }
+            fn from_index(index: usize) -> Self{
+                index as $unsigned as $type
+            }
+        }
+        )+
+    };
+}
+index_letter_impl!{
+    u8 => u8, u16 => u16,
+    i8 => u8, i16 => u16
+}
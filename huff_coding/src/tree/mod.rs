@@ -6,9 +6,21 @@ pub mod leaf;
 pub mod letter;
 
 mod branch_heap;
+mod codes;
+mod flat_tree;
+mod node;
 mod tree_inner;
+mod tree_export;
+#[cfg(feature = "serde")]
+mod tree_serde;
+mod tree_stats;
 
 pub use tree_inner::{
     HuffTree,
-    FromBinError
+    FromBinError,
+    FromWeightsError,
+    FromCodesError,
 };
+pub use codes::HuffCodes;
+pub use flat_tree::{FlatTree, FlatTreeError};
+pub use branch_heap::TieBreak;
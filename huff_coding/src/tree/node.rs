@@ -0,0 +1,43 @@
+use super::letter::HuffLetter;
+
+use alloc::vec::Vec;
+
+/// A branch of a [`HuffTree`][crate::tree::HuffTree], stored in the tree's arena
+/// (a `Vec<HuffNode<L>>`) and linked to its parent and children by their indexes in it
+///
+/// Nodes are always pushed after their children, so the root is the last node
+#[derive(Debug, Clone)]
+pub(crate) struct HuffNode<L: HuffLetter>{
+    pub(crate) letter: Option<L>,
+    pub(crate) weight: usize,
+    pub(crate) parent: Option<usize>,
+    pub(crate) children: Option<(usize, usize)>,
+}
+
+impl<L: HuffLetter> HuffNode<L>{
+    /// Initialize a letter node with the given weight (no parent and no children)
+    pub(crate) fn letter(letter: L, weight: usize) -> Self{
+        HuffNode{
+            letter: Some(letter),
+            weight,
+            parent: None,
+            children: None,
+        }
+    }
+}
+
+/// Push a joint node with the given children and weight
+/// onto the nodes, setting it as the children's parent,
+/// and return its index
+pub(crate) fn push_joint<L: HuffLetter>(nodes: &mut Vec<HuffNode<L>>, left: usize, right: usize, weight: usize) -> usize{
+    let index = nodes.len();
+    nodes[left].parent = Some(index);
+    nodes[right].parent = Some(index);
+    nodes.push(HuffNode{
+        letter: None,
+        weight,
+        parent: None,
+        children: Some((left, right)),
+    });
+    index
+}
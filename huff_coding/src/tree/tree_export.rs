@@ -0,0 +1,219 @@
+use crate::prelude::*;
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+};
+
+impl<L: HuffLetter> HuffTree<L>{
+    /// Return the tree rendered as a [Graphviz DOT][dot] graph,
+    /// with the letters formatted using their [`Debug`][std::fmt::Debug] implementation
+    /// (see [`to_dot_with`](#method.to_dot_with)).
+    ///
+    /// Letter branches are drawn as boxes containing their letter, code and weight,
+    /// joint branches as circles containing their weight, and every edge
+    /// is labeled with the bit it adds to the codes.
+    ///
+    /// Weights are left out if the root's weight is 0,
+    /// like in trees read from binary (see [`try_from_bin`](#method.try_from_bin)).
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, build_weights_btree};
+    ///
+    /// let tree = HuffTree::from_weights(build_weights_btree(&['a', 'b', 'b']));
+    ///
+    /// assert_eq!(tree.to_dot(), concat!(
+    ///     "digraph HuffTree {\n",
+    ///     "    node [fontname=\"monospace\"];\n",
+    ///     "    n0 [shape=circle, label=\"3\"];\n",
+    ///     "    n0 -> n1 [label=\"0\"];\n",
+    ///     "    n1 [shape=box, label=\"'a'\\ncode: 0\\nweight: 1\"];\n",
+    ///     "    n0 -> n2 [label=\"1\"];\n",
+    ///     "    n2 [shape=box, label=\"'b'\\ncode: 1\\nweight: 2\"];\n",
+    ///     "}\n",
+    /// ));
+    /// ```
+    ///
+    /// [dot]:https://graphviz.org/doc/info/lang.html
+    pub fn to_dot(&self) -> String{
+        self.to_dot_with(|letter| format!("{:?}", letter))
+    }
+
+    /// Return the tree rendered as a [Graphviz DOT][dot] graph,
+    /// just like [`to_dot`](#method.to_dot), but formatting the letters with fmt_letter
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abb"));
+    /// let dot = tree.to_dot_with(|byte| (*byte as char).to_string());
+    ///
+    /// assert!(dot.contains("label=\"a\\ncode: 0\\nweight: 1\""));
+    /// ```
+    ///
+    /// [dot]:https://graphviz.org/doc/info/lang.html
+    pub fn to_dot_with<F: Fn(&L) -> String>(&self, fmt_letter: F) -> String{
+        let root = self.root();
+        let show_weights = root.leaf().weight() != 0;
+        // a lone letter's code is 0
+        let mut code = String::from(if root.has_children(){""} else{"0"});
+        let mut dot = String::from("digraph HuffTree {\n    node [fontname=\"monospace\"];\n");
+
+        // branches left to write, with the length of their parent's code, their bit and their parent's id,
+        // kept on the heap (and not recursed into), so that deep trees can't overflow the stack
+        let mut branches = vec![(root, code.len(), None)];
+        let mut next_id = 0;
+        while let Some((branch, parent_code_len, parent)) = branches.pop(){
+            let id = next_id;
+            next_id += 1;
+            code.truncate(parent_code_len);
+            if let Some((parent_id, bit)) = parent{
+                code.push(bit);
+                dot.push_str(&format!("    n{} -> n{} [label=\"{}\"];\n", parent_id, id, bit));
+            }
+
+            let leaf = branch.leaf();
+            let (shape, label) = match leaf.letter(){
+                Some(letter) =>{
+                    let mut label = format!("{}\ncode: {}", fmt_letter(letter), code);
+                    if show_weights{
+                        label.push_str(&format!("\nweight: {}", leaf.weight()));
+                    }
+                    ("box", label)
+                }
+                None if show_weights => ("circle", leaf.weight().to_string()),
+                None => ("point", String::new()),
+            };
+            dot.push_str(&format!("    n{} [shape={}, label=\"{}\"];\n", id, shape, escape_dot(&label)));
+
+            if let (Some(left), Some(right)) = (branch.left_child(), branch.right_child()){
+                // the left child is popped (written and numbered) first
+                branches.push((right, code.len(), Some((id, '1'))));
+                branches.push((left, code.len(), Some((id, '0'))));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Return the tree drawn with ASCII characters, with the letters
+    /// formatted using their [`Debug`][std::fmt::Debug] implementation
+    /// (see [`to_ascii_with`](#method.to_ascii_with)).
+    ///
+    /// Every branch is drawn in its own line, under its parent and after the bit
+    /// it adds to the codes. Letter branches show their letter, code and weight
+    /// (in parentheses), and joint branches only their weight.
+    ///
+    /// Weights are left out if the root's weight is 0 (joint branches are then drawn as `*`),
+    /// like in trees read from binary (see [`try_from_bin`](#method.try_from_bin)).
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, build_weights_btree};
+    ///
+    /// let tree = HuffTree::from_weights(build_weights_btree(&['a', 'b', 'b', 'c', 'c', 'c']));
+    ///
+    /// assert_eq!(tree.to_ascii(), concat!(
+    ///     "(6)\n",
+    ///     "+-0- 'c' = 0 (3)\n",
+    ///     "`-1- (3)\n",
+    ///     "     +-0- 'a' = 10 (1)\n",
+    ///     "     `-1- 'b' = 11 (2)\n",
+    /// ));
+    ///
+    /// // no weights are stored in binary
+    /// let tree = HuffTree::from_weights(build_weights_btree(b"abbccc"));
+    /// let tree = HuffTree::<u8>::try_from_bin(tree.as_bin()).unwrap();
+    /// assert_eq!(tree.to_ascii(), concat!(
+    ///     "*\n",
+    ///     "+-0- 99 = 0\n",
+    ///     "`-1- *\n",
+    ///     "     +-0- 97 = 10\n",
+    ///     "     `-1- 98 = 11\n",
+    /// ));
+    /// ```
+    pub fn to_ascii(&self) -> String{
+        self.to_ascii_with(|letter| format!("{:?}", letter))
+    }
+
+    /// Return the tree drawn with ASCII characters,
+    /// just like [`to_ascii`](#method.to_ascii), but formatting the letters with fmt_letter
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"aaa"));
+    ///
+    /// assert_eq!(tree.to_ascii_with(|byte| format!("{:#04x}", byte)), "0x61 = 0 (3)\n");
+    /// ```
+    pub fn to_ascii_with<F: Fn(&L) -> String>(&self, fmt_letter: F) -> String{
+        let root = self.root();
+        let show_weights = root.leaf().weight() != 0;
+        // a lone letter's code is 0
+        let mut code = String::from(if root.has_children(){""} else{"0"});
+        let mut indent = String::new();
+        let mut ascii = String::new();
+
+        // branches left to write, with the lengths of their parent's code and indent, and their bit,
+        // kept on the heap (and not recursed into), so that deep trees can't overflow the stack
+        let mut branches = vec![(root, code.len(), 0, None)];
+        while let Some((branch, parent_code_len, parent_indent_len, bit)) = branches.pop(){
+            code.truncate(parent_code_len);
+            indent.truncate(parent_indent_len);
+            if let Some(bit) = bit{
+                // the children's lines are prefixed with their parent's indent
+                let (line, child_indent) = if bit == '0'{
+                    ("+-0- ", "|    ")
+                } else{
+                    ("`-1- ", "     ")
+                };
+                ascii.push_str(&indent);
+                ascii.push_str(line);
+                code.push(bit);
+                indent.push_str(child_indent);
+            }
+
+            let leaf = branch.leaf();
+            match leaf.letter(){
+                Some(letter) =>{
+                    ascii.push_str(&format!("{} = {}", fmt_letter(letter), code));
+                    if show_weights{
+                        ascii.push_str(&format!(" ({})", leaf.weight()));
+                    }
+                }
+                None if show_weights => ascii.push_str(&format!("({})", leaf.weight())),
+                None => ascii.push('*'),
+            }
+            ascii.push('\n');
+
+            if let (Some(left), Some(right)) = (branch.left_child(), branch.right_child()){
+                // the left child is popped (written) first
+                branches.push((right, code.len(), indent.len(), Some('1')));
+                branches.push((left, code.len(), indent.len(), Some('0')));
+            }
+        }
+        ascii
+    }
+}
+
+/// Escape the characters that can't appear as they are in a DOT string
+fn escape_dot(label: &str) -> String{
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars(){
+        match c{
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
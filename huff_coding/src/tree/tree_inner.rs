@@ -1,13 +1,26 @@
 use crate::{
     prelude::*,
-    utils::size_of_bits,
+    bits::{BitReader, BitWriter},
     bitvec::prelude::{bitvec, BitVec, Msb0},
+    map::LetterMap,
+};
+use super::{
+    branch_heap::{HuffBranchHeap, TieBreak},
+    node::{HuffNode, push_joint},
 };
-use super::branch_heap::HuffBranchHeap;
 
-use std::{
+use alloc::{
+    borrow::Cow,
+    vec,
+    vec::Vec,
+};
+use core::{
     fmt,
+    iter,
     mem,
+};
+#[cfg(feature = "std")]
+use std::{
     collections::{hash_map::RandomState, HashMap},
     hash::BuildHasher,
 };
@@ -19,10 +32,10 @@ use std::{
 /// 
 /// A `HuffTree` can be initialized in two ways:
 /// * from a struct implementing the [`Weights<L>`][weights] trait ([`from_weights`](#method.from_weights)), 
-/// where `L` must implement the [`HuffLetter`][letter] trait  
+///   where `L` must implement the [`HuffLetter`][letter] trait  
 /// * from a binary representation ([`try_from_bin`](#method.try_from_bin)): 
-/// [`BitVec<Msb0, u8>`][bitvec::prelude::BitVec], where in order to even get it,
-/// `L` must implement the [`HuffLetterAsBytes`][letter_bytes] trait 
+///   [`BitVec<Msb0, u8>`][bitvec::prelude::BitVec], where in order to even get it,
+///   `L` must implement the [`HuffLetterAsBytes`][letter_bytes] trait 
 /// 
 /// Codes stored by the tree can be retrieved using the [`codes`](#method.codes) method
 /// 
@@ -31,28 +44,45 @@ use std::{
 /// When initialized with the [`HuffTree::from_weights`](#method.from_weights) method it
 /// follows the steps of the [Huffman Coding algorithm][huff_wiki] (duh):
 /// 1. Creates standalone branches for every letter found in the given weights and
-/// pushes them onto a branch heap
+///    pushes them onto a branch heap
 /// 2. Finds two branches with the lowest weights
 /// 3. Makes them children to a branch with a [`None`][None] letter and
-/// the children's summed up weight
+///    the children's summed up weight
 /// 4. Removes the two found branches from the heap and adds the newly created
-/// branch into it
+///    branch into it
 /// 5. Repeats steps 2 to 4 until there's only one branch left
 /// 6. Sets the only branch left as root
-/// 7. Recurses into the tree to set every branch's code
-///  * Every branch gets its parent's code with its own position in the parent branch (left - 0, right - 1)
+/// 
+/// When the weights are iterated over sorted by weight, it uses the linear time 
+/// two-queue algorithm instead of the heap (see [`from_sorted_weights`](#method.from_sorted_weights)),
+/// building the very same tree.
+/// 
+/// All the branches are stored in a single array, where every branch
+/// is linked to its parent and children by their indexes,
+/// so building and cloning a tree takes a single allocation 
+/// (besides the ones done by the letters themselves).
+/// The [`HuffBranch`es][branch] returned by [`root`](#method.root) are views into it.
+/// Every code is read by going down the tree from the root 
+/// (left - 0, right - 1) to its letter.
 /// 
 /// Initializing from bits goes as follows:
 /// 1. Go through the `HuffTree` encoded in binary ([big endian][end_wiki]) bit by bit
 /// 2. Every 1 means a joint branch
 /// 3. Every 0 means a letter branch followed by [`size_of::<L> * 8`][mem::size_of] bits representing
-/// the stored letter
+///    the stored letter
+/// 
+/// With the `serde` feature enabled, a `HuffTree<L>` (with `L` implementing `Serialize`
+/// and `Deserialize`) is serialized in a similar, compact form: a struct with its 
+/// `shape` (the 1s and 0s, packed into bytes) and its `letters` (in the same order). 
+/// Weights are not stored, so they're set to 0 after deserializing, and 
+/// deserializing fails if the shape doesn't match the letters, or a letter repeats.
 /// 
 /// 
 /// # Examples
 /// ---
 /// Initialization from [`ByteWeights`][byte_weights]
 /// ```
+/// # #[cfg(feature = "std")]{
 /// use huff_coding::{
 ///     bitvec::prelude::*,
 ///     prelude::{HuffTree, ByteWeights},
@@ -76,9 +106,11 @@ use std::{
 ///     codes.get(&b'a').unwrap(),
 ///     &bitvec![Msb0, u8; 1, 0]
 /// );
+/// # }
 /// ```
 /// Initialization from [`HashMap<L, usize>`][HashMap]:
 /// ```
+/// # #[cfg(feature = "std")]{
 /// use huff_coding::{
 ///     bitvec::prelude::*,
 ///     prelude::{HuffTree, Weights},
@@ -105,9 +137,11 @@ use std::{
 ///     codes.get("pudzian").unwrap(),
 ///     &bitvec![Msb0, u8; 1, 0]
 /// );
+/// # }
 /// ```
 /// Representing and reading the tree from bits:
 /// ```
+/// # #[cfg(feature = "std")]{
 /// use huff_coding::prelude::{HuffTree, ByteWeights};
 /// 
 /// let tree = HuffTree::from_weights(
@@ -142,6 +176,7 @@ use std::{
 ///         .weight(),
 ///      0
 /// );
+/// # }
 /// ```
 /// 
 /// # Panics
@@ -150,9 +185,9 @@ use std::{
 /// [`Weights<L>`][weights] with len == 0:
 /// ```should_panic
 /// use huff_coding::prelude::{HuffTree, Weights};
-/// use std::collections::HashMap;
+/// use std::collections::BTreeMap;
 /// 
-/// let weights = HashMap::<char, usize>::new();
+/// let weights = BTreeMap::<char, usize>::new();
 /// 
 /// // panics here at 'provided empty weights'
 /// let tree = HuffTree::from_weights(weights);
@@ -192,7 +227,7 @@ use std::{
 /// [end_wiki]:https://en.wikipedia.org/wiki/Endianness
 #[derive(Debug, Clone)]
 pub struct HuffTree<L: HuffLetter>{
-    root: HuffBranch<L>,
+    nodes: Vec<HuffNode<L>>,
 }
 
 impl<L: HuffLetter> HuffTree<L>{
@@ -206,6 +241,7 @@ impl<L: HuffLetter> HuffTree<L>{
     /// ---
     /// Initialization from [`ByteWeights`][byte_weights]
     /// ```
+    /// # #[cfg(feature = "std")]{
     /// use huff_coding::{
     ///     bitvec::prelude::*,
     ///     prelude::{HuffTree, ByteWeights},
@@ -229,9 +265,11 @@ impl<L: HuffLetter> HuffTree<L>{
     ///     codes.get(&b'd').unwrap(),
     ///     &bitvec![Msb0, u8; 1, 0]
     /// );
+    /// # }
     /// ```
     /// Initialization from [`HashMap<L, usize>`][std::collections::HashMap]:
     /// ```
+    /// # #[cfg(feature = "std")]{
     /// use huff_coding::{
     ///     bitvec::prelude::*,
     ///     prelude::{HuffTree, Weights},
@@ -258,6 +296,7 @@ impl<L: HuffLetter> HuffTree<L>{
     ///     codes.get(&'ą').unwrap(),
     ///     &bitvec![Msb0, u8; 1, 0]
     /// );
+    /// # }
     /// ```
     /// 
     /// # Panics
@@ -266,67 +305,385 @@ impl<L: HuffLetter> HuffTree<L>{
     /// [`Weights<L>`][weights] with len == 0:
     /// ```should_panic
     /// use huff_coding::prelude::{HuffTree, Weights};
-    /// use std::collections::HashMap;
+    /// use std::collections::BTreeMap;
     /// 
-    /// let weights = HashMap::<char, usize>::new();
+    /// let weights = BTreeMap::<char, usize>::new();
     /// 
     /// // panics here at 'provided empty weights'
     /// let tree = HuffTree::from_weights(weights);
     /// ```
+    /// or when the sum of the provided weights overflows a `usize` 
+    /// (see [`Weights::scale_to`][scale_to] for a way to avoid it):
+    /// ```should_panic
+    /// use huff_coding::prelude::HuffTree;
+    /// use std::collections::BTreeMap;
+    /// 
+    /// let mut weights = BTreeMap::new();
+    /// weights.insert('a', usize::MAX);
+    /// weights.insert('b', 1);
+    /// 
+    /// // panics here at 'provided weights overflow usize'
+    /// let tree = HuffTree::from_weights(weights);
+    /// ```
     /// 
     /// [letter]:crate::tree::letter::HuffLetter
     /// [letter_bytes]:crate::tree::letter::HuffLetterAsBytes
     /// [weights]:crate::weights::Weights
     /// [byte_weights]:crate::weights::ByteWeights
+    /// [scale_to]:crate::weights::Weights::scale_to
     pub fn from_weights<W: Weights<L>>(weights: W) -> Self{
-        // panic when provided with empty weights
+        match HuffTree::try_from_weights(weights){
+            Ok(tree) => tree,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Try to initialize the `HuffTree` with a struct implementing the [`Weights<L>`][weights] trait,
+    /// just like [`from_weights`](#method.from_weights), but returning an error instead of panicking.
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// # #[cfg(feature = "std")]{
+    /// use huff_coding::prelude::{HuffTree, Weights};
+    /// use std::collections::HashMap;
+    /// 
+    /// let mut weights = HashMap::new();
+    /// weights.insert('a', usize::MAX);
+    /// weights.insert('b', 1);
+    /// 
+    /// assert!(HuffTree::try_from_weights(weights.clone()).is_err());
+    /// 
+    /// // scaled weights can always be built into a tree
+    /// weights.scale_to(usize::MAX);
+    /// assert!(HuffTree::try_from_weights(weights).is_ok());
+    /// # }
+    /// ```
+    /// 
+    /// # Errors
+    /// ---
+    /// * When the provided weights are empty
+    /// * When the sum of the provided weights overflows a `usize`
+    /// 
+    /// [weights]:crate::weights::Weights
+    pub fn try_from_weights<W: Weights<L>>(weights: W) -> Result<Self, FromWeightsError>{
+        HuffTree::try_from_weights_with(weights, TieBreak::default())
+    }
+
+    /// Initialize the `HuffTree` with a struct implementing the [`Weights<L>`][weights] trait,
+    /// choosing between branches of equal weights with the provided [`TieBreak`][tie_break].
+    /// 
+    /// [`from_weights`](#method.from_weights) uses the default [`TieBreak::CombinedLast`][combined_last],
+    /// giving minimum-variance codes: of the same total length as any other Huffman codes, 
+    /// but with the smallest possible maximum length (handy for table based decoders).
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, TieBreak};
+    /// use std::collections::BTreeMap;
+    /// 
+    /// let mut weights = BTreeMap::new();
+    /// weights.insert('a', 4);
+    /// weights.insert('b', 2);
+    /// weights.insert('c', 2);
+    /// weights.insert('d', 1);
+    /// weights.insert('e', 1);
+    /// 
+    /// let shallow = HuffTree::from_weights_with(weights.clone(), TieBreak::CombinedLast);
+    /// let deep = HuffTree::from_weights_with(weights.clone(), TieBreak::CombinedFirst);
+    /// 
+    /// assert_eq!(shallow.depth(), 3);
+    /// assert_eq!(deep.depth(), 4);
+    /// 
+    /// // both are optimal
+    /// assert_eq!(shallow.compressed_bit_count(&weights), Some(22));
+    /// assert_eq!(deep.compressed_bit_count(&weights), Some(22));
+    /// ```
+    /// 
+    /// # Panics
+    /// ---
+    /// Just like [`from_weights`](#method.from_weights).
+    /// 
+    /// [weights]:crate::weights::Weights
+    /// [tie_break]:crate::tree::TieBreak
+    /// [combined_last]:crate::tree::TieBreak::CombinedLast
+    pub fn from_weights_with<W: Weights<L>>(weights: W, tie_break: TieBreak) -> Self{
+        match HuffTree::try_from_weights_with(weights, tie_break){
+            Ok(tree) => tree,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Try to initialize the `HuffTree` with a struct implementing the [`Weights<L>`][weights] trait
+    /// and the provided [`TieBreak`][tie_break], just like [`from_weights_with`](#method.from_weights_with),
+    /// but returning an error instead of panicking.
+    /// 
+    /// # Errors
+    /// ---
+    /// Just like [`try_from_weights`](#method.try_from_weights).
+    /// 
+    /// [weights]:crate::weights::Weights
+    /// [tie_break]:crate::tree::TieBreak
+    pub fn try_from_weights_with<W: Weights<L>>(weights: W, tie_break: TieBreak) -> Result<Self, FromWeightsError>{
+        // return Err when provided with empty weights
         if weights.is_empty(){
-            panic!("provided empty weights")
+            return Err(FromWeightsError::new(
+                "provided empty weights"
+            ))
+        }
+
+        // store the letters first, the joint branches will follow them
+        let letter_count = weights.len();
+        let mut nodes = Vec::with_capacity(letter_count * 2 - 1);
+        nodes.extend(weights.into_iter().map(|(letter, weight)| HuffNode::letter(letter, weight)));
+        
+        // weights sorted by weight don't need the heap
+        if nodes.windows(2).all(|pair| pair[0].weight <= pair[1].weight){
+            return HuffTree::build_from_sorted_nodes(nodes, tie_break)
         }
 
-        let mut branch_heap = HuffBranchHeap::from_weights(weights);
+        let mut branch_heap = HuffBranchHeap::with_capacity(letter_count, tie_break);
+        for (index, node) in nodes.iter().enumerate(){
+            branch_heap.push(index, node.weight, false);
+        }
 
         while branch_heap.len() > 1{
             // get the min pair, removing it from the heap
             let min = branch_heap.pop_min();
             let next_min = branch_heap.pop_min();
 
+            // sum up the pair's weights, the root's weight being the sum
+            // of all weights, so if any sum overflows, the whole tree would
+            let weight = nodes[min].weight
+                .checked_add(nodes[next_min].weight)
+                .ok_or_else(|| FromWeightsError::new(
+                    "provided weights overflow usize"
+                ))?;
+
             // initialize a joint branch and push it onto the heap
-            let branch = HuffBranch::new(
-                HuffLeaf::new(
-                    None,
-                    min.leaf().weight() + next_min.leaf().weight()
-                ),
-                Some((min, next_min))
-            );
-            branch_heap.push(branch);
+            let index = push_joint(&mut nodes, min, next_min, weight);
+            branch_heap.push(index, weight, true);
+        }
+
+        // the last pushed branch is root
+        Ok(HuffTree{
+            nodes
+        })
+    }
+
+    /// Initialize the `HuffTree` with letters and their weights, sorted by weight
+    /// in ascending order, in linear time (using two queues instead of a heap).
+    /// 
+    /// The tree is the same as the one built by [`from_weights`](#method.from_weights) 
+    /// from weights iterated over in the same order.
+    /// 
+    /// # How it works
+    /// ---
+    /// The letters form the first queue, already sorted by weight.
+    /// Joint branches are created in the order of their weights, so they form
+    /// the second sorted queue, and the two smallest weights are always at the
+    /// fronts of the queues. On ties letters come first, giving minimum-variance
+    /// codes (see [`TieBreak::CombinedLast`][combined_last]).
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::HuffTree;
+    /// 
+    /// let tree = HuffTree::from_sorted_weights(vec![('a', 1), ('b', 2), ('c', 3)]);
+    /// 
+    /// assert_eq!(tree.root().leaf().weight(), 6);
+    /// assert_eq!(tree.depth(), 2);
+    /// ```
+    /// 
+    /// # Panics
+    /// ---
+    /// When the provided weights are empty, not sorted, repeat a letter, or their sum overflows a `usize`
+    /// (see [`try_from_sorted_weights`](#method.try_from_sorted_weights)).
+    /// 
+    /// [combined_last]:crate::tree::TieBreak::CombinedLast
+    pub fn from_sorted_weights<I: IntoIterator<Item = (L, usize)>>(weights: I) -> Self{
+        match HuffTree::try_from_sorted_weights(weights){
+            Ok(tree) => tree,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Try to initialize the `HuffTree` with letters and their weights, sorted by weight
+    /// in ascending order, just like [`from_sorted_weights`](#method.from_sorted_weights),
+    /// but returning an error instead of panicking.
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::HuffTree;
+    /// 
+    /// assert!(HuffTree::try_from_sorted_weights(vec![(1u8, 1), (2, 1), (3, 5)]).is_ok());
+    /// assert!(HuffTree::try_from_sorted_weights(vec![(1u8, 5), (2, 1)]).is_err());
+    /// assert!(HuffTree::try_from_sorted_weights(vec![(1u8, 1), (1, 1)]).is_err());
+    /// ```
+    /// 
+    /// # Errors
+    /// ---
+    /// * When the provided weights are empty
+    /// * When the provided weights are not sorted in ascending order
+    /// * When the provided weights store a letter more than once
+    /// * When the sum of the provided weights overflows a `usize`
+    pub fn try_from_sorted_weights<I: IntoIterator<Item = (L, usize)>>(weights: I) -> Result<Self, FromWeightsError>{
+        let weights = weights.into_iter();
+        let mut nodes = Vec::with_capacity(weights.size_hint().0.max(1) * 2 - 1);
+        for (letter, weight) in weights{
+            if nodes.last().is_some_and(|last: &HuffNode<L>| last.weight > weight){
+                return Err(FromWeightsError::new(
+                    "provided weights are not sorted"
+                ))
+            }
+            nodes.push(HuffNode::letter(letter, weight));
+        }
+        if nodes.is_empty(){
+            return Err(FromWeightsError::new(
+                "provided empty weights"
+            ))
+        }
+        let mut letters = LetterMap::with_capacity(nodes.len());
+        if !nodes.iter().filter_map(|node| node.letter.as_ref()).all(|letter| letters.insert(letter, ()).is_none()){
+            return Err(FromWeightsError::new(
+                "provided duplicate letters"
+            ))
         }
+        HuffTree::build_from_sorted_nodes(nodes, TieBreak::default())
+    }
+
+    /// Combine the provided letter nodes (sorted by weight) into a tree
+    /// with the two-queue algorithm
+    fn build_from_sorted_nodes(mut nodes: Vec<HuffNode<L>>, tie_break: TieBreak) -> Result<Self, FromWeightsError>{
+        let letter_count = nodes.len();
+        nodes.reserve_exact(letter_count - 1);
 
-        // last branch in branch_heap is root
-        let mut root = branch_heap.pop_min();
+        // nodes[next_letter..letter_count] is the queue of letters,
+        // and nodes[next_joint..] the queue of joint branches
+        let mut next_letter = 0;
+        let mut next_joint = letter_count;
+        let mut pop_min = |nodes: &Vec<HuffNode<L>>|{
+            let take_letter = next_letter < letter_count && (
+                next_joint == nodes.len() || match tie_break{
+                    TieBreak::CombinedLast => nodes[next_letter].weight <= nodes[next_joint].weight,
+                    TieBreak::CombinedFirst => nodes[next_letter].weight < nodes[next_joint].weight,
+                }
+            );
+            let queue_front = if take_letter{&mut next_letter} else{&mut next_joint};
+            *queue_front += 1;
+            *queue_front - 1
+        };
 
-        // set codes for all branches recursively if has children
-        // else just set the root's code to 0
-        if root.has_children(){
-            HuffTree::set_codes_in_child_branches(&mut root, None);
+        while nodes.len() < letter_count * 2 - 1{
+            let min = pop_min(&nodes);
+            let next_min = pop_min(&nodes);
+            let weight = nodes[min].weight
+                .checked_add(nodes[next_min].weight)
+                .ok_or_else(|| FromWeightsError::new(
+                    "provided weights overflow usize"
+                ))?;
+            push_joint(&mut nodes, min, next_min, weight);
         }
-        else{
-            root.set_code({let mut c = BitVec::with_capacity(1); c.push(false); c});
+
+        Ok(HuffTree{
+            nodes
+        })
+    }
+
+    /// Return the tree's root branch
+    pub fn root(&self) -> HuffBranch<'_, L>{
+        HuffBranch::new(&self.nodes, self.nodes.len() - 1)
+    }
+
+    /// Go down the tree calling f with every letter and its code
+    pub(crate) fn for_each_code<F: FnMut(&L, &BitVec<Msb0, u8>)>(&self, mut f: F){
+        let root = self.nodes.len() - 1;
+        if self.nodes[root].children.is_none(){
+            f(self.nodes[root].letter.as_ref().unwrap(), &bitvec![Msb0, u8; 0]);
+            return
         }
 
-        HuffTree{
-            root
+        // branches left to visit, with the length of their parent's code and their position,
+        // kept on the heap (and not recursed into), so that deep trees can't overflow the stack
+        let mut code = BitVec::new();
+        let mut branches = vec![(root, 0, None)];
+        while let Some((index, parent_code_len, position)) = branches.pop(){
+            code.truncate(parent_code_len);
+            if let Some(position) = position{
+                code.push(position);
+            }
+            match self.nodes[index].children{
+                Some((left, right)) =>{
+                    // the left child is popped (visited) first
+                    branches.push((right, code.len(), Some(true)));
+                    branches.push((left, code.len(), Some(false)));
+                }
+                None =>
+                    f(self.nodes[index].letter.as_ref().unwrap(), &code),
+            }
         }
     }
 
-    /// Return a reference to the tree's root branch
-    pub fn root(&self) -> &HuffBranch<L>{
-        &self.root
+    /// Read the branches of a tree from bits in preorder, reading the children
+    /// of joint branches (1s) after them, and the letters of letter branches (0s) with read_letter.
+    /// 
+    /// Return [`None`][None] if the bits end too early or read_letter returns `None`.
+    pub(crate) fn read_preorder<F>(bits: &mut BitReader, mut read_letter: F) -> Option<Self>
+    where F: FnMut(&mut BitReader) -> Option<L>{
+        let mut nodes = Vec::new();
+        // joint branches whose children are still being read, with their left child once it's read,
+        // kept on the heap (and not recursed into), so that deep trees can't overflow the stack
+        let mut joints: Vec<Option<usize>> = Vec::new();
+        loop{
+            if bits.read_bit()?{
+                joints.push(None);
+                continue
+            }
+            nodes.push(HuffNode::letter(read_letter(bits)?, 0));
+            // a finished branch is either the left child of the last joint branch,
+            // or its right one, which finishes the joint branch too (pushed after its children)
+            let mut branch = nodes.len() - 1;
+            loop{
+                match joints.pop(){
+                    None =>
+                        return Some(HuffTree{
+                            nodes
+                        }),
+                    Some(None) =>{
+                        joints.push(Some(branch));
+                        break
+                    }
+                    Some(Some(left)) =>
+                        branch = push_joint(&mut nodes, left, branch, 0),
+                }
+            }
+        }
     }
 
-    /// Return a mutable reference to the tree's root branch
-    pub fn root_mut(&mut self) -> &mut HuffBranch<L>{
-        &mut self.root
+    /// Write the branches of the tree into bits in preorder, every joint branch as a 1,
+    /// and every letter branch as a 0 followed by whatever write_letter writes
+    pub(crate) fn write_preorder<'a, F: FnMut(&mut BitWriter, &'a L)>(&'a self, bits: &mut BitWriter, mut write_letter: F){
+        // branches left to write, kept on the heap (and not recursed into),
+        // so that deep trees can't overflow the stack
+        let mut branches = vec![self.nodes.len() - 1];
+        while let Some(index) = branches.pop(){
+            let node = &self.nodes[index];
+            match node.children{
+                Some((left, right)) =>{
+                    bits.write_bit(true);
+                    // the left child is popped (written) first
+                    branches.push(right);
+                    branches.push(left);
+                }
+                None =>{
+                    bits.write_bit(false);
+                    write_letter(bits, node.letter.as_ref().unwrap());
+                }
+            }
+        }
     }
 
     /// Go down the tree reading every letter's code and returning
@@ -353,6 +710,7 @@ impl<L: HuffLetter> HuffTree<L>{
     /// 
     /// assert_eq!(codes, cmp_codes);
     /// ```
+    #[cfg(feature = "std")]
     pub fn read_codes(&self) -> HashMap<L, BitVec<Msb0, u8>>{
         self.read_codes_with_hasher(RandomState::default())
     }
@@ -385,58 +743,237 @@ impl<L: HuffLetter> HuffTree<L>{
     /// 
     /// assert_eq!(codes, cmp_codes);
     /// ```
+    #[cfg(feature = "std")]
     pub fn read_codes_with_hasher<S: BuildHasher>(&self, hash_builder: S) -> HashMap<L, BitVec<Msb0, u8>, S>{
-        /// Recursively insert letters to codes into the given HashMap<L, BitVec<Msb0, u8>>
-        fn set_codes<L: HuffLetter, S: BuildHasher>(codes: &mut HashMap<L, BitVec<Msb0, u8>, S>, root: &HuffBranch<L>, pos_in_parent: bool){
-            if let Some(children_iter) = root.children_iter(){
-                for (pos, child) in children_iter.enumerate(){
-                    let branch = child;
-                    let leaf = branch.leaf();
-                    if let Some(letter) = leaf.letter(){
-                        codes.insert(letter.clone(), leaf.code().unwrap().clone());
-                    }
-                    else{
-                        set_codes(codes, child, pos != 0);
-                    }
-                }
-            }  
-            else{
-                codes.insert(root.leaf().letter().unwrap().clone(), bitvec![Msb0, u8; pos_in_parent as u8]);
+        let mut codes = HashMap::with_capacity_and_hasher(self.letter_count(), hash_builder);
+        self.for_each_code(|letter, code|{
+            codes.insert(letter.clone(), code.clone());
+        });
+        codes
+    }
+
+    /// Try to initialize a `HuffTree` from the lengths of every letter's code,
+    /// assigning them canonical codes like in DEFLATE: shorter codes come first, and
+    /// codes of the same length are consecutive numbers, given out in the order of the
+    /// provided letters.
+    /// 
+    /// Letters with a code length of 0 are left out of the tree. A single letter
+    /// must have a code length of 1, and gets the code `0`.
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// # #[cfg(feature = "std")]{
+    /// use huff_coding::{
+    ///     bitvec::prelude::*,
+    ///     prelude::HuffTree,
+    /// };
+    /// 
+    /// // example from the DEFLATE specification (RFC 1951)
+    /// let tree = HuffTree::from_code_lengths(&[
+    ///     ('A', 3), ('B', 3), ('C', 3), ('D', 3), 
+    ///     ('E', 3), ('F', 2), ('G', 4), ('H', 4),
+    /// ]).unwrap();
+    /// let codes = tree.read_codes();
+    /// 
+    /// assert_eq!(codes[&'F'], bitvec![Msb0, u8; 0, 0]);
+    /// assert_eq!(codes[&'A'], bitvec![Msb0, u8; 0, 1, 0]);
+    /// assert_eq!(codes[&'E'], bitvec![Msb0, u8; 1, 1, 0]);
+    /// assert_eq!(codes[&'H'], bitvec![Msb0, u8; 1, 1, 1, 1]);
+    /// 
+    /// // over-subscribed, three 1 bit codes can't exist
+    /// assert!(HuffTree::from_code_lengths(&[('a', 1), ('b', 1), ('c', 1)]).is_err());
+    /// // incomplete, code 11 is missing
+    /// assert!(HuffTree::from_code_lengths(&[('a', 1), ('b', 2)]).is_err());
+    /// # }
+    /// ```
+    /// 
+    /// # Errors
+    /// ---
+    /// 1. When no letter has a nonzero code length
+    /// 2. When a letter is provided twice
+    /// 3. When the lengths don't satisfy the Kraft equality (the sum of 
+    ///    2^-length over every code isn't 1), meaning that they're either
+    ///    over-subscribed or incomplete
+    pub fn from_code_lengths(code_lengths: &[(L, u8)]) -> Result<Self, FromCodesError>{
+        let mut code_lengths: Vec<&(L, u8)> = code_lengths
+            .iter()
+            .filter(|(_, code_len)| *code_len != 0)
+            .collect();
+        if code_lengths.is_empty(){
+            return Err(FromCodesError::new("provided no nonzero code lengths"))
+        }
+        let mut letters = LetterMap::with_capacity(code_lengths.len());
+        if !code_lengths.iter().all(|(letter, _)| letters.insert(letter, ()).is_none()){
+            return Err(FromCodesError::new("provided duplicate letters"))
+        }
+        if let [(letter, code_len)] = code_lengths[..]{
+            if *code_len != 1{
+                return Err(FromCodesError::new("a single letter's code length must be 1"))
             }
+            return HuffTree::from_codes(iter::once((letter.clone(), bitvec![Msb0, u8; 0])))
         }
-        
-        let mut codes = HashMap::with_hasher(hash_builder);
-        let root = self.root();
-        if root.has_children(){
-            set_codes(&mut codes, root.left_child().unwrap(), false);
-            set_codes(&mut codes, root.right_child().unwrap(), true);
-            codes
+
+        // check the Kraft equality, counting the unused codes on every length
+        let mut len_counts = [0usize; 256];
+        for (_, code_len) in &code_lengths{
+            len_counts[*code_len as usize] += 1;
+        }
+        let mut unused_codes: usize = 1;
+        for len_count in &len_counts[1..]{
+            // more unused codes than letters left means that some will stay unused
+            unused_codes = unused_codes.saturating_mul(2);
+            if unused_codes > code_lengths.len(){
+                return Err(FromCodesError::new("provided code lengths are incomplete"))
+            }
+            unused_codes = unused_codes
+                .checked_sub(*len_count)
+                .ok_or_else(|| FromCodesError::new("provided code lengths are over-subscribed"))?;
         }
-        else{
-            codes.insert(root.leaf().letter().unwrap().clone(), bitvec![Msb0, u8; 0]);
-            codes
+        if unused_codes != 0{
+            return Err(FromCodesError::new("provided code lengths are incomplete"))
         }
+
+        // assign consecutive codes, starting with the shortest ones
+        code_lengths.sort_by_key(|(_, code_len)| *code_len);
+        let mut codes = Vec::with_capacity(code_lengths.len());
+        let mut code: BitVec<Msb0, u8> = BitVec::new();
+        for (letter, code_len) in code_lengths{
+            // increment the previous code
+            if !code.is_empty(){
+                let last_zero = code.iter().rposition(|bit| !*bit).unwrap();
+                code.truncate(last_zero);
+                code.push(true);
+            }
+            code.resize(*code_len as usize, false);
+            codes.push((letter.clone(), code.clone()));
+        }
+        HuffTree::from_codes(codes)
     }
 
-    /// Recursively set the codes in every encountered branch
-    fn set_codes_in_child_branches(parent: &mut HuffBranch<L>, parent_code: Option<BitVec<Msb0, u8>>){
-        if parent.has_children(){
-            let set_code = |child: &mut HuffBranch<L>, pos|{
-                // append pos_in_parent to parent_code and set the newly created code on child
-                let mut child_code = BitVec::with_capacity(1);
-                if let Some(parent_code) = parent_code{
-                    child_code = parent_code;
-                }   
-                child_code.push(pos != 0);
-                child.set_code(child_code.clone());
-    
-                // recurse into the child's children
-                HuffTree::set_codes_in_child_branches(child, Some(child_code));
-            };
-            
-            set_code.clone()(parent.left_child_mut().unwrap(), 0);
-            set_code(parent.right_child_mut().unwrap(), 1);
+    /// Try to initialize a `HuffTree` storing exactly the provided codes,
+    /// given as any collection of `(letter, code)` pairs (like a `HashMap`).
+    /// 
+    /// A single letter must have the code `0`.
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// # #[cfg(feature = "std")]{
+    /// use huff_coding::{
+    ///     bitvec::prelude::*,
+    ///     prelude::HuffTree,
+    /// };
+    /// use std::collections::HashMap;
+    /// 
+    /// let mut codes = HashMap::new();
+    /// codes.insert(b'a', bitvec![Msb0, u8; 1, 0]);
+    /// codes.insert(b'b', bitvec![Msb0, u8; 1, 1]);
+    /// codes.insert(b'c', bitvec![Msb0, u8; 0]);
+    /// 
+    /// let tree = HuffTree::from_codes(codes.clone()).unwrap();
+    /// assert_eq!(tree.read_codes(), codes);
+    /// 
+    /// // 1 is a prefix of 10 and 11
+    /// codes.insert(b'd', bitvec![Msb0, u8; 1]);
+    /// assert!(HuffTree::from_codes(codes).is_err());
+    /// # }
+    /// ```
+    /// 
+    /// # Errors
+    /// ---
+    /// 1. When the provided codes are empty, or any one of them is
+    /// 2. When a letter is provided twice
+    /// 3. When a code is a prefix of another (or they're equal)
+    /// 4. When the codes are incomplete (there are bit sequences 
+    ///    starting none of them)
+    pub fn from_codes<C>(codes: C) -> Result<Self, FromCodesError>
+    where C: IntoIterator<Item = (L, BitVec<Msb0, u8>)>{
+        /// Branch of a tree being built, its children being indexes
+        /// of other branches (and not boxed in it), so that deep trees
+        /// can't overflow the stack when built or dropped
+        enum Node<L>{
+            Empty,
+            Letter(L),
+            Joint(usize, usize),
+        }
+
+        let codes: Vec<_> = codes.into_iter().collect();
+        if codes.is_empty(){
+            return Err(FromCodesError::new("provided empty codes"))
+        }
+        let mut letters = LetterMap::with_capacity(codes.len());
+        if !codes.iter().all(|(letter, _)| letters.insert(letter, ()).is_none()){
+            return Err(FromCodesError::new("provided duplicate letters"))
+        }
+        if codes.len() == 1{
+            let (letter, code) = codes.into_iter().next().unwrap();
+            if code != bitvec![Msb0, u8; 0]{
+                return Err(FromCodesError::new("a single letter's code must be 0"))
+            }
+            return Ok(HuffTree{
+                nodes: vec![HuffNode::letter(letter, 0)]
+            })
+        }
+
+        // go down the tree along every code (starting with the root at 0), creating missing branches
+        let codes_len = codes.len();
+        let mut branches = vec![Node::Empty];
+        for (letter, code) in codes{
+            if code.is_empty(){
+                return Err(FromCodesError::new("provided an empty code"))
+            }
+            let mut branch = 0;
+            for bit in code.iter(){
+                if let Node::Empty = branches[branch]{
+                    branches[branch] = Node::Joint(branches.len(), branches.len() + 1);
+                    branches.push(Node::Empty);
+                    branches.push(Node::Empty);
+                }
+                branch = match branches[branch]{
+                    Node::Joint(left, right) => if *bit{right} else{left},
+                    _ => return Err(FromCodesError::new("provided codes are not prefix-free")),
+                };
+            }
+            if let Node::Empty = branches[branch]{
+                branches[branch] = Node::Letter(letter);
+            }
+            else{
+                return Err(FromCodesError::new("provided codes are not prefix-free"))
+            }
         }
+
+        // push every branch onto the nodes after its children, keeping the indexes
+        // of the pushed ones until their parent is pushed, and the branches left to push
+        // (with whether their children are already pushed) on the heap, not recursing into them
+        let mut nodes = Vec::with_capacity(codes_len * 2 - 1);
+        let mut pushed = Vec::new();
+        let mut to_push = vec![(0, false)];
+        while let Some((branch, children_pushed)) = to_push.pop(){
+            if children_pushed{
+                let right = pushed.pop().unwrap();
+                let left = pushed.pop().unwrap();
+                pushed.push(push_joint(&mut nodes, left, right, 0));
+                continue
+            }
+            match mem::replace(&mut branches[branch], Node::Empty){
+                Node::Empty =>
+                    return Err(FromCodesError::new("provided codes are incomplete")),
+                Node::Letter(letter) =>{
+                    nodes.push(HuffNode::letter(letter, 0));
+                    pushed.push(nodes.len() - 1);
+                }
+                Node::Joint(left, right) =>{
+                    // the left child is popped (pushed) first
+                    to_push.push((branch, true));
+                    to_push.push((right, false));
+                    to_push.push((left, false));
+                }
+            }
+        }
+        Ok(HuffTree{
+            nodes
+        })
     }
 }
 
@@ -451,13 +988,15 @@ impl<L: HuffLetterAsBytes> HuffTree<L>{
     /// # Decoding scheme
     /// ---
     /// 1. Go bit by bit
-    /// 2. Create a [`HuffBranch`][branch] with no letter (a joint branch) when a 1 is found
+    /// 2. Create a [`HuffBranch`][branch] with no letter (a joint branch) when a 1 is found,
+    ///    after reading its children
     /// 3. When a 0 is found, read next [`size_of::<L>() * 8`][mem::size_of] bits and create a
-    /// value of type `L` from them, inserting it then into a [`HuffBranch`][branch]
+    ///    value of type `L` from them, inserting it then into a [`HuffBranch`][branch]
     /// 
     /// # Example
     /// ---
     /// ```
+    /// # #[cfg(feature = "std")]{
     /// use huff_coding::prelude::{HuffTree, ByteWeights};
     /// 
     /// let tree = HuffTree::from_weights(
@@ -490,6 +1029,7 @@ impl<L: HuffLetterAsBytes> HuffTree<L>{
     ///         .weight(),
     ///      0
     /// );
+    /// # }
     /// ```
     /// 
     /// # Errors
@@ -516,91 +1056,46 @@ impl<L: HuffLetterAsBytes> HuffTree<L>{
     /// let tree = HuffTree::<u128>::try_from_bin(bitvec![Msb0, u8; 0, 1])
     ///     .expect("this will return a FromBinError (provided BitVec is to small)");
     /// ```
+    /// or when the BitVec stores a letter more than once (so it would have more than one code).
     /// 
     /// [branch]:crate::tree::branch::HuffBranch
     /// [letter_bytes]:crate::tree::letter::HuffLetterAsBytes
     pub fn try_from_bin(bin: BitVec<Msb0, u8>) -> Result<Self, FromBinError<L>>{
-        /// Recursively reads branches and their children from the given bits
-        /// When finding a 1 -> recurses to get children,
-        /// and when a 0 -> ends recursion returning a letter branch
-        fn read_branches_from_bits<L: HuffLetterAsBytes>(bits: &mut bitvec::slice::IterMut<Msb0, u8>) -> 
-        Result<HuffBranch<L>, FromBinError<L>>{
-            // check whether the bit can be popped at all, if not return Err
-            // remove first bit, if its 1 -> joint branch
-            if if let Some(bit) = bits.next(){*bit}
-                else{
-                    return Err(FromBinError::new(
-                        "Provided BitVec is too small for an encoded HuffTree"
-                    ))
-                }{
-                // create joint branch, recurse to get its children
-                let branch = HuffBranch::new(
-                    HuffLeaf::new(None, 0),
-                    Some(( 
-                        read_branches_from_bits(bits)?, 
-                        read_branches_from_bits(bits)?
-                    ))
-                );
-                Ok(branch)
-            }
-            // if it's 0 -> letter branch
-            else{
-                // read the letter bits and convert them to bytes
-                let mut letter_bytes = Vec::<u8>::with_capacity(mem::size_of::<L>());
-                let mut byte = 0b0000_0000;
-                let mut bit_ptr = 7;
-
-                // get an iterator over the letter bits, if not enough bits left return err
-                let letter_bits = bits.take(size_of_bits::<L>());
-                if letter_bits.len() != size_of_bits::<L>(){
-                    return Err(FromBinError::new(
-                        "Provided BitVec is too small for an encoded HuffTree", 
-                    ))
-                };
-                for bit in letter_bits{
-                    byte |= (*bit as u8) << bit_ptr;
-                    if bit_ptr == 0{
-                        letter_bytes.push(byte);
-                        byte = 0b0000_0000;
-                        bit_ptr = 7;
-                    }
-                    else{bit_ptr -= 1};
-                }
-                
-                // create letter branch (no children)
-                let branch = HuffBranch::new(
-                    // create letter from letter_bytes
-                    HuffLeaf::new(Some(L::try_from_be_bytes(&letter_bytes).unwrap()), 0),
-                    None,
-                );
-                Ok(branch)
-            }
-        }
-        // declare bin as mutable
+        // make sure the bits start at the beginning of the underlying bytes
         let mut bin = bin;
-        // recurse to create root, and set codes for all branches
-        let mut bin_iter_mut = bin.iter_mut();
-        let mut root = read_branches_from_bits(&mut bin_iter_mut)?;
+        bin.force_align();
+        // recurse to create every branch, the root being the last one
+        let mut bin_reader = BitReader::with_bit_len(bin.as_raw_slice(), bin.len());
+        let tree = HuffTree::read_preorder(&mut bin_reader, |bits|{
+            // read the letter bits as bytes, if not enough bits left return None
+            let mut letter_bytes = Vec::<u8>::with_capacity(mem::size_of::<L>());
+            for _ in 0..mem::size_of::<L>(){
+                letter_bytes.push(bits.read_bits(8)? as u8);
+            }
+            // create letter from letter_bytes
+            Some(L::try_from_be_bytes(&letter_bytes).unwrap())
+        }).ok_or_else(|| FromBinError::new(
+            "Provided BitVec is too small for an encoded HuffTree"
+        ))?;
 
         // return Err if not all bits used
-        if bin_iter_mut.next().is_some(){
+        if !bin_reader.is_empty(){
             return Err(FromBinError::new(
                 "Provided BitVec is too big for an encoded HuffTree", 
             ))
         }
 
-        // set codes for all branches recursively if has children
-        // else just set the root's code to 0
-        if root.has_children(){
-            HuffTree::set_codes_in_child_branches(&mut root, None);
-        }
-        else{
-            root.set_code(bitvec![Msb0, u8; 0]);
+        // return Err if any letter is stored more than once
+        let mut letters = LetterMap::with_capacity(tree.nodes.len() / 2 + 1);
+        if !tree.nodes.iter()
+            .filter_map(|node| node.letter.as_ref())
+            .all(|letter| letters.insert(letter, ()).is_none()){
+            return Err(FromBinError::new(
+                "Provided BitVec stores a letter more than once", 
+            ))
         }
         
-        Ok(HuffTree{
-            root
-        })
+        Ok(tree)
     }
 
     /// Return a binary representation of the `HuffTree<L>` 
@@ -613,7 +1108,7 @@ impl<L: HuffLetterAsBytes> HuffTree<L>{
     /// 1. Recurse down the tree
     /// 2. Every joint branch is encoded as a 1
     /// 3. Every letter branch is encoded as a 0
-    /// and is followed by the letter itself encoded in binary
+    ///    and is followed by the letter itself encoded in binary
     /// 
     /// # Example
     /// ---
@@ -630,40 +1125,14 @@ impl<L: HuffLetterAsBytes> HuffTree<L>{
     /// 
     /// [letter_bytes]:crate::tree::letter::HuffLetterAsBytes
     pub fn as_bin(&self) -> BitVec<Msb0, u8>{
-        /// Recursively push bits to the given BitVec<Msb0, u8>
-        /// depending on the branches you encounter:
-        /// * 0 being a letter branch (followed by a letter encoded in binary)
-        /// * 1 being a joint branch
-        fn set_tree_as_bin<L: HuffLetterAsBytes>(tree_bin: &mut BitVec<Msb0, u8>, root: &HuffBranch<L>){
-            let root = root;
-            let children_iter = root.children_iter();
-
-            // has children -> joint branch
-            if let Some(children_iter) = children_iter{
-                // 1 means joint branch
-                tree_bin.push(true);
-
-                // call set_bin on children
-                for child in children_iter{
-                    set_tree_as_bin(tree_bin, child);
-                }
-            }
-            // no children -> letter branch
-            else{
-                // 0 means letter branch
-                tree_bin.push(false);
-
-                // convert the letter to bytes and push the bytes' bits into the tree_bin
-                for byte in root.leaf().letter().unwrap().as_be_bytes().iter(){
-                    for bit_ptr in 0..8{
-                        tree_bin.push((byte >> (7 - bit_ptr)) & 1 == 1)
-                    }
-                }
-            }
-        }
-
-        let mut treebin = BitVec::new();
-        set_tree_as_bin(&mut treebin, self.root());
+        let mut tree_bin = BitWriter::new();
+        self.write_preorder(&mut tree_bin, |bits, letter|{
+            // convert the letter to bytes and write them into the tree_bin
+            bits.write_bytes(&letter.as_be_bytes());
+        });
+        let bit_len = tree_bin.bit_len();
+        let mut treebin = BitVec::from_vec(tree_bin.finish().0);
+        treebin.truncate(bit_len);
         treebin
     }
 }
@@ -671,30 +1140,93 @@ impl<L: HuffLetterAsBytes> HuffTree<L>{
 /// [Error][std::error::Error] encountered while trying to construct a [`HuffTree`][HuffTree] from bin
 /// with the [`HuffTree::try_from_bin`](struct.HuffTree.html#method.try_from_bin) method
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FromBinError<L: HuffLetterAsBytes>{
-    message: &'static str,
-    _typebind: std::marker::PhantomData<L>,
+    message: Cow<'static, str>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _typebind: core::marker::PhantomData<L>,
 }
 
 impl<L: HuffLetterAsBytes> fmt::Display for FromBinError<L>{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}<{}>", self.message, std::any::type_name::<L>())
+        write!(f, "{}<{}>", self.message, core::any::type_name::<L>())
     }
 }
 
-impl<L: HuffLetterAsBytes> std::error::Error for FromBinError<L>{}
+impl<L: HuffLetterAsBytes> core::error::Error for FromBinError<L>{}
 
 impl<L: HuffLetterAsBytes> FromBinError<L>{
     /// Initialize a new `FromBinError` with the given message
     pub fn new(message: &'static str) -> Self{
         Self{
-            message,
-            _typebind: std::marker::PhantomData,
+            message: Cow::Borrowed(message),
+            _typebind: core::marker::PhantomData,
+        }
+    }
+
+    /// Return the message
+    pub fn message(&self) -> &str{
+        &self.message
+    }
+}
+
+/// [Error][std::error::Error] encountered while trying to construct a [`HuffTree`][HuffTree] from weights
+/// with the [`HuffTree::try_from_weights`](struct.HuffTree.html#method.try_from_weights) method
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FromWeightsError{
+    message: Cow<'static, str>,
+}
+
+impl fmt::Display for FromWeightsError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl core::error::Error for FromWeightsError{}
+
+impl FromWeightsError{
+    /// Initialize a new `FromWeightsError` with the given message
+    pub fn new(message: &'static str) -> Self{
+        Self{
+            message: Cow::Borrowed(message),
+        }
+    }
+
+    /// Return the message
+    pub fn message(&self) -> &str{
+        &self.message
+    }
+}
+
+/// [Error][std::error::Error] encountered while trying to construct a [`HuffTree`][HuffTree] from codes
+/// with the [`HuffTree::from_codes`](struct.HuffTree.html#method.from_codes) or
+/// [`HuffTree::from_code_lengths`](struct.HuffTree.html#method.from_code_lengths) method
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FromCodesError{
+    message: Cow<'static, str>,
+}
+
+impl fmt::Display for FromCodesError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl core::error::Error for FromCodesError{}
+
+impl FromCodesError{
+    /// Initialize a new `FromCodesError` with the given message
+    pub fn new(message: &'static str) -> Self{
+        Self{
+            message: Cow::Borrowed(message),
         }
     }
 
     /// Return the message
     pub fn message(&self) -> &str{
-        self.message
+        &self.message
     }
 }
@@ -0,0 +1,66 @@
+use crate::{
+    prelude::*,
+    bits::{BitReader, BitWriter},
+    map::LetterMap,
+};
+
+use serde::{
+    de::Error,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+use alloc::vec::Vec;
+
+/// Serialized form of a `HuffTree`, borrowing its letters
+#[derive(Serialize)]
+#[serde(rename = "HuffTree")]
+struct HuffTreeRepr<'a, L>{
+    shape: Vec<u8>,
+    letters: Vec<&'a L>,
+}
+
+/// Deserialized form of a `HuffTree`, to be validated
+#[derive(Deserialize)]
+#[serde(rename = "HuffTree")]
+struct OwnedHuffTreeRepr<L>{
+    shape: Vec<u8>,
+    letters: Vec<L>,
+}
+
+impl<L: HuffLetter + Serialize> Serialize for HuffTree<L>{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>{
+        let mut shape = BitWriter::new();
+        let mut letters = Vec::with_capacity(self.letter_count());
+        self.write_preorder(&mut shape, |_, letter| letters.push(letter));
+        HuffTreeRepr{
+            shape: shape.finish().0,
+            letters,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de, L: HuffLetter + Deserialize<'de>> Deserialize<'de> for HuffTree<L>{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>{
+        let OwnedHuffTreeRepr{shape, letters} = OwnedHuffTreeRepr::deserialize(deserializer)?;
+
+        let mut unique_letters = LetterMap::with_capacity(letters.len());
+        if !letters.iter().all(|letter| unique_letters.insert(letter, ()).is_none()){
+            return Err(D::Error::custom("serialized HuffTree stores duplicate letters"))
+        }
+
+        // every letter must be used, and only the padding may be left in the shape
+        let mut letters = letters.into_iter();
+        let mut shape = BitReader::new(&shape, 0);
+        match HuffTree::read_preorder(&mut shape, |_| letters.next()){
+            Some(tree) if letters.len() == 0
+            && shape.bits_left() < 8
+            && shape.read_bits(shape.bits_left() as u32) == Some(0) =>
+                Ok(tree),
+            _ =>
+                Err(D::Error::custom("serialized HuffTree's shape doesn't match its letters")),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{
+    prelude::*,
+    map::LetterMap,
+};
+
+use alloc::vec;
+
+impl<L: HuffLetter> HuffTree<L>{
+    /// Return the number of letters stored in the tree
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// let tree = HuffTree::from_weights(
+    ///     ByteWeights::from_bytes(b"abbccc")
+    /// );
+    /// assert_eq!(tree.letter_count(), 3);
+    /// ```
+    pub fn letter_count(&self) -> usize{
+        // branches left to count, kept on the heap (and not recursed into),
+        // so that deep trees can't overflow the stack
+        let mut branches = vec![self.root()];
+        let mut letter_count = 0;
+        while let Some(branch) = branches.pop(){
+            match branch.children_iter(){
+                Some(children_iter) => branches.extend(children_iter),
+                None => letter_count += 1,
+            }
+        }
+        letter_count
+    }
+
+    /// Return the depth of the tree, that is the length of its longest code
+    ///
+    /// A tree storing only one letter has a depth of 1,
+    /// as its only code is `0`.
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// let tree = HuffTree::from_weights(
+    ///     ByteWeights::from_bytes(b"abbcccdddd")
+    /// );
+    /// assert_eq!(tree.depth(), 3);
+    /// ```
+    pub fn depth(&self) -> usize{
+        // branches left to visit with their depths, kept on the heap (and not recursed into),
+        // so that deep trees can't overflow the stack
+        let mut branches = vec![(self.root(), 0)];
+        let mut depth = 1;
+        while let Some((branch, branch_depth)) = branches.pop(){
+            match branch.children_iter(){
+                Some(children_iter) =>
+                    branches.extend(children_iter.map(|child| (child, branch_depth + 1))),
+                None => depth = depth.max(branch_depth),
+            }
+        }
+        depth
+    }
+
+    /// Return the exact number of bits the letters counted in the provided
+    /// [`Weights`][weights] would be compressed into using this tree's codes.
+    ///
+    /// Returns [`None`][None] if any letter with a nonzero weight has no code in the tree.
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights, compress};
+    ///
+    /// let bytes = b"abbccc";
+    /// let weights = ByteWeights::from_bytes(bytes);
+    /// let tree = HuffTree::from_weights(weights);
+    ///
+    /// // 'a' -> 10, 'b' -> 11, 'c' -> 0
+    /// assert_eq!(tree.compressed_bit_count(&weights), Some(2 + 2 * 2 + 3));
+    ///
+    /// // no code for 'd'
+    /// assert_eq!(tree.compressed_bit_count(&ByteWeights::from_bytes(b"d")), None);
+    /// ```
+    ///
+    /// [weights]:crate::weights::Weights
+    pub fn compressed_bit_count<W: Weights<L>>(&self, weights: &W) -> Option<u128>{
+        let mut code_lens = LetterMap::with_capacity(self.letter_count());
+        self.for_each_code(|letter, code|{
+            code_lens.insert(letter.clone(), code.len());
+        });
+        let mut bit_count = 0;
+        for (letter, weight) in weights.clone(){
+            if weight == 0{
+                continue
+            }
+            bit_count += weight as u128 * *code_lens.get(&letter)? as u128;
+        }
+        Some(bit_count)
+    }
+
+    /// Return the expected length (in bits) of a code used to compress
+    /// a letter with the probabilities given by the provided [`Weights`][weights].
+    ///
+    /// Returns [`None`][None] if any letter with a nonzero weight has no code in the tree,
+    /// or if the weights are all 0.
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// let weights = ByteWeights::from_bytes(b"aabc");
+    /// let tree = HuffTree::from_weights(weights);
+    ///
+    /// // 'a' -> 1 bit, 'b' and 'c' -> 2 bits
+    /// assert_eq!(tree.average_code_len(&weights), Some(1.5));
+    /// ```
+    ///
+    /// [weights]:crate::weights::Weights
+    pub fn average_code_len<W: Weights<L>>(&self, weights: &W) -> Option<f64>{
+        let total = weights.total();
+        if total == 0{
+            return None
+        }
+        Some(self.compressed_bit_count(weights)? as f64 / total as f64)
+    }
+
+    /// Return the coding efficiency of the tree for the provided [`Weights`][weights],
+    /// that is their [entropy][entropy] divided by the [average code length](#method.average_code_len).
+    ///
+    /// The efficiency is at most 1.0, which means that the codes can't be any shorter.
+    ///
+    /// Returns [`None`][None] if any letter with a nonzero weight has no code in the tree,
+    /// or if the weights are all 0.
+    ///
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{HuffTree, ByteWeights};
+    ///
+    /// // probabilities being powers of 1/2 can be coded perfectly
+    /// let weights = ByteWeights::from_bytes(b"aabc");
+    /// let tree = HuffTree::from_weights(weights);
+    /// assert_eq!(tree.efficiency(&weights), Some(1.0));
+    ///
+    /// let weights = ByteWeights::from_bytes(b"abc");
+    /// let tree = HuffTree::from_weights(weights);
+    /// assert!(tree.efficiency(&weights).unwrap() < 1.0);
+    /// ```
+    ///
+    /// [weights]:crate::weights::Weights
+    /// [entropy]:crate::weights::Weights::entropy
+    #[cfg(feature = "std")]
+    pub fn efficiency<W: Weights<L>>(&self, weights: &W) -> Option<f64>{
+        Some(weights.entropy() / self.average_code_len(weights)?)
+    }
+}
@@ -1,7 +1,12 @@
-pub use self::byte_weights::ByteWeights;
+pub use self::{
+    byte_weights::ByteWeights,
+    index_weights::IndexWeights,
+};
 
 use super::tree::letter::HuffLetter;
 
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
 use std::{
     collections::{
         HashMap,
@@ -16,8 +21,13 @@ use std::{
 /// Trait signifying that the struct stores the weights of a certain type (letter), so that
 /// for any stored letter there is a corresponding `usize`(weight).
 /// 
-/// Implemented by default for [`HashMap<L, usize>`][std::collections::HashMap] and
-/// for [`ByteWeights`][byte_weights::ByteWeights]
+/// Implemented by default for [`HashMap<L, usize>`][std::collections::HashMap] (with `std`), 
+/// [`BTreeMap<L, usize>`][std::collections::BTreeMap], [`ByteWeights`][byte_weights::ByteWeights]
+/// and [`IndexWeights`][index_weights::IndexWeights]
+/// 
+/// The order in which the weights are iterated decides how ties between equal weights
+/// are broken when building a [`HuffTree`][crate::tree::HuffTree]. Every implementation
+/// except `HashMap` iterates in the letters' order, so it always builds the same tree.
 /// 
 /// Needed implementations:
 /// * Traits:
@@ -30,14 +40,139 @@ use std::{
 ///  * `fn len(&self) -> usize`
 ///  * `fn is_empty(&self) -> bool`
 /// 
+/// 
+/// Provided methods:
+/// * `fn total(&self) -> u128`
+/// * `fn scale_to(&mut self, max_total: usize)`
+/// * `fn entropy(&self) -> f64`
+/// 
 /// In order to build with a [`HuffTree`][crate::tree::HuffTree] `L` must implement [`HuffLetter`][crate::tree::letter::HuffLetter]
 pub trait Weights<L>: Eq + Clone + IntoIterator<Item = (L, usize)>{
     fn get(&self, letter: &L) -> Option<&usize>;
     fn get_mut(&mut self, letter: &L) -> Option<&mut usize>;
     fn len(&self) -> usize;
     fn is_empty(&self) -> bool;
+
+    /// Return the sum of every stored weight.
+    /// 
+    /// The sum is returned as a `u128`, so it cannot overflow 
+    /// even if the weights themselves would overflow a `usize` when added.
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// # #[cfg(feature = "std")]{
+    /// use huff_coding::prelude::Weights;
+    /// use std::collections::HashMap;
+    /// 
+    /// let mut weights = HashMap::new();
+    /// weights.insert('a', usize::MAX);
+    /// weights.insert('b', 1);
+    /// 
+    /// assert_eq!(weights.total(), usize::MAX as u128 + 1);
+    /// # }
+    /// ```
+    fn total(&self) -> u128{
+        self.clone()
+            .into_iter()
+            .map(|(_, weight)| weight as u128)
+            .sum()
+    }
+
+    /// Scale every weight down proportionally, so that their sum is not larger than `max_total`.
+    /// 
+    /// Weights already summing up to at most `max_total` are left untouched. Otherwise,
+    /// every nonzero weight gets scaled down, but never to 0, so every letter is preserved
+    /// and a [`HuffTree`][crate::tree::HuffTree] can still be built from the weights without
+    /// overflowing.
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// # #[cfg(feature = "std")]{
+    /// use huff_coding::prelude::{HuffTree, Weights};
+    /// use std::collections::HashMap;
+    /// 
+    /// let mut weights = HashMap::new();
+    /// weights.insert('a', usize::MAX);
+    /// weights.insert('b', usize::MAX / 2);
+    /// weights.insert('c', 1);
+    /// 
+    /// // the weights would overflow when building a tree
+    /// assert!(HuffTree::try_from_weights(weights.clone()).is_err());
+    /// 
+    /// weights.scale_to(1000);
+    /// assert!(weights.total() <= 1000);
+    /// assert_eq!(weights.get(&'c'), Some(&1));
+    /// 
+    /// let tree = HuffTree::from_weights(weights);
+    /// assert_eq!(tree.read_codes().len(), 3);
+    /// # }
+    /// ```
+    /// 
+    /// # Panics
+    /// ---
+    /// When `max_total` is smaller than the number of stored letters, 
+    /// as then not every letter could keep a nonzero weight.
+    fn scale_to(&mut self, max_total: usize){
+        if max_total < self.len(){
+            panic!("max_total smaller than the number of stored letters")
+        }
+        let total = self.total();
+        if total <= max_total as u128{
+            return
+        }
+
+        let spread = (max_total - self.len()) as u128;
+        for (letter, weight) in self.clone(){
+            if weight == 0{
+                continue
+            }
+            if let Some(weight_mut) = self.get_mut(&letter){
+                *weight_mut = scale_weight(weight, spread, total);
+            }
+        }
+    }
+
+    /// Return the [Shannon entropy][entropy_wiki] of the weights, in bits per letter, 
+    /// which is the lowest possible average code length when coding letters with
+    /// the probabilities given by the weights.
+    /// 
+    /// Returns 0.0 if all the weights are 0.
+    /// 
+    /// # Example
+    /// ---
+    /// ```
+    /// use huff_coding::prelude::{ByteWeights, Weights};
+    /// 
+    /// assert_eq!(ByteWeights::from_bytes(b"aaaa").entropy(), 0.0);
+    /// assert_eq!(ByteWeights::from_bytes(b"ab").entropy(), 1.0);
+    /// assert_eq!(ByteWeights::from_bytes(b"aabc").entropy(), 1.5);
+    /// ```
+    /// 
+    /// [entropy_wiki]:https://en.wikipedia.org/wiki/Entropy_(information_theory)
+    #[cfg(feature = "std")]
+    fn entropy(&self) -> f64{
+        let total = self.total() as f64;
+        self.clone()
+            .into_iter()
+            .filter(|(_, weight)| *weight != 0)
+            .map(|(_, weight)|{
+                let p = weight as f64 / total;
+                -p * p.log2()
+            })
+            .sum::<f64>()
+            .max(0.0)
+    }
+}
+
+/// Scale the weight by `spread / total`, adding 1 so that
+/// nonzero weights stay nonzero
+fn scale_weight(weight: usize, spread: u128, total: u128) -> usize{
+    (weight as u128 * spread / total) as usize + 1
 }
 
+#[cfg(feature = "std")]
 impl<L: Eq + Clone + Hash> Weights<L> for HashMap<L, usize>{
     fn get(&self, letter: &L) -> Option<&usize>{
         self.get(letter)
@@ -53,9 +188,28 @@ impl<L: Eq + Clone + Hash> Weights<L> for HashMap<L, usize>{
     }
 }
 
+impl<L: Ord + Clone> Weights<L> for BTreeMap<L, usize>{
+    fn get(&self, letter: &L) -> Option<&usize>{
+        self.get(letter)
+    }
+    fn get_mut(&mut self, letter: &L) -> Option<&mut usize>{
+        self.get_mut(letter)
+    }
+    fn len(&self) -> usize{
+        self.len()
+    }
+    fn is_empty(&self) -> bool{
+        self.is_empty()
+    }
+}
+
 /// Count every letter in the provided slice Returning a [`HashMap`][std::collections::HashMap]
 /// of letters to their counts (weights)
 /// 
+/// The `HashMap` is iterated in a random order, so trees built from it
+/// may differ (in codes, not in their lengths) between runs. Use 
+/// [`build_weights_btree`][build_weights_btree] if that's a problem.
+/// 
 /// # Example
 /// ---
 /// ```
@@ -79,6 +233,7 @@ impl<L: Eq + Clone + Hash> Weights<L> for HashMap<L, usize>{
 /// 
 /// let tree = HuffTree::from_weights(weights);
 /// ```
+#[cfg(feature = "std")]
 pub fn build_weights_map<L: HuffLetter>(letters: &[L]) -> HashMap<L, usize>{
     build_weights_map_with_hasher(letters, RandomState::default())
 }
@@ -113,6 +268,7 @@ pub fn build_weights_map<L: HuffLetter>(letters: &[L]) -> HashMap<L, usize>{
 /// 
 /// let tree = HuffTree::from_weights(weights);
 /// ```
+#[cfg(feature = "std")]
 pub fn build_weights_map_with_hasher<L: HuffLetter, S: BuildHasher>(letters: &[L], hash_builder: S) -> HashMap<L, usize, S>{
     let mut map = HashMap::with_hasher(hash_builder);
     for l in letters{
@@ -122,23 +278,66 @@ pub fn build_weights_map_with_hasher<L: HuffLetter, S: BuildHasher>(letters: &[L
     map
 }
 
+/// Count every letter in the provided slice Returning a [`BTreeMap`][std::collections::BTreeMap]
+/// of letters to their counts (weights)
+/// 
+/// The `BTreeMap` is iterated in the letters' order, so the 
+/// [`HuffTree`][crate::tree::HuffTree] built from it is always the same
+/// (as opposed to the one built from [`build_weights_map`][build_weights_map]).
+/// 
+/// # Example
+/// ---
+/// ```
+/// # #[cfg(feature = "std")]{
+/// use huff_coding::prelude::{
+///     HuffTree,
+///     build_weights_btree,
+/// };
+/// 
+/// let letters = ['a', 'b', 'c', 'd', 'e', 'f'];
+/// let weights = build_weights_btree(&letters);
+/// assert_eq!(weights.get(&'a'), Some(&1));
+/// 
+/// // every weight is equal, yet the tree is always the same
+/// let tree = HuffTree::from_weights(weights.clone());
+/// for _ in 0..10{
+///     assert_eq!(HuffTree::from_weights(weights.clone()).read_codes(), tree.read_codes());
+/// }
+/// # }
+/// ```
+pub fn build_weights_btree<L: HuffLetter + Ord>(letters: &[L]) -> BTreeMap<L, usize>{
+    let mut map = BTreeMap::new();
+    for l in letters{
+        let entry = map.entry(l.clone()).or_insert(0);
+        *entry += 1;
+    }
+    map
+}
+
 /// Struct storing the number of occurences of each byte in
 /// a provided byte slice.
 pub mod byte_weights{
-    use crate::utils::ration_vec;
-    use super::Weights;
-
-    use std::{
-        ops::{Add, AddAssign},
-        thread,
+    #[cfg(feature = "std")]
+    use crate::concurrency::Concurrency;
+    use super::{
+        Weights,
+        scale_weight,
     };
 
+    use core::ops::{Add, AddAssign};
+
+    /// Number of interleaved tables used when counting bytes
+    const TABLE_COUNT: usize = 4;
+
     /// Struct storing the number of occurences of each byte in
     /// a provided byte slice.
     /// 
     /// A [`HuffTree`][crate::tree::HuffTree] can be initialized with it,
     /// as `ByteWeights` implements the [`Weights`][crate::weights::Weights] trait.
     /// 
+    /// With the `serde` feature enabled, it's serialized as a map
+    /// of the counted bytes to their weights.
+    /// 
     /// # Examples
     /// ---
     /// Initialization and interfacing:
@@ -193,6 +392,14 @@ pub mod byte_weights{
         fn is_empty(&self) -> bool{
             self.is_empty()
         }
+
+        fn total(&self) -> u128{
+            self.total()
+        }
+
+        fn scale_to(&mut self, max_total: usize){
+            self.scale_to(max_total)
+        }
     }
 
     impl IntoIterator for ByteWeights{
@@ -209,7 +416,7 @@ pub mod byte_weights{
         type IntoIter = Iter<'a>;
 
         fn into_iter(self) -> Iter<'a>{
-            Iter{weights: &self, current_index: 0}
+            Iter{weights: self, current_index: 0}
         }   
     }
 
@@ -254,6 +461,9 @@ pub mod byte_weights{
         /// This algorithm is inherently O(n), therefore for
         /// larger collections [`threaded_from_bytes`](#method.threaded_from_bytes) is faster.
         /// 
+        /// The bytes are counted into several interleaved tables, which are summed up
+        /// at the end, so that runs of the same byte don't stall on incrementing the same counter.
+        /// 
         /// # Example
         /// ---
         /// ```
@@ -263,13 +473,24 @@ pub mod byte_weights{
         /// assert_eq!(*byte_weights.get(&b'a').unwrap(), 5);
         /// ```
         pub fn from_bytes(bytes: &[u8]) -> Self{
-            // count bytes into an array
+            // count bytes into TABLE_COUNT interleaved arrays
+            let mut tables = [[0usize; 256]; TABLE_COUNT];
+            let mut chunks = bytes.chunks_exact(TABLE_COUNT);
+            for chunk in &mut chunks{
+                for (table, byte) in tables.iter_mut().zip(chunk){
+                    table[*byte as usize] += 1;
+                }
+            }
+            for (table, byte) in tables.iter_mut().zip(chunks.remainder()){
+                table[*byte as usize] += 1;
+            }
+
+            // sum up the tables into one
             let mut weights: [usize; 256] = [0;256];
             let mut len = 0;
-
-            for byte in bytes{
-                if weights[*byte as usize] == 0{len += 1;}
-                weights[*byte as usize] += 1;
+            for (i, weight) in weights.iter_mut().enumerate(){
+                *weight = tables.iter().map(|table| table[i]).sum();
+                if *weight != 0{len += 1;}
             }
  
             ByteWeights{
@@ -278,10 +499,38 @@ pub mod byte_weights{
             }
         }
 
+        /// Initialize new `ByteWeights` from already counted weights, where
+        /// `counts[byte]` is the weight of `byte`, and 0 means it wasn't counted.
+        ///
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::ByteWeights;
+        ///
+        /// let mut counts = [0; 256];
+        /// counts[b'a' as usize] = 1;
+        /// counts[b'b' as usize] = 2;
+        ///
+        /// let byte_weights = ByteWeights::from_counts(counts);
+        /// assert!(byte_weights == ByteWeights::from_bytes(b"abb"));
+        /// assert_eq!(byte_weights.len(), 2);
+        /// ```
+        pub fn from_counts(counts: [usize; 256]) -> Self{
+            ByteWeights{
+                weights: counts,
+                len: counts.iter().filter(|weight| **weight != 0).count(),
+            }
+        }
+
         /// Initialize new `ByteWeights` from the given [`&[u8]`][u8], but
         /// using the specified number of threads to speed up the
         /// process.
         /// 
+        /// The bytes are split into `thread_num` subslices, every one of them counted in a
+        /// scoped thread, so nothing is copied. On targets without threads (`wasm32-unknown-unknown`)
+        /// they're counted on the calling thread instead. See [`concurrent_from_bytes`](#method.concurrent_from_bytes)
+        /// for more control over the threads used.
+        /// 
         /// # Example
         /// ---
         /// ```
@@ -290,32 +539,32 @@ pub mod byte_weights{
         /// let byte_weights = ByteWeights::threaded_from_bytes(b"aaaaa", 12);
         /// assert_eq!(*byte_weights.get(&b'a').unwrap(), 5)
         /// ```
+        #[cfg(feature = "std")]
         pub fn threaded_from_bytes(bytes: &[u8], thread_num: usize) -> Self{
-            // divide the bytes into rations per thread
-            let byte_rations = ration_vec(bytes, thread_num);
-
-            // create ByteWeights from every ration
-            let mut handles = Vec::with_capacity(thread_num);
-            for ration in byte_rations{
-                let handle = thread::spawn(move || {
-                    ByteWeights::from_bytes(&ration)
-                });
-                handles.push(handle);
-            }
-
-            // push all created ByteWeights into a Vec 
-            let mut weights_vec: Vec<ByteWeights> = Vec::with_capacity(thread_num);
-            for handle in handles{
-                weights_vec.push(handle.join().unwrap());
-            }
-
-            // add all ByteWeights into one
-            let mut weights = weights_vec.pop().unwrap();
-            for weights_other in weights_vec{
-                weights += weights_other;
-            }
+            ByteWeights::concurrent_from_bytes(
+                bytes, 
+                &Concurrency::fixed(thread_num).with_sequential_threshold(0)
+            )
+        }
 
-            weights
+        /// Initialize new `ByteWeights` from the given [`&[u8]`][u8], 
+        /// splitting the work as configured by the provided [`Concurrency`][Concurrency].
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::{ByteWeights, Concurrency};
+        /// 
+        /// let byte_weights = ByteWeights::concurrent_from_bytes(b"aaaaa", &Concurrency::available());
+        /// assert_eq!(*byte_weights.get(&b'a').unwrap(), 5)
+        /// ```
+        #[cfg(feature = "std")]
+        pub fn concurrent_from_bytes(bytes: &[u8], concurrency: &Concurrency) -> Self{
+            // create ByteWeights from every subslice, adding them all into one
+            concurrency
+                .map_chunks(bytes, ByteWeights::from_bytes)
+                .into_iter()
+                .fold(ByteWeights::new(), |weights, other| weights + other)
         }
 
         /// Return a reference to the weight corresponding
@@ -349,10 +598,63 @@ pub mod byte_weights{
         }
 
         /// Returns an iterator over the bytes to their weights `(u8, usize)`
-        pub fn iter(&self) -> Iter{
+        pub fn iter(&self) -> Iter<'_>{
             self.into_iter()
         }
 
+        /// Return the sum of every stored weight as a `u128`, 
+        /// so that it cannot overflow.
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::ByteWeights;
+        /// 
+        /// let byte_weights = ByteWeights::from_bytes(b"abbccc");
+        /// assert_eq!(byte_weights.total(), 6);
+        /// ```
+        pub fn total(&self) -> u128{
+            self.weights
+                .iter()
+                .map(|weight| *weight as u128)
+                .sum()
+        }
+
+        /// Scale every weight down proportionally, so that their sum is not larger than `max_total`,
+        /// keeping every counted byte's weight nonzero (see [`Weights::scale_to`][crate::weights::Weights::scale_to]).
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::ByteWeights;
+        /// 
+        /// let mut byte_weights = ByteWeights::from_bytes(&[b'a'; 1000]);
+        /// byte_weights += ByteWeights::from_bytes(b"b");
+        /// 
+        /// byte_weights.scale_to(100);
+        /// 
+        /// assert!(byte_weights.total() <= 100);
+        /// assert_eq!(*byte_weights.get(&b'b').unwrap(), 1);
+        /// ```
+        /// 
+        /// # Panics
+        /// ---
+        /// When `max_total` is smaller than [`len`](#method.len).
+        pub fn scale_to(&mut self, max_total: usize){
+            if max_total < self.len{
+                panic!("max_total smaller than the number of stored letters")
+            }
+            let total = self.total();
+            if total <= max_total as u128{
+                return
+            }
+
+            let spread = (max_total - self.len) as u128;
+            for weight in self.weights.iter_mut().filter(|weight| **weight != 0){
+                *weight = scale_weight(*weight, spread, total);
+            }
+        }
+
         /// Add another `ByteWeights` to self, like so:
         /// * if a byte is present in self & other, add their weights
         /// * if a byte is present in other, but not in self, add it to self with other's weight
@@ -371,19 +673,116 @@ pub mod byte_weights{
         /// assert_eq!(*byte_weights.get(&b'b').unwrap(), 5);
         /// assert_eq!(*byte_weights.get(&b'c').unwrap(), 1);
         /// ```
+        /// 
+        /// # Panics
+        /// ---
+        /// When any of the summed up weights overflows a `usize` (use 
+        /// [`checked_add_byte_weights`](#method.checked_add_byte_weights) or 
+        /// [`saturating_add_byte_weights`](#method.saturating_add_byte_weights)
+        /// to handle it).
         pub fn add_byte_weights(&mut self, other: &ByteWeights){
+            *self = self.checked_add_byte_weights(other)
+                .expect("byte weights overflow usize");
+        }
+
+        /// Add another `ByteWeights` to self, like [`add_byte_weights`](#method.add_byte_weights), 
+        /// returning the sum, or [`None`][None] if any of the summed up weights overflows a `usize`.
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::ByteWeights;
+        /// 
+        /// let byte_weights = ByteWeights::from_bytes(b"aabbb");
+        /// let other = ByteWeights::from_bytes(b"aaabbc");
+        /// 
+        /// let sum = byte_weights.checked_add_byte_weights(&other).unwrap();
+        /// assert_eq!(*sum.get(&b'a').unwrap(), 5);
+        /// 
+        /// let mut huge = ByteWeights::from_bytes(b"a");
+        /// *huge.get_mut(&b'a').unwrap() = usize::MAX;
+        /// assert!(huge.checked_add_byte_weights(&byte_weights).is_none());
+        /// ```
+        pub fn checked_add_byte_weights(&self, other: &ByteWeights) -> Option<ByteWeights>{
+            let mut sum = *self;
             for (b, f) in other{
-                let self_entry = self.get_mut(&b);
-                match self_entry{
-                    Some(self_entry) =>{
-                        *self_entry += f;
-                    }
-                    None =>{
-                        self.weights[b as usize] = f;
-                        self.len += 1;
-                    }
+                let sum_entry = &mut sum.weights[b as usize];
+                if *sum_entry == 0{
+                    sum.len += 1;
+                }
+                *sum_entry = sum_entry.checked_add(f)?;
+            }
+            Some(sum)
+        }
+
+        /// Add another `ByteWeights` to self, like [`add_byte_weights`](#method.add_byte_weights), 
+        /// but saturating at `usize::MAX` instead of overflowing.
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::ByteWeights;
+        /// 
+        /// let mut byte_weights = ByteWeights::from_bytes(b"a");
+        /// *byte_weights.get_mut(&b'a').unwrap() = usize::MAX;
+        /// 
+        /// byte_weights.saturating_add_byte_weights(&ByteWeights::from_bytes(b"ab"));
+        /// 
+        /// assert_eq!(*byte_weights.get(&b'a').unwrap(), usize::MAX);
+        /// assert_eq!(*byte_weights.get(&b'b').unwrap(), 1);
+        /// ```
+        pub fn saturating_add_byte_weights(&mut self, other: &ByteWeights){
+            for (b, f) in other{
+                let self_entry = &mut self.weights[b as usize];
+                if *self_entry == 0{
+                    self.len += 1;
+                }
+                *self_entry = self_entry.saturating_add(f);
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serialize for ByteWeights{
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>{
+            serializer.collect_map(self.iter())
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de> serde::Deserialize<'de> for ByteWeights{
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>{
+            deserializer.deserialize_map(ByteWeightsVisitor)
+        }
+    }
+
+    /// Visitor reading `ByteWeights` from a map of bytes to their weights
+    #[cfg(feature = "serde")]
+    struct ByteWeightsVisitor;
+
+    #[cfg(feature = "serde")]
+    impl<'de> serde::de::Visitor<'de> for ByteWeightsVisitor{
+        type Value = ByteWeights;
+
+        fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result{
+            write!(f, "a map of bytes to their weights")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<ByteWeights, A::Error>{
+            let mut byte_weights = ByteWeights::new();
+            let mut seen = [false; 256];
+            while let Some((byte, weight)) = map.next_entry::<u8, usize>()?{
+                if seen[byte as usize]{
+                    return Err(serde::de::Error::custom(alloc::format!("duplicate byte {}", byte)))
+                }
+                seen[byte as usize] = true;
+                // weights of 0 mean that the byte wasn't counted
+                if weight != 0{
+                    byte_weights.weights[byte as usize] = weight;
+                    byte_weights.len += 1;
                 }
             }
+            Ok(byte_weights)
         }
     }
 
@@ -397,20 +796,15 @@ pub mod byte_weights{
         type Item = (u8, usize);
 
         fn next(&mut self) -> Option<Self::Item>{
-            if self.current_index == 256{
-                return None
-            }
-
-            while self.weights.get(&(self.current_index as u8)).is_none(){
-                if self.current_index == 256{
-                    return None
+            // the index is checked before being cast, as 256 would wrap around to byte 0
+            while self.current_index < 256{
+                let byte = self.current_index as u8;
+                self.current_index += 1;
+                if let Some(weight) = self.weights.get(&byte){
+                    return Some((byte, *weight))
                 }
-                self.current_index += 1
             }
-            let entry = Some((self.current_index as u8, *self.weights.get(&(self.current_index as u8)).unwrap()));
-            if self.current_index != 256{self.current_index += 1;}
-
-            entry
+            None
         }
     }
 
@@ -424,20 +818,408 @@ pub mod byte_weights{
             type Item = (u8, usize);
     
             fn next(&mut self) -> Option<Self::Item>{
-                if self.current_index == 256{
-                    return None
-                }
-    
-                while self.weights.get(&(self.current_index as u8)).is_none(){
-                    if self.current_index == 256{
-                        return None
+                // the index is checked before being cast, as 256 would wrap around to byte 0
+                while self.current_index < 256{
+                    let byte = self.current_index as u8;
+                    self.current_index += 1;
+                    if let Some(weight) = self.weights.get(&byte){
+                        return Some((byte, *weight))
                     }
-                    self.current_index += 1
                 }
-                let entry = Some((self.current_index as u8, *self.weights.get(&(self.current_index as u8)).unwrap()));
-                if self.current_index != 256{self.current_index += 1;}
-    
-                entry
+                None
             }
     }
 }
+
+
+/// Struct storing the number of occurences of each letter of a
+/// dense alphabet (types implementing [`HuffLetterAsIndex`][crate::tree::letter::HuffLetterAsIndex])
+/// in a provided slice.
+pub mod index_weights{
+    #[cfg(feature = "std")]
+    use crate::concurrency::Concurrency;
+    use crate::tree::letter::HuffLetterAsIndex;
+    use super::{
+        Weights,
+        scale_weight,
+    };
+
+    use alloc::{vec, vec::Vec};
+    use core::{
+        ops::{Add, AddAssign},
+        marker::PhantomData,
+    };
+
+    /// Struct storing the number of occurences of each letter of a dense alphabet 
+    /// (types implementing [`HuffLetterAsIndex`][crate::tree::letter::HuffLetterAsIndex])
+    /// in a provided slice.
+    /// 
+    /// It works just like [`ByteWeights`][crate::weights::ByteWeights], but for any alphabet
+    /// that can be mapped onto indexes, storing the weights in a `Vec` of `L::ALPHABET_LEN` length,
+    /// so counting doesn't need any hashing.
+    /// 
+    /// A [`HuffTree`][crate::tree::HuffTree] can be initialized with it,
+    /// as `IndexWeights` implements the [`Weights`][crate::weights::Weights] trait.
+    /// 
+    /// # Examples
+    /// ---
+    /// Initialization and interfacing:
+    /// ```
+    /// use huff_coding::prelude::IndexWeights;
+    /// 
+    /// let weights = IndexWeights::from_letters(&[1000u16, 1000, 1000, 2]);
+    /// assert_eq!(*weights.get(&1000).unwrap(), 3);
+    /// assert_eq!(weights.len(), 2);
+    /// ```
+    /// Iteration:
+    /// ```
+    /// use huff_coding::prelude::IndexWeights;
+    /// 
+    /// let weights = IndexWeights::from_letters(&[-1i16, 0, 0, 1, 1, 1]);
+    /// for (letter, weight) in weights{
+    ///     assert_eq!(letter as isize, weight as isize - 2);
+    /// }
+    /// ```
+    /// Adding two `IndexWeights`:
+    /// ```
+    /// use huff_coding::prelude::IndexWeights;
+    /// 
+    /// let mut weights = IndexWeights::from_letters(&[300u16, 300, 5]);
+    /// let other = IndexWeights::from_letters(&[300u16, 7]);
+    /// 
+    /// weights += other;
+    /// 
+    /// assert_eq!(*weights.get(&300).unwrap(), 3);
+    /// assert_eq!(*weights.get(&5).unwrap(), 1);
+    /// assert_eq!(*weights.get(&7).unwrap(), 1);
+    /// ```
+    /// Building a [`HuffTree`][crate::tree::HuffTree]:
+    /// ```
+    /// # #[cfg(feature = "std")]{
+    /// use huff_coding::prelude::{HuffTree, IndexWeights};
+    /// 
+    /// let samples: Vec<i16> = vec![-512, 0, 0, 0, 512, 512];
+    /// 
+    /// let tree = HuffTree::from_weights(IndexWeights::from_letters(&samples));
+    /// assert_eq!(tree.read_codes().len(), 3);
+    /// # }
+    /// ```
+    #[derive(Debug, Clone, Eq)]
+    pub struct IndexWeights<L: HuffLetterAsIndex>{
+        weights: Vec<usize>,
+        len: usize,
+        _typebind: PhantomData<L>,
+    }
+
+    impl<L: HuffLetterAsIndex> Weights<L> for IndexWeights<L>{
+        fn get(&self, letter: &L) -> Option<&usize>{
+            self.get(letter)
+        }
+
+        fn get_mut(&mut self, letter: &L) -> Option<&mut usize>{
+            self.get_mut(letter)
+        }
+
+        fn len(&self) -> usize{
+            self.len()
+        }
+
+        fn is_empty(&self) -> bool{
+            self.is_empty()
+        }
+
+        fn total(&self) -> u128{
+            self.total()
+        }
+
+        fn scale_to(&mut self, max_total: usize){
+            self.scale_to(max_total)
+        }
+    }
+
+    impl<L: HuffLetterAsIndex> IntoIterator for IndexWeights<L>{
+        type Item = (L, usize);
+        type IntoIter = IntoIter<L>;
+
+        fn into_iter(self) -> IntoIter<L>{
+            IntoIter{weights: self, current_index: 0}
+        }   
+    }
+
+    impl <'a, L: HuffLetterAsIndex> IntoIterator for &'a IndexWeights<L>{
+        type Item = (L, usize);
+        type IntoIter = Iter<'a, L>;
+
+        fn into_iter(self) -> Iter<'a, L>{
+            Iter{weights: self, current_index: 0}
+        }   
+    }
+
+    impl<L: HuffLetterAsIndex> PartialEq for IndexWeights<L>{
+        fn eq(&self, other: &Self) -> bool {
+            self.weights == other.weights
+        }
+    }
+
+    impl<L: HuffLetterAsIndex> Add for IndexWeights<L>{
+        type Output = Self;
+
+        fn add(mut self, other: Self) -> Self {
+            self.add_index_weights(&other);
+            self
+        }
+    }
+
+    impl<L: HuffLetterAsIndex> AddAssign for IndexWeights<L>{
+        fn add_assign(&mut self, other: Self){
+            self.add_index_weights(&other);
+        }
+    }
+
+    impl<L: HuffLetterAsIndex> Default for IndexWeights<L>{
+        fn default() -> Self{
+            Self::new()
+        }
+    }
+
+    impl<L: HuffLetterAsIndex> IndexWeights<L>{
+        /// Initialize new empty `IndexWeights`
+        pub fn new() -> Self{
+            Self{
+                weights: vec![0; L::ALPHABET_LEN],
+                len: 0,
+                _typebind: PhantomData,
+            }
+        }
+
+        /// Initialize new `IndexWeights` from the given `&[L]`
+        /// 
+        /// This algorithm is inherently O(n), therefore for
+        /// larger collections [`threaded_from_letters`](#method.threaded_from_letters) is faster.
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::IndexWeights;
+        /// 
+        /// let weights = IndexWeights::from_letters(&[0xfffeu16; 5]);
+        /// assert_eq!(*weights.get(&0xfffe).unwrap(), 5);
+        /// ```
+        pub fn from_letters(letters: &[L]) -> Self{
+            let mut weights = IndexWeights::new();
+
+            for letter in letters{
+                let weight = &mut weights.weights[letter.as_index()];
+                if *weight == 0{weights.len += 1;}
+                *weight += 1;
+            }
+
+            weights
+        }
+
+        /// Initialize new `IndexWeights` from the given `&[L]`, but
+        /// using the specified number of threads to speed up the
+        /// process.
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::IndexWeights;
+        /// 
+        /// let weights = IndexWeights::threaded_from_letters(&[-7i8; 5], 12);
+        /// assert_eq!(*weights.get(&-7).unwrap(), 5)
+        /// ```
+        #[cfg(feature = "std")]
+        pub fn threaded_from_letters(letters: &[L], thread_num: usize) -> Self
+        where L: Send + Sync{
+            IndexWeights::concurrent_from_letters(
+                letters, 
+                &Concurrency::fixed(thread_num).with_sequential_threshold(0)
+            )
+        }
+
+        /// Initialize new `IndexWeights` from the given `&[L]`, 
+        /// splitting the work as configured by the provided [`Concurrency`][Concurrency].
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::{IndexWeights, Concurrency};
+        /// 
+        /// let weights = IndexWeights::concurrent_from_letters(&[9u16; 5], &Concurrency::available());
+        /// assert_eq!(*weights.get(&9).unwrap(), 5)
+        /// ```
+        #[cfg(feature = "std")]
+        pub fn concurrent_from_letters(letters: &[L], concurrency: &Concurrency) -> Self
+        where L: Send + Sync{
+            // create IndexWeights from every subslice, adding them all into one
+            concurrency
+                .map_chunks(letters, IndexWeights::from_letters)
+                .into_iter()
+                .fold(IndexWeights::new(), |weights, other| weights + other)
+        }
+
+        /// Return a reference to the weight corresponding
+        /// to the given letter.
+        pub fn get(&self, letter: &L) -> Option<&usize>{
+            let weight = self.weights.get(letter.as_index())?;
+            if *weight == 0{
+                return None
+            }
+            Some(weight)
+        }
+
+        /// Return a mutable reference to the weight corresponding
+        /// to the given letter.
+        pub fn get_mut(&mut self, letter: &L) -> Option<&mut usize>{
+            let weight = self.weights.get_mut(letter.as_index())?;
+            if *weight == 0{
+                return None
+            }
+            Some(weight)
+        }
+
+        /// Return the number of different counted letters stored in the `IndexWeights`
+        pub fn len(&self) -> usize{
+            self.len
+        }
+
+        /// Return true if len == 0
+        pub fn is_empty(&self) -> bool{
+            self.len == 0
+        }
+
+        /// Returns an iterator over the letters to their weights `(L, usize)`
+        pub fn iter(&self) -> Iter<'_, L>{
+            self.into_iter()
+        }
+
+        /// Return the sum of every stored weight as a `u128`, 
+        /// so that it cannot overflow.
+        pub fn total(&self) -> u128{
+            self.weights
+                .iter()
+                .map(|weight| *weight as u128)
+                .sum()
+        }
+
+        /// Scale every weight down proportionally, so that their sum is not larger than `max_total`,
+        /// keeping every counted letter's weight nonzero (see [`Weights::scale_to`][crate::weights::Weights::scale_to]).
+        /// 
+        /// # Panics
+        /// ---
+        /// When `max_total` is smaller than [`len`](#method.len).
+        pub fn scale_to(&mut self, max_total: usize){
+            if max_total < self.len{
+                panic!("max_total smaller than the number of stored letters")
+            }
+            let total = self.total();
+            if total <= max_total as u128{
+                return
+            }
+
+            let spread = (max_total - self.len) as u128;
+            for weight in self.weights.iter_mut().filter(|weight| **weight != 0){
+                *weight = scale_weight(*weight, spread, total);
+            }
+        }
+
+        /// Add another `IndexWeights` to self, like so:
+        /// * if a letter is present in self & other, add their weights
+        /// * if a letter is present in other, but not in self, add it to self with other's weight
+        /// 
+        /// # Example
+        /// ---
+        /// ```
+        /// use huff_coding::prelude::IndexWeights;
+        /// 
+        /// let mut weights = IndexWeights::from_letters(&[1u16, 1, 2]);
+        /// let other = IndexWeights::from_letters(&[1u16, 3]);
+        /// 
+        /// weights.add_index_weights(&other);
+        /// 
+        /// assert_eq!(*weights.get(&1).unwrap(), 3);
+        /// assert_eq!(*weights.get(&2).unwrap(), 1);
+        /// assert_eq!(*weights.get(&3).unwrap(), 1);
+        /// ```
+        /// 
+        /// # Panics
+        /// ---
+        /// When any of the summed up weights overflows a `usize` (use 
+        /// [`checked_add_index_weights`](#method.checked_add_index_weights) or 
+        /// [`saturating_add_index_weights`](#method.saturating_add_index_weights)
+        /// to handle it).
+        pub fn add_index_weights(&mut self, other: &IndexWeights<L>){
+            *self = self.checked_add_index_weights(other)
+                .expect("index weights overflow usize");
+        }
+
+        /// Add another `IndexWeights` to self, like [`add_index_weights`](#method.add_index_weights), 
+        /// returning the sum, or [`None`][None] if any of the summed up weights overflows a `usize`.
+        pub fn checked_add_index_weights(&self, other: &IndexWeights<L>) -> Option<IndexWeights<L>>{
+            let mut sum = self.clone();
+            for (index, weight) in other.weights.iter().enumerate().filter(|(_, weight)| **weight != 0){
+                let sum_entry = &mut sum.weights[index];
+                if *sum_entry == 0{
+                    sum.len += 1;
+                }
+                *sum_entry = sum_entry.checked_add(*weight)?;
+            }
+            Some(sum)
+        }
+
+        /// Add another `IndexWeights` to self, like [`add_index_weights`](#method.add_index_weights), 
+        /// but saturating at `usize::MAX` instead of overflowing.
+        pub fn saturating_add_index_weights(&mut self, other: &IndexWeights<L>){
+            for (index, weight) in other.weights.iter().enumerate().filter(|(_, weight)| **weight != 0){
+                let self_entry = &mut self.weights[index];
+                if *self_entry == 0{
+                    self.len += 1;
+                }
+                *self_entry = self_entry.saturating_add(*weight);
+            }
+        }
+    }
+
+    /// Return the index of the next nonzero weight 
+    /// (starting at `current_index`) with the weight itself
+    fn next_nonzero(weights: &[usize], current_index: usize) -> Option<(usize, usize)>{
+        weights[current_index.min(weights.len())..]
+            .iter()
+            .enumerate()
+            .find(|(_, weight)| **weight != 0)
+            .map(|(offset, weight)| (current_index + offset, *weight))
+    }
+
+    /// Consuming iterator over the contents (`(L, usize)`) of `IndexWeights`
+    pub struct IntoIter<L: HuffLetterAsIndex>{
+        weights: IndexWeights<L>,
+        current_index: usize,
+    }
+    
+    impl<L: HuffLetterAsIndex> Iterator for IntoIter<L>{
+        type Item = (L, usize);
+
+        fn next(&mut self) -> Option<Self::Item>{
+            let (index, weight) = next_nonzero(&self.weights.weights, self.current_index)?;
+            self.current_index = index + 1;
+            Some((L::from_index(index), weight))
+        }
+    }
+
+    /// Non consuming iterator over the contents (`(L, usize)`) of `IndexWeights`
+    pub struct Iter<'a, L: HuffLetterAsIndex>{
+        weights: &'a IndexWeights<L>,
+        current_index: usize,
+    }
+
+    impl<L: HuffLetterAsIndex> Iterator for Iter<'_, L>{
+        type Item = (L, usize);
+
+        fn next(&mut self) -> Option<Self::Item>{
+            let (index, weight) = next_nonzero(&self.weights.weights, self.current_index)?;
+            self.current_index = index + 1;
+            Some((L::from_index(index), weight))
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// only uses what's available without the `std` feature, so it can be run with
+// `cargo test -p huff_coding --no-default-features --test alloc_only`
+
+use huff_coding::{
+    bits::BitWriter,
+    bitvec::prelude::*,
+    prelude::*,
+};
+
+#[test]
+fn alloc_only_compress_decompress(){
+    let letters = ["ab", "cd", "cd", "ef", "ef", "ef", "gh"];
+    let comp_data = compress(&letters);
+    assert_eq!(decompress(&comp_data), letters.to_vec());
+
+    let chars: Vec<char> = "the quick brown fox jumps over the lazy dog".chars().collect();
+    let packed = compress_or_store(&chars.iter().map(|c| *c as u32).collect::<Vec<_>>());
+    assert!(!packed.is_stored());
+    assert_eq!(packed.unpack().unwrap(), chars.iter().map(|c| *c as u32).collect::<Vec<_>>());
+
+    let bytes = b"abbcccdddd";
+    let comp_data = compress(bytes);
+    let from_bytes = CompressData::<u8>::try_from_bytes(&comp_data.to_bytes()).unwrap();
+    assert_eq!(decompress(&from_bytes), bytes.to_vec());
+}
+
+#[test]
+fn alloc_only_codes(){
+    let tree = HuffTree::from_codes(vec![
+        ('a', bitvec![Msb0, u8; 1, 0]),
+        ('b', bitvec![Msb0, u8; 1, 1]),
+        ('c', bitvec![Msb0, u8; 0]),
+    ]).unwrap();
+    let codes = HuffCodes::from_tree(tree);
+    assert_eq!(codes.get(&'a'), Some(bitvec![Msb0, u8; 1, 0]));
+    assert_eq!(codes.code_len(&'c'), Some(1));
+    assert_eq!(codes.get(&'d'), None);
+
+    let mut writer = BitWriter::new();
+    codes.encode(&['c', 'a', 'b'], &mut writer).unwrap();
+    assert_eq!(writer.finish(), (vec![0b0101_1000], 3));
+    assert!(codes.encode(&['d'], &mut BitWriter::new()).is_err());
+
+    // the same letter twice
+    assert!(HuffTree::from_codes(vec![
+        ('a', bitvec![Msb0, u8; 0]),
+        ('a', bitvec![Msb0, u8; 1]),
+    ]).is_err());
+    assert!(HuffTree::from_code_lengths(&[('a', 1), ('a', 1)]).is_err());
+
+    let tree = HuffTree::from_code_lengths(&[('x', 2), ('y', 1), ('z', 2)]).unwrap();
+    let weights = build_weights_btree(&['x', 'y', 'y', 'z']);
+    assert_eq!(tree.compressed_bit_count(&weights), Some(2 + 2 + 2));
+    assert_eq!(tree.compressed_bit_count(&build_weights_btree(&['w'])), None);
+}
@@ -0,0 +1,57 @@
+use huff_coding::{
+    bits::{BitReader, BitWriter},
+    bitvec::prelude::*,
+};
+
+#[test]
+fn bits_write_read(){
+    let mut writer = BitWriter::new();
+    writer.write_bits(u64::MAX, 64);
+    writer.write_bit(false);
+    writer.write_bits(0x0123_4567_89ab_cdef, 61);
+    writer.write_bitslice(&bitvec![Msb0, u8; 1, 0, 1]);
+    assert_eq!(writer.bit_len(), 129);
+    let (bytes, padding_bits) = writer.finish();
+    assert_eq!(bytes.len(), 17);
+    assert_eq!(padding_bits, 7);
+
+    let mut reader = BitReader::new(&bytes, padding_bits);
+    assert_eq!(reader.peek_bits(64), Some(u64::MAX));
+    assert_eq!(reader.read_bits(64), Some(u64::MAX));
+    assert_eq!(reader.read_bit(), Some(false));
+    assert_eq!(reader.read_bits(61), Some(0x0123_4567_89ab_cdef & ((1 << 61) - 1)));
+    assert_eq!(reader.read_bits(4), None);
+    assert_eq!(reader.read_bits(3), Some(0b101));
+    assert_eq!(reader.read_bit(), None);
+}
+
+#[test]
+fn bits_append_align(){
+    // append packed data at every offset
+    for offset in 0..8{
+        let mut writer = BitWriter::new();
+        writer.write_bits(0, offset);
+        writer.append(&[0b1100_1010, 0b1110_0000], 5);
+        let (bytes, padding_bits) = writer.finish();
+
+        let mut reader = BitReader::new(&bytes, padding_bits);
+        assert!(reader.skip_bits(offset as usize));
+        assert_eq!(reader.read_bits(11), Some(0b110_0101_0111));
+        assert!(reader.is_empty());
+    }
+
+    let mut writer = BitWriter::from_bytes(vec![0xff, 0b1000_0000], 7);
+    assert_eq!(writer.align(), 7);
+    writer.write_bytes(b"hff");
+    assert_eq!(writer.take_bytes(), vec![0xff, 0b1000_0000, b'h', b'f', b'f']);
+    writer.write_bits(0b11, 2);
+    assert_eq!(writer.finish(), (vec![0b1100_0000], 6));
+
+    let bytes = [0b1010_0000, b'h', b'f', b'f'];
+    let mut reader = BitReader::new(&bytes, 0);
+    assert_eq!(reader.read_bits(3), Some(0b101));
+    assert_eq!(reader.read_aligned_bytes(3), None);
+    assert_eq!(reader.align(), 5);
+    assert_eq!(reader.read_aligned_bytes(3), Some(&b"hff"[..]));
+    assert_eq!(reader.align(), 0);
+}
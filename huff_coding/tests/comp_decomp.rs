@@ -2,8 +2,6 @@ use huff_coding::{
     prelude::*,
 };
 
-// TODO: more
-
 #[test]
 fn compress_decompress(){
     let bytes = b"float Q_rsqrt( float number )
@@ -28,3 +26,109 @@ fn compress_decompress(){
 
     assert_eq!(decompressed, bytes.to_vec());
 }
+
+#[test]
+fn compress_or_store_fallback(){
+    // every letter is different, so the codes can't be shorter than the letters
+    let letters: Vec<u16> = (0..300).collect();
+    let packed = compress_or_store(&letters);
+    assert!(packed.is_stored());
+    assert_eq!(packed.to_bytes().len(), 1 + letters.len() * 2);
+    assert!(CompressData::<u16>::try_from_bytes(&packed.to_bytes()).is_err());
+    assert_eq!(PackedData::<u16>::try_from_bytes(&packed.to_bytes()).unwrap().unpack().unwrap(), letters);
+
+    let empty = compress_or_store::<u8>(&[]);
+    assert!(empty.is_stored());
+    assert_eq!(PackedData::<u8>::try_from_bytes(&empty.to_bytes()).unwrap().unpack().unwrap(), Vec::<u8>::new());
+
+    let letters = vec![7u16; 300];
+    let packed = compress_or_store(&letters);
+    assert!(!packed.is_stored());
+    assert_eq!(PackedData::<u16>::try_from_bytes(&packed.to_bytes()).unwrap().unpack().unwrap(), letters);
+
+    // read, but not decompressed, with a corrupted last byte
+    let packed = compress_or_store(&b"abbcccdddd".repeat(8));
+    assert!(!packed.is_stored());
+    let mut packed_bytes = packed.to_bytes();
+    *packed_bytes.last_mut().unwrap() ^= 0xff;
+    assert!(PackedData::<u8>::try_from_bytes(&packed_bytes).unwrap().unpack().is_err());
+}
+
+#[test]
+fn compress_deterministic(){
+    // every letter has the same weight, so the tree depends on how the ties are broken
+    let bytes = b"abcdabcd";
+    let comp_bytes = compress(bytes).to_bytes();
+    let tied_letters: Vec<u32> = (0..8).cycle().take(512).collect();
+    let packed_bytes = compress_or_store(&tied_letters).to_bytes();
+    assert!(!compress_or_store(&tied_letters).is_stored());
+
+    for _ in 0..50{
+        assert_eq!(compress(bytes).to_bytes(), comp_bytes);
+        assert_eq!(compress_or_store(&tied_letters).to_bytes(), packed_bytes);
+    }
+}
+
+#[test]
+fn decompress_into_buffers(){
+    let letters: Vec<i32> = (0..1000).map(|i| i % 7 - 3).collect();
+    let comp_data = compress(&letters);
+
+    let mut buf = vec![0; letters.len()];
+    assert_eq!(comp_data.decompress_into(&mut buf).unwrap(), letters.len());
+    assert_eq!(buf, letters);
+
+    let mut short_buf = vec![0; letters.len() - 1];
+    assert!(comp_data.decompress_into(&mut short_buf).is_err());
+
+    let mut vec = vec![42];
+    assert_eq!(comp_data.decompress_to_vec(&mut vec).unwrap(), letters.len());
+    assert_eq!(vec[0], 42);
+    assert_eq!(vec[1..], letters[..]);
+
+    let bytes = b"zero-copy decoding, zero-copy decoding";
+    let comp_data_bytes = compress(bytes).to_bytes();
+    let comp_data_ref = CompressDataRef::<u8>::try_from_bytes(&comp_data_bytes).unwrap();
+    assert_eq!(comp_data_ref.letter_count(), bytes.len());
+    assert_eq!(comp_data_ref.into_owned().decompress_to_vec(&mut Vec::new()).unwrap(), bytes.len());
+
+    // invalid data padding is an error, not a panic
+    let mut invalid_bytes = comp_data_bytes;
+    invalid_bytes[0] |= 0b0000_1000;
+    assert!(CompressDataRef::<u8>::try_from_bytes(&invalid_bytes).is_err());
+}
+
+#[test]
+fn decompress_letter_count(){
+    // with a single letter tree every padding bit would decode into a letter
+    let bytes = b"aaa";
+    let comp_data = compress(bytes);
+    assert_eq!(comp_data.letter_count(), 3);
+    assert_eq!(decompress(&comp_data), bytes.to_vec());
+
+    let (comp_bytes, padding_bits, _, huff_tree) = comp_data.into_inner();
+    let mut vec = vec![b'z'];
+
+    let more_letters = CompressData::new(comp_bytes.clone(), padding_bits, 2, huff_tree.clone());
+    assert!(more_letters.decompress_to_vec(&mut vec).is_err());
+    let fewer_letters = CompressData::new(comp_bytes.clone(), padding_bits, 4, huff_tree.clone());
+    assert!(fewer_letters.decompress_to_vec(&mut vec).is_err());
+    // more letters than bits is rejected before reserving space for them
+    let huge_count = CompressData::new(comp_bytes, padding_bits, usize::MAX, huff_tree);
+    assert!(huge_count.decompress_to_vec(&mut vec).is_err());
+    assert_eq!(vec, b"z");
+
+    // a stream cut off in the middle of a code
+    let comp_data = compress(b"abbccc");
+    assert_eq!(comp_data.padding_bits(), 7);
+    let (comp_bytes, _, letter_count, huff_tree) = comp_data.into_inner();
+    let cut_off = CompressData::new(comp_bytes[..1].to_vec(), 1, letter_count, huff_tree);
+    assert!(cut_off.decompress_to_vec(&mut Vec::new()).is_err());
+}
+
+#[test]
+#[should_panic]
+fn decompress_invalid_letter_count(){
+    let (comp_bytes, padding_bits, letter_count, huff_tree) = compress(b"abbccc").into_inner();
+    decompress(&CompressData::new(comp_bytes, padding_bits, letter_count + 1, huff_tree));
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "std")]
+
+use huff_coding::{
+    prelude::*,
+    stream::{
+        StreamEncoder,
+        StreamDecoder,
+        FrameCodec,
+    },
+};
+use proptest::{
+    prelude::*,
+    collection::vec,
+    sample::select,
+};
+
+use std::fmt::Debug;
+
+/// Letters either picked from a small alphabet (so some repeat and the
+/// codes differ in length), or arbitrary ones (so most are different)
+fn letters<L: Arbitrary + Clone + Debug + 'static>(min_len: usize) -> impl Strategy<Value = Vec<L>>{
+    prop_oneof![
+        vec(any::<L>(), 1..8)
+            .prop_flat_map(move |alphabet| vec(select(alphabet), min_len..300)),
+        vec(any::<L>(), min_len..300),
+    ]
+}
+
+/// Generates the round-trip properties for every provided `HuffLetterAsBytes` type
+macro_rules! letter_props{
+    {$($name:ident: $type:ty),+} => {
+        $(
+        mod $name{
+            use super::*;
+
+            proptest!{
+                #[test]
+                fn compress_decompress(letters in letters::<$type>(1)){
+                    let comp_data = compress(&letters);
+                    prop_assert_eq!(comp_data.letter_count(), letters.len());
+                    prop_assert_eq!(decompress(&comp_data), letters.clone());
+
+                    let mut decomp_letters = Vec::new();
+                    comp_data.as_data_ref().decompress_to_vec(&mut decomp_letters).unwrap();
+                    prop_assert_eq!(decomp_letters, letters);
+                }
+
+                #[test]
+                fn bin_roundtrip(letters in letters::<$type>(1)){
+                    let tree = HuffTree::from_weights(build_weights_map(&letters));
+                    let tree_from_bin = HuffTree::<$type>::try_from_bin(tree.as_bin()).unwrap();
+                    prop_assert_eq!(tree_from_bin.read_codes(), tree.read_codes());
+                    prop_assert_eq!(tree_from_bin.as_bin(), tree.as_bin());
+                }
+
+                #[test]
+                fn bytes_roundtrip(letters in letters::<$type>(0)){
+                    let packed_bytes = compress_or_store(&letters).to_bytes();
+                    let packed = PackedData::<$type>::try_from_bytes(&packed_bytes).unwrap();
+                    prop_assert_eq!(packed.to_bytes(), packed_bytes);
+                    prop_assert_eq!(packed.unpack().unwrap(), letters.clone());
+
+                    if !letters.is_empty(){
+                        let comp_bytes = compress(&letters).to_bytes();
+                        let comp_data = CompressData::<$type>::try_from_bytes(&comp_bytes).unwrap();
+                        prop_assert_eq!(comp_data.to_bytes(), comp_bytes);
+                        prop_assert_eq!(decompress(&comp_data), letters);
+                    }
+                }
+            }
+        }
+        )+
+    };
+}
+letter_props!{
+    u8_props: u8, u16_props: u16, u32_props: u32, u64_props: u64, usize_props: usize, u128_props: u128,
+    i8_props: i8, i16_props: i16, i32_props: i32, i64_props: i64, isize_props: isize, i128_props: i128
+}
+
+proptest!{
+    #[test]
+    fn stream_roundtrip(bytes in letters::<u8>(0), part_len in 1..64usize){
+        let weights = ByteWeights::from_bytes(&bytes);
+        let mut encoder = StreamEncoder::new(&weights);
+        let packed_bytes = compress_or_store(&bytes).to_bytes();
+
+        let mut encoded = Vec::new();
+        for part in bytes.chunks(part_len){
+            encoded.extend(encoder.encode(part).unwrap());
+        }
+        encoded.extend(encoder.finish().unwrap());
+        prop_assert_eq!(&encoded, &packed_bytes);
+
+        let mut decoder = StreamDecoder::new();
+        let mut decoded = Vec::new();
+        for part in packed_bytes.chunks(part_len){
+            decoded.extend(decoder.decode(part).unwrap());
+        }
+        decoded.extend(decoder.finish().unwrap());
+        prop_assert_eq!(decoded, bytes);
+    }
+
+    #[test]
+    fn frame_roundtrip(tree_bytes in letters::<u8>(1), payloads in vec(letters::<u8>(0), 0..8)){
+        let codec = FrameCodec::new(HuffTree::from_weights(ByteWeights::from_bytes(&tree_bytes)));
+        let mut frames = Vec::new();
+        for payload in &payloads{
+            codec.encode(payload, &mut frames).unwrap();
+        }
+
+        let mut decoded = Vec::new();
+        let mut src = &frames[..];
+        while let Some((payload, frame_len)) = codec.decode(src).unwrap(){
+            decoded.push(payload);
+            src = &src[frame_len..];
+        }
+        prop_assert!(src.is_empty());
+        prop_assert_eq!(decoded, payloads);
+    }
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "serde")]
+
+use huff_coding::{
+    prelude::*,
+    bitvec::prelude::*,
+    comp::errors::CompressError,
+};
+
+/// Return the codes of the provided letters in the tree,
+/// using only what's available without the `std` feature
+fn codes_of<L: HuffLetter>(tree: &HuffTree<L>, letters: &[L]) -> Vec<Option<BitVec<Msb0, u8>>>{
+    let codes = HuffCodes::from_tree(tree.clone());
+    letters.iter().map(|letter| codes.get(letter)).collect()
+}
+
+#[test]
+fn serde_tree(){
+    let letters = ['a', 'b', 'b', 'c', 'c', 'c', 'd'];
+    let tree = HuffTree::from_weights(build_weights_btree(&letters));
+    let json = serde_json::to_string(&tree).unwrap();
+    let de_tree: HuffTree<char> = serde_json::from_str(&json).unwrap();
+    assert_eq!(codes_of(&de_tree, &letters), codes_of(&tree, &letters));
+    // weights aren't stored
+    assert_eq!(de_tree.root().leaf().weight(), 0);
+
+    let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"aaa"));
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(json, r#"{"shape":[0],"letters":[97]}"#);
+    let de_tree: HuffTree<u8> = serde_json::from_str(&json).unwrap();
+    assert_eq!(codes_of(&de_tree, b"a"), codes_of(&tree, b"a"));
+
+    // 1 0a 0b: a joint branch with 'a' and 'b'
+    assert!(serde_json::from_str::<HuffTree<u8>>(r#"{"shape":[128],"letters":[97,98]}"#).is_ok());
+    // duplicate letters
+    assert!(serde_json::from_str::<HuffTree<u8>>(r#"{"shape":[128],"letters":[97,97]}"#).is_err());
+    // too many letters
+    assert!(serde_json::from_str::<HuffTree<u8>>(r#"{"shape":[128],"letters":[97,98,99]}"#).is_err());
+    // too few letters
+    assert!(serde_json::from_str::<HuffTree<u8>>(r#"{"shape":[128],"letters":[97]}"#).is_err());
+    // nonzero padding
+    assert!(serde_json::from_str::<HuffTree<u8>>(r#"{"shape":[129],"letters":[97,98]}"#).is_err());
+    // leftover bytes
+    assert!(serde_json::from_str::<HuffTree<u8>>(r#"{"shape":[128,0],"letters":[97,98]}"#).is_err());
+}
+
+#[test]
+fn serde_byte_weights(){
+    let weights = ByteWeights::from_bytes(b"abbccc");
+    let json = serde_json::to_string(&weights).unwrap();
+    assert_eq!(json, r#"{"97":1,"98":2,"99":3}"#);
+    assert!(serde_json::from_str::<ByteWeights>(&json).unwrap() == weights);
+
+    // zero weights are skipped
+    assert!(serde_json::from_str::<ByteWeights>(r#"{"97":1,"98":2,"99":3,"100":0}"#).unwrap() == weights);
+    // duplicate bytes
+    assert!(serde_json::from_str::<ByteWeights>(r#"{"97":1,"97":2}"#).is_err());
+}
+
+#[test]
+fn serde_compress_data(){
+    let bytes = b"abbcccddddeeeee";
+    let comp_data = compress(bytes);
+    let json = serde_json::to_string(&comp_data).unwrap();
+    let de_comp_data: CompressData<u8> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decompress(&de_comp_data), bytes.to_vec());
+    assert_eq!(de_comp_data.to_bytes(), comp_data.to_bytes());
+
+    let tree = r#"{"shape":[128],"letters":[97,98]}"#;
+    let with = |comp_bytes: &str, padding_bits: u8, letter_count: usize|{
+        serde_json::from_str::<CompressData<u8>>(&format!(
+            r#"{{"comp_bytes":{},"padding_bits":{},"letter_count":{},"huff_tree":{}}}"#,
+            comp_bytes, padding_bits, letter_count, tree
+        ))
+    };
+    assert_eq!(decompress(&with("[80]", 4, 4).unwrap()), b"abab".to_vec());
+    // empty bytes
+    assert!(with("[]", 0, 0).is_err());
+    // too much padding
+    assert!(with("[80]", 8, 0).is_err());
+    // more letters than stored
+    assert!(with("[80]", 4, 5).is_err());
+}
+
+#[test]
+fn serde_errors(){
+    let err = CompressError::new("some error", 'x');
+    let json = serde_json::to_string(&err).unwrap();
+    let de_err: CompressError<char> = serde_json::from_str(&json).unwrap();
+    assert_eq!(de_err.message(), err.message());
+    assert_eq!(de_err.missing_letter(), &'x');
+}
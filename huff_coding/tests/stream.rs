@@ -0,0 +1,121 @@
+use huff_coding::{
+    prelude::*,
+    bits::BitWriter,
+    stream::{
+        StreamEncoder,
+        StreamDecoder,
+        StreamErrorKind,
+    },
+};
+
+#[test]
+fn stream_encode_decode(){
+    let all_bytes: Vec<u8> = (0..=255).chain(0..128).collect();
+    let inputs: [&[u8]; 5] = [
+        b"Grimgor Ironhide...
+        the biggest and meanest Orc warboss there ever was.",
+        b"aaaaaaaaaaaaaaaaaaaa",
+        b"ab",
+        b"",
+        &all_bytes,
+    ];
+    for bytes in inputs.iter(){
+        let packed_bytes = compress_or_store(bytes).to_bytes();
+        for part_len in [1, 2, 7, 13, 1000].iter(){
+            let mut encoder = StreamEncoder::new(&ByteWeights::from_bytes(bytes));
+            let mut encoded = Vec::new();
+            for part in bytes.chunks(*part_len){
+                encoded.extend(encoder.encode(part).unwrap());
+            }
+            encoded.extend(encoder.finish().unwrap());
+            assert_eq!(encoded, packed_bytes);
+
+            let mut decoder = StreamDecoder::new();
+            let mut decoded = Vec::new();
+            for part in packed_bytes.chunks(*part_len){
+                decoded.extend(decoder.decode(part).unwrap());
+            }
+            assert_eq!(decoder.tree().is_some(), !decoder.is_stored());
+            decoded.extend(decoder.finish().unwrap());
+            assert_eq!(&decoded, bytes);
+        }
+    }
+}
+
+#[test]
+fn stream_encode_scaled(){
+    let bytes = b"abbcccddddeeeeeffffffggggggg".repeat(9);
+    for max_total in [bytes.len(), 40, 8].iter(){
+        let mut weights = ByteWeights::from_bytes(&bytes);
+        weights.scale_to(*max_total);
+        let mut encoder = StreamEncoder::with_byte_count(&weights, bytes.len() as u64);
+        assert!(!encoder.is_stored());
+
+        let mut encoded = encoder.encode(&bytes).unwrap();
+        let (last_bytes, header_byte) = encoder.finish_with_header_byte().unwrap();
+        encoded.extend(last_bytes);
+        encoded[0] = header_byte;
+
+        let mut decoder = StreamDecoder::new();
+        let mut decoded = decoder.decode(&encoded).unwrap();
+        decoded.extend(decoder.finish().unwrap());
+        assert_eq!(decoded, bytes);
+    }
+    // exact weights need no rewriting
+    let mut encoder = StreamEncoder::new(&ByteWeights::from_bytes(&bytes));
+    let encoded = encoder.encode(&bytes).unwrap();
+    assert_eq!(encoder.finish_with_header_byte().unwrap().1, encoded[0]);
+}
+
+#[test]
+fn stream_invalid(){
+    let bytes = b"abbcccddddeeeee".repeat(4);
+    let weights = ByteWeights::from_bytes(&bytes);
+    let encode = |bytes: &[u8]|{
+        let mut encoder = StreamEncoder::new(&weights);
+        assert!(!encoder.is_stored());
+        encoder.encode(bytes)?;
+        encoder.finish()
+    };
+    // too many, too few, or different bytes than counted
+    assert_eq!(encode(&[&bytes[..], b"e"].concat()).unwrap_err().kind(), StreamErrorKind::InvalidInput);
+    assert_eq!(encode(&bytes[1..]).unwrap_err().kind(), StreamErrorKind::InvalidInput);
+    assert_eq!(encode(&[&bytes[1..], b"f"].concat()).unwrap_err().kind(), StreamErrorKind::InvalidInput);
+    assert_eq!(encode(&[&bytes[1..], b"e"].concat()).unwrap_err().kind(), StreamErrorKind::InvalidInput);
+    assert!(encode(&bytes.iter().rev().copied().collect::<Vec<_>>()).is_ok());
+
+    let decode = |bytes: &[u8]|{
+        let mut decoder = StreamDecoder::new();
+        let mut decoded = decoder.decode(bytes)?;
+        decoded.extend(decoder.finish()?);
+        Ok::<_, huff_coding::stream::StreamError>(decoded)
+    };
+    let packed_bytes = compress_or_store(&bytes).to_bytes();
+    assert_eq!(decode(&[]).unwrap_err().kind(), StreamErrorKind::MissingHeader);
+    assert_eq!(decode(&packed_bytes[..12]).unwrap_err().kind(), StreamErrorKind::MissingHeader);
+    assert_eq!(decode(&packed_bytes[..15]).unwrap_err().kind(), StreamErrorKind::MissingHeader);
+    // stored flag with other bits set, or invalid padding bits
+    assert_eq!(decode(&[0x81, 0]).unwrap_err().kind(), StreamErrorKind::InvalidHeader);
+    assert_eq!(decode(&[&[0x08], &packed_bytes[1..]].concat()).unwrap_err().kind(), StreamErrorKind::InvalidHeader);
+    // trees of letter_count letters, all of them 'a', every one deeper than the last
+    let all_a_header = |letter_count: usize|{
+        let mut tree_bin = BitWriter::new();
+        for _ in 1..letter_count{
+            tree_bin.write_bits(0b10, 2);
+            tree_bin.write_bits(b'a' as u64, 8);
+        }
+        tree_bin.write_bits(0, 1);
+        tree_bin.write_bits(b'a' as u64, 8);
+        let (tree_bytes, tree_padding_bits) = tree_bin.finish();
+        [
+            &[tree_padding_bits << 4][..], &(tree_bytes.len() as u32).to_be_bytes(), &1u64.to_be_bytes(), &tree_bytes, &[0]
+        ].concat()
+    };
+    assert_eq!(decode(&all_a_header(300)).unwrap_err().kind(), StreamErrorKind::InvalidHeader);
+    assert_eq!(decode(&all_a_header(100_000)).unwrap_err().kind(), StreamErrorKind::InvalidHeader);
+    // cut off, or with bytes left after the data
+    assert_eq!(decode(&packed_bytes[..packed_bytes.len() - 1]).unwrap_err().kind(), StreamErrorKind::InvalidData);
+    assert_eq!(decode(&[&packed_bytes[..], &[0]].concat()).unwrap_err().kind(), StreamErrorKind::InvalidData);
+    assert_eq!(decode(&packed_bytes).unwrap(), bytes);
+    assert_eq!(decode(&[0x80, b'a']).unwrap(), b"a".to_vec());
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "tokio")]
+
+use huff_coding::{
+    prelude::*,
+    stream::{
+        AsyncEncoder,
+        AsyncDecoder,
+    },
+};
+
+use tokio::io::{
+    self,
+    AsyncReadExt,
+    AsyncWriteExt,
+};
+
+const BYTES: &[u8] = b"Grimgor Ironhide...
+    the biggest and meanest Orc warboss there ever was.";
+
+/// Write bytes into a small duplex stream in parts, with the encoder on the other end,
+/// returning what the encoder wrote into the stream
+async fn encode_duplex(mut encoder: AsyncEncoder<io::DuplexStream>, bytes: &[u8], mut other_end: io::DuplexStream) -> io::Result<Vec<u8>>{
+    // the encoder is dropped when writing ends, even with an error, closing the stream
+    let write = async move{
+        for part in bytes.chunks(5){
+            encoder.write_all(part).await?;
+        }
+        encoder.shutdown().await
+    };
+    let read = async move{
+        let mut encoded = Vec::new();
+        other_end.read_to_end(&mut encoded).await.map(|_| encoded)
+    };
+    let (written, read) = tokio::join!(write, read);
+    written?;
+    read
+}
+
+/// Read bytes from a small duplex stream with a decoder,
+/// writing them into the other end in parts
+async fn decode_duplex(bytes: &[u8]) -> io::Result<Vec<u8>>{
+    let (mut client, server) = io::duplex(16);
+    let mut decoder = AsyncDecoder::new(server);
+    let write = async move{
+        for part in bytes.chunks(5){
+            client.write_all(part).await?;
+        }
+        client.shutdown().await
+    };
+    // the decoder is dropped when reading ends, even with an error, closing the stream
+    let read = async move{
+        let mut decoded = Vec::new();
+        decoder.read_to_end(&mut decoded).await.map(|_| decoded)
+    };
+    let (written, read) = tokio::join!(write, read);
+    let decoded = read?;
+    written?;
+    Ok(decoded)
+}
+
+#[tokio::test]
+async fn stream_async_encode_decode(){
+    for bytes in [BYTES, b"aaaaaaaaaaaaaaaaaaaa", b"ab", b""].iter(){
+        let packed_bytes = compress_or_store(bytes).to_bytes();
+
+        let (client, server) = io::duplex(16);
+        let encoded = encode_duplex(AsyncEncoder::new(client), bytes, server).await.unwrap();
+        assert_eq!(encoded, packed_bytes);
+
+        let (client, server) = io::duplex(16);
+        let encoder = AsyncEncoder::with_weights(client, &ByteWeights::from_bytes(bytes));
+        let encoded = encode_duplex(encoder, bytes, server).await.unwrap();
+        assert_eq!(encoded, packed_bytes);
+
+        assert_eq!(&decode_duplex(&packed_bytes).await.unwrap(), bytes);
+    }
+}
+
+#[tokio::test]
+async fn stream_async_invalid(){
+    let (client, server) = io::duplex(16);
+    let encoder = AsyncEncoder::with_weights(client, &ByteWeights::from_bytes(b"abbccc"));
+    let e = encode_duplex(encoder, BYTES, server).await.unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+
+    let packed_bytes = compress_or_store(&BYTES.repeat(4)).to_bytes();
+    let e = decode_duplex(&packed_bytes[..packed_bytes.len() - 1]).await.unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    let e = decode_duplex(&packed_bytes[..5]).await.unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+}
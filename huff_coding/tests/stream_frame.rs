@@ -0,0 +1,145 @@
+#![cfg(feature = "std")]
+
+use huff_coding::{
+    prelude::*,
+    stream::FrameCodec,
+};
+
+use std::{
+    io::Cursor,
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+const MESSAGES: [&[u8]; 6] = [
+    b"Grimgor Ironhide...",
+    b"the biggest and meanest Orc warboss there ever was.",
+    b"",
+    b"a",
+    b"ORC WAAAGH!!!",
+    &[0, 1, 2, 3, 255],
+];
+
+fn shared_codec() -> FrameCodec{
+    FrameCodec::new(HuffTree::from_weights(ByteWeights::from_bytes(&MESSAGES[..2].concat())))
+}
+
+#[test]
+fn stream_frame_encode_decode(){
+    let codec = shared_codec();
+    let other_tree = HuffTree::from_weights(ByteWeights::from_bytes(MESSAGES[4]));
+    let lone_tree = HuffTree::from_weights(ByteWeights::from_bytes(b"a"));
+
+    let mut buf = Vec::new();
+    for message in MESSAGES.iter(){
+        codec.encode(message, &mut buf).unwrap();
+    }
+    codec.encode_with_tree(MESSAGES[4], &other_tree, &mut buf).unwrap();
+    codec.encode_with_tree(&[b'a'; 100], &lone_tree, &mut buf).unwrap();
+    let expected: Vec<Vec<u8>> = MESSAGES.iter()
+        .map(|message| message.to_vec())
+        .chain([MESSAGES[4].to_vec(), vec![b'a'; 100]].iter().cloned())
+        .collect();
+
+    // frames are read only when they're whole
+    let mut decoded = Vec::new();
+    let mut pos = 0;
+    for end in 0..=buf.len(){
+        while let Some((payload, frame_len)) = codec.decode(&buf[pos..end]).unwrap(){
+            decoded.push(payload);
+            pos += frame_len;
+        }
+    }
+    assert_eq!(pos, buf.len());
+    assert_eq!(decoded, expected);
+
+    // coded with the shared tree, stored when it's not shorter or
+    // a byte isn't in the shared tree, with their own tree when it pays off
+    let frame_flags = |message: &[u8]|{
+        let mut frame = Vec::new();
+        codec.encode(message, &mut frame).unwrap();
+        frame[FrameCodec::LEN_LEN]
+    };
+    assert_eq!(frame_flags(MESSAGES[1]) & (FrameCodec::STORED_FLAG | FrameCodec::TREE_FLAG), 0);
+    assert_eq!(frame_flags(MESSAGES[2]), FrameCodec::STORED_FLAG);
+    assert_eq!(frame_flags(MESSAGES[5]), FrameCodec::STORED_FLAG);
+    let mut frame = Vec::new();
+    codec.encode_with_tree(&[b'a'; 100], &lone_tree, &mut frame).unwrap();
+    assert_eq!(frame[FrameCodec::LEN_LEN] & FrameCodec::TREE_FLAG, FrameCodec::TREE_FLAG);
+    assert!(frame.len() < 100);
+}
+
+#[test]
+fn stream_frame_invalid(){
+    let codec = shared_codec();
+    let mut frame = Vec::new();
+    codec.encode(MESSAGES[1], &mut frame).unwrap();
+
+    // empty or too long
+    assert!(codec.decode(&[0, 0, 0, 0]).is_err());
+    assert!(codec.decode(&[0xff, 0, 0, 0]).is_err());
+    let small_codec = FrameCodec::with_max_frame_len(codec.tree().clone(), 8);
+    assert!(small_codec.decode(&frame).is_err());
+    assert!(small_codec.encode(MESSAGES[1], &mut Vec::new()).is_err());
+    // invalid flags
+    assert!(codec.decode(&[0, 0, 0, 2, 0x81, 0]).is_err());
+    assert!(codec.decode(&[0, 0, 0, 2, 0x08, 0]).is_err());
+    // missing payload or tree
+    assert!(codec.decode(&[0, 0, 0, 1, 0]).is_err());
+    assert!(codec.decode(&[0, 0, 0, 3, 0x40, 0, 5]).is_err());
+    // cut off in the middle of a code
+    let mut cut_frame = frame.clone();
+    cut_frame[FrameCodec::LEN_LEN] = 7;
+    assert!(codec.decode(&cut_frame).is_err());
+    // payload with a byte not in the provided tree
+    let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"ab"));
+    assert!(codec.encode_with_tree(b"abc", &tree, &mut Vec::new()).is_err());
+
+    // reader ending in the middle of a frame, or right before one
+    let mut reader = Cursor::new(&frame[..frame.len() - 1]);
+    assert!(codec.read_frame(&mut reader).is_err());
+    let mut reader = Cursor::new(&frame[..2]);
+    assert!(codec.read_frame(&mut reader).is_err());
+    let mut reader = Cursor::new(&frame[..0]);
+    assert!(codec.read_frame(&mut reader).unwrap().is_none());
+}
+
+#[test]
+fn stream_frame_pipe_loopback(){
+    let codec = shared_codec();
+    let other_tree = HuffTree::from_weights(ByteWeights::from_bytes(MESSAGES[4]));
+
+    // in-memory pipe
+    let mut pipe = Vec::new();
+    for message in MESSAGES.iter(){
+        codec.write_frame(&mut pipe, message).unwrap();
+    }
+    codec.write_frame_with_tree(&mut pipe, MESSAGES[4], &other_tree).unwrap();
+    let mut reader = Cursor::new(pipe);
+    for message in MESSAGES.iter().chain([MESSAGES[4]].iter()){
+        assert_eq!(&codec.read_frame(&mut reader).unwrap().unwrap(), message);
+    }
+    assert!(codec.read_frame(&mut reader).unwrap().is_none());
+
+    // local loopback, both ends building the codec from the same weights
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let sender = thread::spawn(move ||{
+        let codec = shared_codec();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        for message in MESSAGES.iter(){
+            codec.write_frame(&mut stream, message).unwrap();
+        }
+        codec.write_frame_with_tree(&mut stream, MESSAGES[4], &other_tree).unwrap();
+    });
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut received = Vec::new();
+    while let Some(payload) = codec.read_frame(&mut stream).unwrap(){
+        received.push(payload);
+    }
+    sender.join().unwrap();
+    assert_eq!(received.len(), MESSAGES.len() + 1);
+    for (payload, message) in received.iter().zip(MESSAGES.iter().chain([MESSAGES[4]].iter())){
+        assert_eq!(payload, message);
+    }
+}
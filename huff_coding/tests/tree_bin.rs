@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 use huff_coding::{
     prelude::*,
     bitvec::prelude::*,
@@ -30,3 +32,50 @@ fn tree_bin_invalid_type(){
 fn tree_bin_invalid_vec(){
     HuffTree::<u8>::try_from_bin(BitVec::new()).unwrap();
 }
+
+#[test]
+fn tree_bin_duplicate_letters(){
+    // a joint branch with 'a' on both sides
+    let mut bin = bitvec![Msb0, u8; 1, 0];
+    bin.extend_from_bitslice(b'a'.view_bits::<Msb0>());
+    bin.push(false);
+    bin.extend_from_bitslice(b'a'.view_bits::<Msb0>());
+    assert!(HuffTree::<u8>::try_from_bin(bin.clone()).is_err());
+
+    // 'a' and '`' differ only in the last bit
+    let last = bin.len() - 1;
+    bin.set(last, false);
+    assert!(HuffTree::<u8>::try_from_bin(bin).is_ok());
+}
+
+#[test]
+fn tree_bin_deep(){
+    // a tree with every joint branch's right child being the next one,
+    // deeper than the stack would allow recursing
+    let chain_bin = |letter_count: i32|{
+        let mut bin = BitVec::<Msb0, u8>::new();
+        for letter in 0..letter_count{
+            if letter != letter_count - 1{
+                bin.push(true);
+            }
+            bin.push(false);
+            bin.extend_from_bitslice(letter.to_be_bytes().view_bits::<Msb0>());
+        }
+        bin
+    };
+    let bin = chain_bin(100_000);
+    let tree = HuffTree::<i32>::try_from_bin(bin.clone()).unwrap();
+    assert_eq!(tree.letter_count(), 100_000);
+    assert_eq!(tree.depth(), 99_999);
+    assert_eq!(tree.as_bin(), bin);
+
+    // the same chain of u8 letters repeats them
+    let mut bin = BitVec::<Msb0, u8>::new();
+    for _ in 1..100_000{
+        bin.extend_from_bitslice(bits![Msb0, u8; 1, 0]);
+        bin.extend_from_bitslice(b'a'.view_bits::<Msb0>());
+    }
+    bin.push(false);
+    bin.extend_from_bitslice(b'a'.view_bits::<Msb0>());
+    assert!(HuffTree::<u8>::try_from_bin(bin).is_err());
+}
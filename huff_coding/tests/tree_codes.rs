@@ -0,0 +1,147 @@
+#![cfg(feature = "std")]
+
+use huff_coding::{
+    prelude::*,
+    bitvec::prelude::*,
+};
+
+use std::collections::HashMap;
+
+#[test]
+fn codes_match_tree(){
+    let bytes = b"the same codes are read once and reused for every encoded slice";
+    let tree = HuffTree::from_weights(ByteWeights::from_bytes(bytes));
+    let tree_codes = tree.read_codes();
+
+    let codes = HuffCodes::from_tree(tree.clone());
+    let indexed_codes = HuffCodes::from_tree_indexed(tree);
+    assert_eq!(codes.to_map(), tree_codes);
+    assert_eq!(indexed_codes.to_map(), tree_codes);
+    for (letter, code) in &tree_codes{
+        assert_eq!(codes.get(letter).as_ref(), Some(code));
+        assert_eq!(indexed_codes.get(letter).as_ref(), Some(code));
+    }
+    assert_eq!(indexed_codes.len(), tree_codes.len());
+    // the codes of bytes are always looked up in the fixed table, like when compressing them
+    assert!(codes.is_indexed());
+    assert!(indexed_codes.is_indexed());
+    assert_eq!(codes.get(&b'#'), None);
+
+    let u16_tree = HuffTree::from_weights(build_weights_map(&bytes.iter().map(|byte| *byte as u16).collect::<Vec<_>>()));
+    assert!(!HuffCodes::from_tree(u16_tree.clone()).is_indexed());
+    assert!(HuffCodes::from_tree_indexed(u16_tree).is_indexed());
+    assert_eq!(indexed_codes.get(&b'#'), None);
+    assert!(compress_with_codes(b"#", &indexed_codes).is_err());
+
+    let comp_data = compress_with_codes(bytes, &indexed_codes).unwrap();
+    assert_eq!(comp_data.comp_bytes(), compress_with_tree(bytes, indexed_codes.into_tree()).unwrap().comp_bytes());
+}
+
+#[test]
+fn codes_longer_than_64_bits(){
+    // fibonacci weights make the tree as deep as possible
+    let mut weights = HashMap::new();
+    let (mut prev, mut next) = (1usize, 1usize);
+    for letter in 0..80u8{
+        weights.insert(letter, prev);
+        let sum = prev + next;
+        prev = next;
+        next = sum;
+    }
+    let tree = HuffTree::from_weights(weights);
+    assert!(tree.depth() > 64);
+
+    let codes = HuffCodes::from_tree_indexed(tree);
+    assert_eq!(codes.code_len(&0), Some(79));
+    assert_eq!(codes.to_map(), codes.huff_tree().read_codes());
+
+    let letters: Vec<u8> = (0..80).rev().chain(0..80).collect();
+    let comp_data = compress_with_codes(&letters, &codes).unwrap();
+    assert_eq!(decompress(&comp_data), letters);
+}
+
+#[test]
+fn tree_from_code_lengths(){
+    // lengths read from a tree rebuild a tree with equally long codes
+    let bytes = b"canonical codes only need their lengths to be transmitted";
+    let weights = ByteWeights::from_bytes(bytes);
+    let tree = HuffTree::from_weights(weights);
+    let mut code_lengths: Vec<(u8, u8)> = tree
+        .read_codes()
+        .into_iter()
+        .map(|(letter, code)| (letter, code.len() as u8))
+        .collect();
+    code_lengths.sort();
+    code_lengths.push((b'#', 0));
+
+    let canonical_tree = HuffTree::from_code_lengths(&code_lengths).unwrap();
+    let canonical_codes = canonical_tree.read_codes();
+    assert_eq!(canonical_codes.len(), code_lengths.len() - 1);
+    for (letter, code_len) in &code_lengths[..code_lengths.len() - 1]{
+        assert_eq!(canonical_codes[letter].len(), *code_len as usize);
+    }
+    assert_eq!(canonical_tree.compressed_bit_count(&weights), tree.compressed_bit_count(&weights));
+    let comp_data = compress_with_tree(bytes, canonical_tree).unwrap();
+    assert_eq!(decompress(&comp_data), bytes.to_vec());
+
+    // single letters
+    assert_eq!(HuffTree::from_code_lengths(&[(b'a', 1)]).unwrap().read_codes()[&b'a'].len(), 1);
+    assert!(HuffTree::from_code_lengths(&[(b'a', 2)]).is_err());
+    // errors
+    assert!(HuffTree::<u8>::from_code_lengths(&[]).is_err());
+    assert!(HuffTree::from_code_lengths(&[(b'a', 0)]).is_err());
+    assert!(HuffTree::from_code_lengths(&[(b'a', 1), (b'a', 1)]).is_err());
+    assert!(HuffTree::from_code_lengths(&[(b'a', 1), (b'b', 255)]).is_err());
+    let over_subscribed = HuffTree::from_code_lengths(&[(b'a', 1), (b'b', 2), (b'c', 2), (b'd', 2)]);
+    assert_eq!(over_subscribed.unwrap_err().message(), "provided code lengths are over-subscribed");
+    // long codes
+    let long_code_lengths: Vec<(u8, u8)> = (1..=200).map(|len| (len, len)).chain(std::iter::once((0, 200))).collect();
+    assert_eq!(HuffTree::from_code_lengths(&long_code_lengths).unwrap().depth(), 200);
+}
+
+#[test]
+fn tree_from_codes(){
+    let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"any tree can be rebuilt from its codes"));
+    let codes = tree.read_codes();
+    let rebuilt_tree = HuffTree::from_codes(codes.clone()).unwrap();
+    assert_eq!(rebuilt_tree.read_codes(), codes);
+    assert_eq!(rebuilt_tree.as_bin(), tree.as_bin());
+
+    let single: HashMap<u8, _> = std::iter::once((b'a', bitvec![Msb0, u8; 1])).collect();
+    assert!(HuffTree::from_codes(single).is_err());
+    assert!(HuffTree::<u8>::from_codes(HashMap::new()).is_err());
+
+    let mut incomplete = HashMap::new();
+    incomplete.insert(b'a', bitvec![Msb0, u8; 0]);
+    incomplete.insert(b'b', bitvec![Msb0, u8; 1, 0]);
+    assert_eq!(HuffTree::from_codes(incomplete.clone()).unwrap_err().message(), "provided codes are incomplete");
+    let mut duplicate = incomplete.clone();
+    duplicate.insert(b'c', bitvec![Msb0, u8; 1, 0]);
+    assert!(HuffTree::from_codes(duplicate).is_err());
+    let mut prefixed = incomplete;
+    prefixed.insert(b'c', bitvec![Msb0, u8; 1, 0, 1]);
+    assert_eq!(HuffTree::from_codes(prefixed).unwrap_err().message(), "provided codes are not prefix-free");
+}
+
+#[test]
+fn tree_from_codes_deep(){
+    // a code far longer than the stack would allow recursing
+    let mut deep = HashMap::new();
+    deep.insert(b'a', BitVec::<Msb0, u8>::repeat(false, 1_000_000));
+    deep.insert(b'b', bitvec![Msb0, u8; 1]);
+    assert_eq!(HuffTree::from_codes(deep).unwrap_err().message(), "provided codes are incomplete");
+
+    // every letter's code is one 1 longer than the last one's, ending with a 0 (but the last code)
+    let letter_count = 1_000u16;
+    let chain: HashMap<u16, _> = (0..letter_count).map(|letter|{
+        let mut code = BitVec::<Msb0, u8>::repeat(true, letter as usize);
+        if letter != letter_count - 1{
+            code.push(false);
+        }
+        (letter, code)
+    }).collect();
+    let tree = HuffTree::from_codes(chain.clone()).unwrap();
+    assert_eq!(tree.letter_count(), letter_count as usize);
+    assert_eq!(tree.depth(), letter_count as usize - 1);
+    assert_eq!(tree.read_codes(), chain);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+
+use huff_coding::{
+    prelude::*,
+    bitvec::prelude::*,
+};
+
+#[test]
+fn tree_export_dot(){
+    let text = "\"quoted\" \\ backslashes \\ and \"quotes\"";
+    let letters: Vec<char> = text.chars().collect();
+    let tree = HuffTree::from_weights(build_weights_btree(&letters));
+    let dot = tree.to_dot();
+
+    // every branch is a node, and every branch but the root has an edge to it
+    let letter_count = tree.letter_count();
+    assert_eq!(dot.matches("shape=box").count(), letter_count);
+    assert_eq!(dot.matches("shape=circle").count(), letter_count - 1);
+    assert_eq!(dot.matches(" -> ").count(), letter_count * 2 - 2);
+
+    // letters are escaped
+    assert!(dot.contains("label=\"'\\\"'\\ncode: "));
+    assert!(dot.contains("label=\"'\\\\\\\\'\\ncode: "));
+
+    // every code is shown
+    for (letter, code) in tree.read_codes().into_iter().filter(|(letter, _)| !"\"\\".contains(*letter)){
+        let code: String = code.iter().map(|bit| if *bit{'1'} else{'0'}).collect();
+        assert!(dot.contains(&format!("label=\"{:?}\\ncode: {}\\n", letter, code)));
+    }
+}
+
+#[test]
+fn tree_export_ascii(){
+    let tree = HuffTree::from_weights(build_weights_btree(b"abbcccdddd"));
+    let ascii = tree.to_ascii_with(|byte| (*byte as char).to_string());
+
+    // one line per branch, letter lines end with their code and weight
+    assert_eq!(ascii.lines().count(), 7);
+    for (letter, code) in tree.read_codes(){
+        let code: String = code.iter().map(|bit| if *bit{'1'} else{'0'}).collect();
+        let weight = (letter - b'a' + 1) as usize;
+        assert!(ascii.contains(&format!("{} = {} ({})\n", letter as char, code, weight)));
+    }
+
+    // the children's lines are indented by their depth
+    let depths: Vec<usize> = ascii.lines().map(|line| line.rfind("- ").map_or(0, |i| i + 2)).collect();
+    assert_eq!(depths.iter().max(), Some(&(tree.depth() * 5)));
+}
+
+#[test]
+fn tree_export_deep(){
+    // a tree with every joint branch's right child being the next one,
+    // deeper than the stack would allow recursing
+    let letter_count = 5_000i32;
+    let mut bin = BitVec::<Msb0, u8>::new();
+    for letter in 0..letter_count{
+        if letter != letter_count - 1{
+            bin.push(true);
+        }
+        bin.push(false);
+        bin.extend_from_bitslice(letter.to_be_bytes().view_bits::<Msb0>());
+    }
+    let tree = HuffTree::<i32>::try_from_bin(bin).unwrap();
+    let branch_count = letter_count as usize * 2 - 1;
+
+    assert_eq!(tree.to_dot().matches("shape=").count(), branch_count);
+    assert_eq!(tree.to_ascii().lines().count(), branch_count);
+    assert_eq!(format!("{:?}", tree.root()).matches("HuffBranch").count(), branch_count);
+}
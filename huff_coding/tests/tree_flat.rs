@@ -0,0 +1,89 @@
+use huff_coding::{
+    prelude::*,
+    comp::STORED_FLAG,
+};
+
+#[test]
+fn tree_flat_decode(){
+    let all_bytes: Vec<u8> = (0..=255).chain(0..128).collect();
+    let inputs: [&[u8]; 4] = [
+        b"Grimgor Ironhide...
+        the biggest and meanest Orc warboss there ever was.",
+        b"aaaa",
+        b"ab",
+        &all_bytes,
+    ];
+    for bytes in inputs.iter(){
+        let comp_data = compress(bytes);
+        let table = comp_data.huff_tree().to_flat_table().unwrap();
+        assert!(table.len() <= FlatTree::MAX_LEN);
+        let flat_tree = FlatTree::new(&table).unwrap();
+
+        let mut buf = vec![0; bytes.len()];
+        let letter_count = flat_tree.decode_into(
+            comp_data.comp_bytes(), comp_data.padding_bits(), comp_data.letter_count(), &mut buf
+        ).unwrap();
+        assert_eq!(letter_count, bytes.len());
+        assert_eq!(&buf, bytes);
+
+        let mut from_bin_table = [0; FlatTree::MAX_LEN];
+        let mut buf = vec![0; bytes.len() + 3];
+        let byte_count = decompress_flat(&comp_data.to_bytes(), &mut from_bin_table, &mut buf).unwrap();
+        assert_eq!(&buf[..byte_count], *bytes);
+        assert_eq!(&from_bin_table[..table.len()], &table[..]);
+    }
+}
+
+#[test]
+fn tree_flat_invalid(){
+    assert!(FlatTree::new(&[]).is_err());
+    assert!(FlatTree::new(&[0x8061]).is_err());
+    // larger than a byte
+    assert!(FlatTree::new(&[0x8100, 0x8061]).is_err());
+    // pointing backwards, or outside of the table
+    assert!(FlatTree::new(&[0x8061, 0]).is_err());
+    assert!(FlatTree::new(&[0x8061, 2, 0x8062, 0]).is_err());
+    assert!(FlatTree::new(&[0x8061, 4, 0x8062, 0x8063]).is_err());
+    assert!(FlatTree::new(&[0x8061, 2, 0x8062, 0x8063]).is_ok());
+
+    let tree = HuffTree::from_weights(ByteWeights::from_bytes(b"abbcccdddd"));
+    let bin = tree.as_bin();
+    let padding_bits = ((8 - bin.len() % 8) % 8) as u8;
+    // too short a table
+    assert!(FlatTree::from_bin(bin.as_raw_slice(), padding_bits, &mut [0; 5]).is_err());
+    assert!(FlatTree::from_bin(bin.as_raw_slice(), padding_bits, &mut [0; 6]).is_ok());
+    // too few or too many bits
+    assert!(FlatTree::from_bin(bin.as_raw_slice(), padding_bits + 1, &mut [0; 6]).is_err());
+    assert!(FlatTree::from_bin(&[bin.as_raw_slice(), &[0]].concat(), padding_bits, &mut [0; 6]).is_err());
+    assert!(FlatTree::from_bin(&[], 0, &mut [0; 6]).is_err());
+
+    let comp_data = compress(b"abbcccdddd");
+    let table = comp_data.huff_tree().to_flat_table().unwrap();
+    let flat_tree = FlatTree::new(&table).unwrap();
+    let decode = |letter_count, buf: &mut [u8]| flat_tree.decode_into(
+        comp_data.comp_bytes(), comp_data.padding_bits(), letter_count, buf
+    );
+    // too short a buffer
+    assert!(decode(10, &mut [0; 9]).is_err());
+    // wrong letter counts
+    assert!(decode(9, &mut [0; 10]).is_err());
+    assert!(decode(11, &mut [0; 11]).is_err());
+    assert_eq!(decode(10, &mut [0; 10]).unwrap(), 10);
+    assert!(flat_tree.decode_into(comp_data.comp_bytes(), 8, 10, &mut [0; 10]).is_err());
+
+    let mut table = [0; FlatTree::MAX_LEN];
+    assert!(decompress_flat(&[], &mut table, &mut [0; 10]).is_err());
+    assert!(decompress_flat(&comp_data.to_bytes(), &mut table, &mut [0; 9]).is_err());
+    assert!(decompress_flat(&comp_data.to_bytes(), &mut [0; 4], &mut [0; 10]).is_err());
+
+    // stored bytes are copied, even with no room for a table
+    let packed = compress_or_store(b"abc");
+    assert!(packed.is_stored());
+    let mut buf = [0; 3];
+    assert_eq!(decompress_flat(&packed.to_bytes(), &mut [], &mut buf).unwrap(), 3);
+    assert_eq!(&buf, b"abc");
+    assert!(decompress_flat(&packed.to_bytes(), &mut [], &mut [0; 2]).is_err());
+    assert_eq!(decompress_flat(&[STORED_FLAG], &mut [], &mut []).unwrap(), 0);
+    // other flags set
+    assert!(decompress_flat(&[STORED_FLAG | 1, b'a'], &mut table, &mut [0; 1]).is_err());
+}
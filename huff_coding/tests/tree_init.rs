@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 use huff_coding::{
     prelude::*,
     bitvec::prelude::*,
@@ -59,7 +61,7 @@ fn tree_single_branch(){
     assert_eq!(tree.root().leaf().letter(), Some(&-12));
 
     // code equals 0
-    assert_eq!(tree.root().leaf().code().unwrap()[0], false);
+    assert!(!tree.root().leaf().code().unwrap()[0]);
     assert_eq!(tree.root().leaf().code().unwrap().get(1), None);
 }
 
@@ -67,4 +69,174 @@ fn tree_single_branch(){
 #[should_panic(expected = "provided empty weights")]
 fn tree_invalid_weights(){
     HuffTree::from_weights(HashMap::<char, usize>::new());
-}
\ No newline at end of file
+}
+
+#[test]
+fn tree_overflowing_weights(){
+    let mut weights = {
+        let mut h = HashMap::new();
+        h.insert('a', usize::MAX / 2);
+        h.insert('b', usize::MAX / 2);
+        h.insert('c', 3);
+        h.insert('d', 1);
+        h
+    };
+    assert_eq!(
+        HuffTree::try_from_weights(weights.clone()).unwrap_err().message(), 
+        "provided weights overflow usize"
+    );
+
+    weights.scale_to(usize::MAX);
+    let tree = HuffTree::try_from_weights(weights).unwrap();
+    let codes = tree.read_codes();
+
+    // every letter is kept after scaling
+    assert_eq!(codes.len(), 4);
+    assert_eq!(codes.get(&'d').unwrap().len(), 3);
+}
+
+#[test]
+fn tree_scaled_byte_weights(){
+    let mut byte_weights = ByteWeights::from_bytes(b"aaaaaaaabbbbccd");
+    *byte_weights.get_mut(&b'a').unwrap() = usize::MAX;
+    assert!(byte_weights.checked_add_byte_weights(&byte_weights).is_none());
+
+    byte_weights.scale_to(usize::MAX / 2);
+    let sum = byte_weights.checked_add_byte_weights(&byte_weights).unwrap();
+    assert!(sum.total() <= usize::MAX as u128);
+    assert_eq!(sum.len(), 4);
+
+    let tree = HuffTree::from_weights(sum);
+    assert_eq!(tree.read_codes().len(), 4);
+}
+
+#[test]
+fn tree_deterministic_ties(){
+    let bytes = b"abcdabcd";
+    let tree = HuffTree::from_weights(ByteWeights::from_bytes(bytes));
+
+    // equal weights are combined in the order the letters are iterated in
+    let mut cmp_codes = HashMap::new();
+    cmp_codes.insert(b'a', bitvec![Msb0, u8; 0, 0]);
+    cmp_codes.insert(b'b', bitvec![Msb0, u8; 0, 1]);
+    cmp_codes.insert(b'c', bitvec![Msb0, u8; 1, 0]);
+    cmp_codes.insert(b'd', bitvec![Msb0, u8; 1, 1]);
+    assert_eq!(tree.read_codes(), cmp_codes);
+
+    // every ordered weights build the same tree
+    let tree_bin = tree.as_bin();
+    assert_eq!(HuffTree::from_weights(build_weights_btree(bytes)).as_bin(), tree_bin);
+    assert_eq!(HuffTree::from_weights(IndexWeights::from_letters(bytes)).as_bin(), tree_bin);
+
+    let text = b"the same input always compresses into the same bytes";
+    let comp_bytes = compress_with_tree(text, HuffTree::from_weights(build_weights_btree(text))).unwrap().to_bytes();
+    for _ in 0..8{
+        assert_eq!(
+            compress_with_tree(text, HuffTree::from_weights(build_weights_btree(text))).unwrap().to_bytes(),
+            comp_bytes
+        );
+        assert_eq!(
+            compress_with_tree(text, HuffTree::from_weights(ByteWeights::from_bytes(text))).unwrap().to_bytes(),
+            comp_bytes
+        );
+    }
+}
+
+#[test]
+fn tree_tie_break(){
+    // many ties between letters and combined branches
+    let mut weights = std::collections::BTreeMap::new();
+    for (letter, weight) in b"abcdefgh".iter().zip([8, 4, 4, 2, 2, 1, 1, 2].iter()){
+        weights.insert(*letter, *weight);
+    }
+
+    let shallow = HuffTree::from_weights_with(weights.clone(), TieBreak::CombinedLast);
+    let deep = HuffTree::from_weights_with(weights.clone(), TieBreak::CombinedFirst);
+
+    // the default is the minimum-variance tie break
+    assert_eq!(HuffTree::from_weights(weights.clone()).as_bin(), shallow.as_bin());
+
+    // same total length, lower maximum length
+    assert_eq!(shallow.compressed_bit_count(&weights), deep.compressed_bit_count(&weights));
+    assert!(shallow.depth() < deep.depth());
+
+    let shallow_lens: Vec<usize> = shallow.read_codes().values().map(|code| code.len()).collect();
+    let deep_lens: Vec<usize> = deep.read_codes().values().map(|code| code.len()).collect();
+    assert_eq!(shallow_lens.iter().max(), Some(&shallow.depth()));
+    assert_eq!(deep_lens.iter().max(), Some(&deep.depth()));
+
+    // errors are the same as without a tie break
+    assert!(HuffTree::try_from_weights_with(HashMap::<u8, usize>::new(), TieBreak::CombinedFirst).is_err());
+}
+
+#[test]
+fn tree_sorted_weights(){
+    let text = b"the two-queue algorithm builds the very same tree as the heap does";
+    let weights = build_weights_btree(text);
+    let mut sorted_weights: Vec<(u8, usize)> = weights.clone().into_iter().collect();
+    sorted_weights.sort_by_key(|(_, weight)| *weight);
+
+    // iterated in the same order, ties are broken the same way
+    let tree = HuffTree::from_weights(weights.clone());
+    let sorted_tree = HuffTree::from_sorted_weights(sorted_weights.clone());
+    assert_eq!(sorted_tree.as_bin(), tree.as_bin());
+    assert_eq!(sorted_tree.read_codes(), tree.read_codes());
+    assert_eq!(sorted_tree.root().leaf().weight(), text.len());
+    assert_eq!(sorted_tree.compressed_bit_count(&weights), tree.compressed_bit_count(&weights));
+
+    // the heap is skipped when the weights come sorted
+    let sorted_map: HashMap<u8, usize> = sorted_weights.iter().cloned().collect();
+    assert_eq!(HuffTree::from_weights(sorted_map.clone()).read_codes().len(), sorted_map.len());
+
+    // single letter
+    let single = HuffTree::from_sorted_weights(vec![('x', 3)]);
+    assert_eq!(single.read_codes()[&'x'], bitvec![Msb0, u8; 0]);
+    assert_eq!(single.root().leaf().code(), Some(bitvec![Msb0, u8; 0]));
+
+    assert_eq!(
+        HuffTree::<u8>::try_from_sorted_weights(vec![]).unwrap_err().message(),
+        "provided empty weights"
+    );
+    assert_eq!(
+        HuffTree::try_from_sorted_weights(vec![(b'a', 2), (b'b', 1)]).unwrap_err().message(),
+        "provided weights are not sorted"
+    );
+    assert_eq!(
+        HuffTree::try_from_sorted_weights((0..300).map(|i| (i as u8, 1))).unwrap_err().message(),
+        "provided duplicate letters"
+    );
+    assert_eq!(
+        HuffTree::try_from_sorted_weights(vec![(b'a', usize::MAX), (b'b', usize::MAX)]).unwrap_err().message(),
+        "provided weights overflow usize"
+    );
+}
+
+#[test]
+fn tree_branch_views(){
+    let tree = HuffTree::from_weights(build_weights_btree(b"abbcccdddd"));
+    let codes = tree.read_codes();
+
+    // every letter branch's code matches the one read from the tree
+    fn check_codes(branch: HuffBranch<u8>, codes: &HashMap<u8, BitVec<Msb0, u8>>){
+        match branch.children_iter(){
+            Some(children_iter) =>{
+                let children: Vec<HuffBranch<u8>> = children_iter.collect();
+                assert_eq!(
+                    branch.leaf().weight(),
+                    children[0].leaf().weight() + children[1].leaf().weight()
+                );
+                children.into_iter().for_each(|child| check_codes(child, codes));
+            }
+            None =>
+                assert_eq!(Some(&codes[branch.leaf().letter().unwrap()]), branch.leaf().code().as_ref()),
+        }
+    }
+    check_codes(tree.root(), &codes);
+    assert_eq!(tree.root().leaf().code(), None);
+    assert_eq!(tree.root().leaf().letter(), None);
+
+    // clones are independent of the original
+    let clone = tree.clone();
+    drop(tree);
+    assert_eq!(clone.read_codes(), codes);
+}
@@ -0,0 +1,210 @@
+/* Generated by cbindgen from src/lib.rs, do not edit by hand */
+
+#ifndef HUFF_CODING_H
+#define HUFF_CODING_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Status returned by every fallible function
+typedef enum HuffStatus {
+  // The function succeeded
+  HUFF_STATUS_OK = 0,
+  // A pointer that cannot be null was null
+  HUFF_STATUS_NULL_POINTER = 1,
+  // An argument was invalid (e.g. empty bytes to compress,
+  // or bytes not matching the weights an encoder was made with)
+  HUFF_STATUS_INVALID_ARGUMENT = 2,
+  // The provided bytes aren't valid compressed data or a valid tree
+  HUFF_STATUS_INVALID_DATA = 3,
+  // The library panicked, which is a bug in it
+  HUFF_STATUS_PANIC = 4,
+} HuffStatus;
+
+// Opaque handle to a [`StreamDecoder`]
+typedef struct HuffDecoder HuffDecoder;
+
+// Opaque handle to a [`StreamEncoder`]
+typedef struct HuffEncoder HuffEncoder;
+
+// Opaque handle to a [`HuffTree<u8>`][tree::HuffTree]
+typedef struct HuffTree HuffTree;
+
+// Bytes allocated by the library, to be freed with [`huff_buffer_free`].
+//
+// An empty buffer's data is null.
+typedef struct HuffBuffer {
+  uint8_t *data;
+  size_t len;
+} HuffBuffer;
+
+// Return a static, null terminated description of the status
+const char *huff_status_message(HuffStatus status);
+
+// Free the bytes stored in the buffer
+//
+// # Safety
+// The buffer has to be returned by the library, and not freed before.
+void huff_buffer_free(HuffBuffer buffer);
+
+// Compress len bytes at bytes, writing them into out in the
+// [`CompressData::to_bytes`][to_bytes] format
+//
+// Returns [`HuffStatus::InvalidArgument`] when there are no bytes to compress.
+//
+// # Safety
+// bytes has to point to len readable bytes, and out to a writable `HuffBuffer`.
+//
+// [to_bytes]:huff_coding::comp::CompressData::to_bytes
+HuffStatus huff_compress(const uint8_t *bytes, size_t len, HuffBuffer *out);
+
+// Decompress len bytes at bytes, stored in the [`CompressData::to_bytes`][to_bytes] format
+// (or the [`PackedData::to_bytes`][packed] format, which [`HuffEncoder`] writes),
+// writing the decompressed bytes into out
+//
+// Returns [`HuffStatus::InvalidData`] when the bytes can't be decompressed.
+//
+// # Safety
+// bytes has to point to len readable bytes, and out to a writable `HuffBuffer`.
+//
+// [to_bytes]:huff_coding::comp::CompressData::to_bytes
+// [packed]:huff_coding::comp::PackedData::to_bytes
+HuffStatus huff_decompress(const uint8_t *bytes, size_t len, HuffBuffer *out);
+
+// Build a tree from the weights of the len bytes at bytes, writing its handle into out
+//
+// Returns [`HuffStatus::InvalidArgument`] when there are no bytes.
+//
+// # Safety
+// bytes has to point to len readable bytes, and out to a writable pointer.
+HuffStatus huff_tree_from_bytes(const uint8_t *bytes, size_t len, HuffTree **out);
+
+// Build a tree from 256 weights at weights, where `weights[byte]` is the weight of `byte`
+// (0 meaning it's not in the tree), writing its handle into out
+//
+// Returns [`HuffStatus::InvalidArgument`] when every weight is 0,
+// or they'd overflow when added together.
+//
+// # Safety
+// weights has to point to 256 readable `size_t`s, and out to a writable pointer.
+HuffStatus huff_tree_from_weights(const size_t *weights, HuffTree **out);
+
+// Read a tree from its binary representation (see [`huff_tree_to_bin`]), stored in len
+// bytes at bytes except for their padding_bits last bits, writing its handle into out
+//
+// Returns [`HuffStatus::InvalidArgument`] when padding_bits is larger than 7,
+// and [`HuffStatus::InvalidData`] when the bytes don't store a tree.
+//
+// # Safety
+// bytes has to point to len readable bytes, and out to a writable pointer.
+HuffStatus huff_tree_from_bin(const uint8_t *bytes, size_t len, uint8_t padding_bits, HuffTree **out);
+
+// Write the tree's binary representation into out,
+// and the number of bits used as padding in its last byte into padding_bits
+//
+// # Safety
+// tree has to be a handle returned by the library, out has to point
+// to a writable `HuffBuffer`, and padding_bits to a writable byte.
+HuffStatus huff_tree_to_bin(const HuffTree *tree, HuffBuffer *out, uint8_t *padding_bits);
+
+// Return the number of bytes stored in the tree, or 0 if tree is null
+//
+// # Safety
+// tree has to be null or a handle returned by the library.
+size_t huff_tree_letter_count(const HuffTree *tree);
+
+// Compress len bytes at bytes with the tree, writing them into out in the
+// [`CompressData::to_bytes`][to_bytes] format
+//
+// Returns [`HuffStatus::InvalidArgument`] when there are no bytes,
+// or they store a byte not found in the tree.
+//
+// # Safety
+// tree has to be a handle returned by the library, bytes has to point
+// to len readable bytes, and out to a writable `HuffBuffer`.
+//
+// [to_bytes]:huff_coding::comp::CompressData::to_bytes
+HuffStatus huff_compress_with_tree(const HuffTree *tree, const uint8_t *bytes, size_t len, HuffBuffer *out);
+
+// Free the tree
+//
+// # Safety
+// tree has to be null or a handle returned by the library, not freed before.
+void huff_tree_free(HuffTree *tree);
+
+// Initialize a streaming encoder for exactly the bytes counted in 256 weights
+// at weights (see [`huff_tree_from_weights`]), writing its handle into out
+//
+// The encoder writes the `.hff` format (the [`PackedData::to_bytes`][packed] format),
+// which stores the bytes uncompressed if compressing them wouldn't make them shorter.
+//
+// # Safety
+// weights has to point to 256 readable `size_t`s, and out to a writable pointer.
+//
+// [packed]:huff_coding::comp::PackedData::to_bytes
+HuffStatus huff_encoder_new(const size_t *weights, HuffEncoder **out);
+
+// Encode the next len bytes at bytes, writing the output ready to be
+// written (starting with the header) into out
+//
+// Returns [`HuffStatus::InvalidArgument`] when there are more bytes
+// than the weights counted, or a byte they didn't count.
+//
+// # Safety
+// encoder has to be a handle returned by the library, bytes has to point
+// to len readable bytes, and out to a writable `HuffBuffer`.
+HuffStatus huff_encoder_encode(HuffEncoder *encoder, const uint8_t *bytes, size_t len, HuffBuffer *out);
+
+// Free the encoder, writing the rest of its output into out
+//
+// Returns [`HuffStatus::InvalidArgument`] when there were fewer bytes
+// than the weights counted, or different ones. The encoder is freed either way.
+//
+// # Safety
+// encoder has to be a handle returned by the library, not freed before,
+// and out has to point to a writable `HuffBuffer`.
+HuffStatus huff_encoder_finish(HuffEncoder *encoder, HuffBuffer *out);
+
+// Free the encoder without finishing it
+//
+// # Safety
+// encoder has to be null or a handle returned by the library, not freed before.
+void huff_encoder_free(HuffEncoder *encoder);
+
+// Initialize a streaming decoder of the `.hff` format, writing its handle into out
+//
+// # Safety
+// out has to point to a writable pointer.
+HuffStatus huff_decoder_new(HuffDecoder **out);
+
+// Decode the next len bytes of the stream at bytes,
+// writing the bytes decoded from them into out
+//
+// Returns [`HuffStatus::InvalidData`] when the stream's header
+// is invalid, or it stores more bytes than the header states.
+//
+// # Safety
+// decoder has to be a handle returned by the library, bytes has to point
+// to len readable bytes, and out to a writable `HuffBuffer`.
+HuffStatus huff_decoder_decode(HuffDecoder *decoder, const uint8_t *bytes, size_t len, HuffBuffer *out);
+
+// Free the decoder, writing the rest of the decoded bytes into out
+//
+// Returns [`HuffStatus::InvalidData`] when the stream ended before its header did,
+// or doesn't store as many bytes as the header states. The decoder is freed either way.
+//
+// # Safety
+// decoder has to be a handle returned by the library, not freed before,
+// and out has to point to a writable `HuffBuffer`.
+HuffStatus huff_decoder_finish(HuffDecoder *decoder, HuffBuffer *out);
+
+// Free the decoder without finishing it
+//
+// # Safety
+// decoder has to be null or a handle returned by the library, not freed before.
+void huff_decoder_free(HuffDecoder *decoder);
+
+#endif  /* HUFF_CODING_H */
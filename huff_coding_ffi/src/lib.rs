@@ -0,0 +1,494 @@
+//! C bindings for the [`huff_coding`] crate, built as a `cdylib` and a `staticlib`,
+//! with the matching header in `include/huff_coding.h`.
+//!
+//! They expose:
+//! * compressing bytes into the [`CompressData::to_bytes`][to_bytes] format and decompressing them
+//! * building [trees](HuffTree) and reading/writing their binary representation
+//! * [streaming encoders](HuffEncoder) and [decoders](HuffDecoder) of the `.hff` format
+//!
+//! Every fallible function returns a [`HuffStatus`], and writes its results through
+//! the provided pointers only when it returns [`HuffStatus::Ok`]. Panics never cross
+//! the FFI boundary, they're caught and returned as [`HuffStatus::Panic`].
+//!
+//! Bytes returned by the library are stored in a [`HuffBuffer`], which has to be
+//! freed with [`huff_buffer_free`], and every handle has its own `_free` function.
+//!
+//! [to_bytes]:huff_coding::comp::CompressData::to_bytes
+
+use huff_coding::{
+    prelude::{
+        ByteWeights,
+        PackedData,
+        compress,
+        compress_with_tree,
+    },
+    stream::{
+        StreamEncoder,
+        StreamDecoder,
+    },
+    bitvec::prelude::BitVec,
+    tree,
+};
+
+use std::{
+    os::raw::c_char,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    slice,
+};
+
+/// Status returned by every fallible function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HuffStatus{
+    /// The function succeeded
+    Ok = 0,
+    /// A pointer that cannot be null was null
+    NullPointer = 1,
+    /// An argument was invalid (e.g. empty bytes to compress,
+    /// or bytes not matching the weights an encoder was made with)
+    InvalidArgument = 2,
+    /// The provided bytes aren't valid compressed data or a valid tree
+    InvalidData = 3,
+    /// The library panicked, which is a bug in it
+    Panic = 4,
+}
+
+/// Bytes allocated by the library, to be freed with [`huff_buffer_free`].
+///
+/// An empty buffer's data is null.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HuffBuffer{
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl HuffBuffer{
+    /// Move the bytes into a `HuffBuffer`, leaving them to be freed by the caller
+    fn from_vec(bytes: Vec<u8>) -> Self{
+        if bytes.is_empty(){
+            return Self{
+                data: ptr::null_mut(),
+                len: 0,
+            }
+        }
+        let len = bytes.len();
+        Self{
+            data: Box::into_raw(bytes.into_boxed_slice()) as *mut u8,
+            len,
+        }
+    }
+}
+
+/// Opaque handle to a [`HuffTree<u8>`][tree::HuffTree]
+pub struct HuffTree{
+    tree: tree::HuffTree<u8>,
+}
+
+/// Opaque handle to a [`StreamEncoder`]
+pub struct HuffEncoder{
+    encoder: StreamEncoder,
+}
+
+/// Opaque handle to a [`StreamDecoder`]
+pub struct HuffDecoder{
+    decoder: StreamDecoder,
+}
+
+/// Run f, returning its status, or [`HuffStatus::Panic`] if it panicked
+fn catch<F: FnOnce() -> Result<(), HuffStatus>>(f: F) -> HuffStatus{
+    match panic::catch_unwind(AssertUnwindSafe(f)){
+        Ok(Ok(())) => HuffStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => HuffStatus::Panic,
+    }
+}
+
+/// Borrow len bytes at ptr, which can be null only when len is 0
+unsafe fn bytes_arg<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], HuffStatus>{
+    if len == 0{
+        return Ok(&[])
+    }
+    if ptr.is_null(){
+        return Err(HuffStatus::NullPointer)
+    }
+    Ok(slice::from_raw_parts(ptr, len))
+}
+
+/// Read 256 weights at ptr, one for every byte
+unsafe fn weights_arg(ptr: *const usize) -> Result<ByteWeights, HuffStatus>{
+    if ptr.is_null(){
+        return Err(HuffStatus::NullPointer)
+    }
+    let mut counts = [0; 256];
+    counts.copy_from_slice(slice::from_raw_parts(ptr, 256));
+    Ok(ByteWeights::from_counts(counts))
+}
+
+/// Return a static, null terminated description of the status
+#[no_mangle]
+pub extern "C" fn huff_status_message(status: HuffStatus) -> *const c_char{
+    let message: &'static [u8] = match status{
+        HuffStatus::Ok => b"ok\0",
+        HuffStatus::NullPointer => b"a required pointer was null\0",
+        HuffStatus::InvalidArgument => b"invalid argument\0",
+        HuffStatus::InvalidData => b"invalid compressed data or tree\0",
+        HuffStatus::Panic => b"the library panicked\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+/// Free the bytes stored in the buffer
+///
+/// # Safety
+/// The buffer has to be returned by the library, and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn huff_buffer_free(buffer: HuffBuffer){
+    if !buffer.data.is_null(){
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+}
+
+/// Compress len bytes at bytes, writing them into out in the
+/// [`CompressData::to_bytes`][to_bytes] format
+///
+/// Returns [`HuffStatus::InvalidArgument`] when there are no bytes to compress.
+///
+/// # Safety
+/// bytes has to point to len readable bytes, and out to a writable `HuffBuffer`.
+///
+/// [to_bytes]:huff_coding::comp::CompressData::to_bytes
+#[no_mangle]
+pub unsafe extern "C" fn huff_compress(bytes: *const u8, len: usize, out: *mut HuffBuffer) -> HuffStatus{
+    catch(||{
+        let bytes = bytes_arg(bytes, len)?;
+        if out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        if bytes.is_empty(){
+            return Err(HuffStatus::InvalidArgument)
+        }
+        *out = HuffBuffer::from_vec(compress(bytes).to_bytes());
+        Ok(())
+    })
+}
+
+/// Decompress len bytes at bytes, stored in the [`CompressData::to_bytes`][to_bytes] format
+/// (or the [`PackedData::to_bytes`][packed] format, which [`HuffEncoder`] writes),
+/// writing the decompressed bytes into out
+///
+/// Returns [`HuffStatus::InvalidData`] when the bytes can't be decompressed.
+///
+/// # Safety
+/// bytes has to point to len readable bytes, and out to a writable `HuffBuffer`.
+///
+/// [to_bytes]:huff_coding::comp::CompressData::to_bytes
+/// [packed]:huff_coding::comp::PackedData::to_bytes
+#[no_mangle]
+pub unsafe extern "C" fn huff_decompress(bytes: *const u8, len: usize, out: *mut HuffBuffer) -> HuffStatus{
+    catch(||{
+        let bytes = bytes_arg(bytes, len)?;
+        if out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let decomp_bytes = PackedData::<u8>::try_from_bytes(bytes)
+            .map_err(|_| HuffStatus::InvalidData)?
+            .unpack()
+            .map_err(|_| HuffStatus::InvalidData)?;
+        *out = HuffBuffer::from_vec(decomp_bytes);
+        Ok(())
+    })
+}
+
+/// Build a tree from the weights of the len bytes at bytes, writing its handle into out
+///
+/// Returns [`HuffStatus::InvalidArgument`] when there are no bytes.
+///
+/// # Safety
+/// bytes has to point to len readable bytes, and out to a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn huff_tree_from_bytes(bytes: *const u8, len: usize, out: *mut *mut HuffTree) -> HuffStatus{
+    catch(||{
+        let bytes = bytes_arg(bytes, len)?;
+        huff_tree_from_byte_weights(ByteWeights::from_bytes(bytes), out)
+    })
+}
+
+/// Build a tree from 256 weights at weights, where `weights[byte]` is the weight of `byte`
+/// (0 meaning it's not in the tree), writing its handle into out
+///
+/// Returns [`HuffStatus::InvalidArgument`] when every weight is 0,
+/// or they'd overflow when added together.
+///
+/// # Safety
+/// weights has to point to 256 readable `size_t`s, and out to a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn huff_tree_from_weights(weights: *const usize, out: *mut *mut HuffTree) -> HuffStatus{
+    catch(||{
+        huff_tree_from_byte_weights(weights_arg(weights)?, out)
+    })
+}
+
+/// Build a tree from the weights, writing its handle into out
+unsafe fn huff_tree_from_byte_weights(weights: ByteWeights, out: *mut *mut HuffTree) -> Result<(), HuffStatus>{
+    if out.is_null(){
+        return Err(HuffStatus::NullPointer)
+    }
+    let tree = tree::HuffTree::try_from_weights(weights)
+        .map_err(|_| HuffStatus::InvalidArgument)?;
+    *out = Box::into_raw(Box::new(HuffTree{tree}));
+    Ok(())
+}
+
+/// Read a tree from its binary representation (see [`huff_tree_to_bin`]), stored in len
+/// bytes at bytes except for their padding_bits last bits, writing its handle into out
+///
+/// Returns [`HuffStatus::InvalidArgument`] when padding_bits is larger than 7,
+/// and [`HuffStatus::InvalidData`] when the bytes don't store a tree.
+///
+/// # Safety
+/// bytes has to point to len readable bytes, and out to a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn huff_tree_from_bin(bytes: *const u8, len: usize, padding_bits: u8, out: *mut *mut HuffTree) -> HuffStatus{
+    catch(||{
+        let bytes = bytes_arg(bytes, len)?;
+        if out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        if padding_bits > 7 || (bytes.is_empty() && padding_bits != 0){
+            return Err(HuffStatus::InvalidArgument)
+        }
+        let mut bin = BitVec::from_vec(bytes.to_vec());
+        bin.truncate(bin.len() - padding_bits as usize);
+        let tree = tree::HuffTree::try_from_bin(bin)
+            .map_err(|_| HuffStatus::InvalidData)?;
+        *out = Box::into_raw(Box::new(HuffTree{tree}));
+        Ok(())
+    })
+}
+
+/// Write the tree's binary representation into out,
+/// and the number of bits used as padding in its last byte into padding_bits
+///
+/// # Safety
+/// tree has to be a handle returned by the library, out has to point
+/// to a writable `HuffBuffer`, and padding_bits to a writable byte.
+#[no_mangle]
+pub unsafe extern "C" fn huff_tree_to_bin(tree: *const HuffTree, out: *mut HuffBuffer, padding_bits: *mut u8) -> HuffStatus{
+    catch(||{
+        if tree.is_null() || out.is_null() || padding_bits.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let bin = (*tree).tree.as_bin();
+        *padding_bits = ((8 - bin.len() % 8) % 8) as u8;
+        *out = HuffBuffer::from_vec(bin.into_vec());
+        Ok(())
+    })
+}
+
+/// Return the number of bytes stored in the tree, or 0 if tree is null
+///
+/// # Safety
+/// tree has to be null or a handle returned by the library.
+#[no_mangle]
+pub unsafe extern "C" fn huff_tree_letter_count(tree: *const HuffTree) -> usize{
+    if tree.is_null(){
+        return 0
+    }
+    (*tree).tree.letter_count()
+}
+
+/// Compress len bytes at bytes with the tree, writing them into out in the
+/// [`CompressData::to_bytes`][to_bytes] format
+///
+/// Returns [`HuffStatus::InvalidArgument`] when there are no bytes,
+/// or they store a byte not found in the tree.
+///
+/// # Safety
+/// tree has to be a handle returned by the library, bytes has to point
+/// to len readable bytes, and out to a writable `HuffBuffer`.
+///
+/// [to_bytes]:huff_coding::comp::CompressData::to_bytes
+#[no_mangle]
+pub unsafe extern "C" fn huff_compress_with_tree(tree: *const HuffTree, bytes: *const u8, len: usize, out: *mut HuffBuffer) -> HuffStatus{
+    catch(||{
+        let bytes = bytes_arg(bytes, len)?;
+        if tree.is_null() || out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        if bytes.is_empty(){
+            return Err(HuffStatus::InvalidArgument)
+        }
+        let comp_data = compress_with_tree(bytes, (*tree).tree.clone())
+            .map_err(|_| HuffStatus::InvalidArgument)?;
+        *out = HuffBuffer::from_vec(comp_data.to_bytes());
+        Ok(())
+    })
+}
+
+/// Free the tree
+///
+/// # Safety
+/// tree has to be null or a handle returned by the library, not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn huff_tree_free(tree: *mut HuffTree){
+    if !tree.is_null(){
+        drop(Box::from_raw(tree));
+    }
+}
+
+/// Initialize a streaming encoder for exactly the bytes counted in 256 weights
+/// at weights (see [`huff_tree_from_weights`]), writing its handle into out
+///
+/// The encoder writes the `.hff` format (the [`PackedData::to_bytes`][packed] format),
+/// which stores the bytes uncompressed if compressing them wouldn't make them shorter.
+///
+/// # Safety
+/// weights has to point to 256 readable `size_t`s, and out to a writable pointer.
+///
+/// [packed]:huff_coding::comp::PackedData::to_bytes
+#[no_mangle]
+pub unsafe extern "C" fn huff_encoder_new(weights: *const usize, out: *mut *mut HuffEncoder) -> HuffStatus{
+    catch(||{
+        let weights = weights_arg(weights)?;
+        if out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        *out = Box::into_raw(Box::new(HuffEncoder{encoder: StreamEncoder::new(&weights)}));
+        Ok(())
+    })
+}
+
+/// Encode the next len bytes at bytes, writing the output ready to be
+/// written (starting with the header) into out
+///
+/// Returns [`HuffStatus::InvalidArgument`] when there are more bytes
+/// than the weights counted, or a byte they didn't count.
+///
+/// # Safety
+/// encoder has to be a handle returned by the library, bytes has to point
+/// to len readable bytes, and out to a writable `HuffBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn huff_encoder_encode(encoder: *mut HuffEncoder, bytes: *const u8, len: usize, out: *mut HuffBuffer) -> HuffStatus{
+    catch(||{
+        let bytes = bytes_arg(bytes, len)?;
+        if encoder.is_null() || out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let output = (*encoder).encoder.encode(bytes)
+            .map_err(|_| HuffStatus::InvalidArgument)?;
+        *out = HuffBuffer::from_vec(output);
+        Ok(())
+    })
+}
+
+/// Free the encoder, writing the rest of its output into out
+///
+/// Returns [`HuffStatus::InvalidArgument`] when there were fewer bytes
+/// than the weights counted, or different ones. The encoder is freed either way.
+///
+/// # Safety
+/// encoder has to be a handle returned by the library, not freed before,
+/// and out has to point to a writable `HuffBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn huff_encoder_finish(encoder: *mut HuffEncoder, out: *mut HuffBuffer) -> HuffStatus{
+    catch(||{
+        if encoder.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let encoder = Box::from_raw(encoder).encoder;
+        if out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let output = encoder.finish()
+            .map_err(|_| HuffStatus::InvalidArgument)?;
+        *out = HuffBuffer::from_vec(output);
+        Ok(())
+    })
+}
+
+/// Free the encoder without finishing it
+///
+/// # Safety
+/// encoder has to be null or a handle returned by the library, not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn huff_encoder_free(encoder: *mut HuffEncoder){
+    if !encoder.is_null(){
+        drop(Box::from_raw(encoder));
+    }
+}
+
+/// Initialize a streaming decoder of the `.hff` format, writing its handle into out
+///
+/// # Safety
+/// out has to point to a writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn huff_decoder_new(out: *mut *mut HuffDecoder) -> HuffStatus{
+    catch(||{
+        if out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        *out = Box::into_raw(Box::new(HuffDecoder{decoder: StreamDecoder::new()}));
+        Ok(())
+    })
+}
+
+/// Decode the next len bytes of the stream at bytes,
+/// writing the bytes decoded from them into out
+///
+/// Returns [`HuffStatus::InvalidData`] when the stream's header
+/// is invalid, or it stores more bytes than the header states.
+///
+/// # Safety
+/// decoder has to be a handle returned by the library, bytes has to point
+/// to len readable bytes, and out to a writable `HuffBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn huff_decoder_decode(decoder: *mut HuffDecoder, bytes: *const u8, len: usize, out: *mut HuffBuffer) -> HuffStatus{
+    catch(||{
+        let bytes = bytes_arg(bytes, len)?;
+        if decoder.is_null() || out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let output = (*decoder).decoder.decode(bytes)
+            .map_err(|_| HuffStatus::InvalidData)?;
+        *out = HuffBuffer::from_vec(output);
+        Ok(())
+    })
+}
+
+/// Free the decoder, writing the rest of the decoded bytes into out
+///
+/// Returns [`HuffStatus::InvalidData`] when the stream ended before its header did,
+/// or doesn't store as many bytes as the header states. The decoder is freed either way.
+///
+/// # Safety
+/// decoder has to be a handle returned by the library, not freed before,
+/// and out has to point to a writable `HuffBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn huff_decoder_finish(decoder: *mut HuffDecoder, out: *mut HuffBuffer) -> HuffStatus{
+    catch(||{
+        if decoder.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let decoder = Box::from_raw(decoder).decoder;
+        if out.is_null(){
+            return Err(HuffStatus::NullPointer)
+        }
+        let output = decoder.finish()
+            .map_err(|_| HuffStatus::InvalidData)?;
+        *out = HuffBuffer::from_vec(output);
+        Ok(())
+    })
+}
+
+/// Free the decoder without finishing it
+///
+/// # Safety
+/// decoder has to be null or a handle returned by the library, not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn huff_decoder_free(decoder: *mut HuffDecoder){
+    if !decoder.is_null(){
+        drop(Box::from_raw(decoder));
+    }
+}
@@ -0,0 +1,168 @@
+use huff_coding_ffi::*;
+
+use std::{
+    ffi::CStr,
+    ptr,
+    slice,
+};
+
+/// Copy the buffer's bytes into a Vec and free it
+unsafe fn take(buffer: HuffBuffer) -> Vec<u8>{
+    let bytes = if buffer.data.is_null(){
+        Vec::new()
+    }
+    else{
+        slice::from_raw_parts(buffer.data, buffer.len).to_vec()
+    };
+    huff_buffer_free(buffer);
+    bytes
+}
+
+fn empty_buffer() -> HuffBuffer{
+    HuffBuffer{data: ptr::null_mut(), len: 0}
+}
+
+#[test]
+fn ffi_compress_decompress(){
+    let bytes = b"abbcccddddeeeee".repeat(4);
+    unsafe{
+        let mut comp = empty_buffer();
+        assert_eq!(huff_compress(bytes.as_ptr(), bytes.len(), &mut comp), HuffStatus::Ok);
+        let comp = take(comp);
+        assert_eq!(comp, huff_coding::prelude::compress(&bytes).to_bytes());
+
+        let mut decomp = empty_buffer();
+        assert_eq!(huff_decompress(comp.as_ptr(), comp.len(), &mut decomp), HuffStatus::Ok);
+        assert_eq!(take(decomp), bytes);
+        // stored bytes
+        let mut decomp = empty_buffer();
+        assert_eq!(huff_decompress([0x80, b'a'].as_ptr(), 2, &mut decomp), HuffStatus::Ok);
+        assert_eq!(take(decomp), b"a");
+
+        // trees
+        let mut tree = ptr::null_mut();
+        assert_eq!(huff_tree_from_bytes(bytes.as_ptr(), bytes.len(), &mut tree), HuffStatus::Ok);
+        assert_eq!(huff_tree_letter_count(tree), 5);
+        let mut bin = empty_buffer();
+        let mut padding_bits = 0;
+        assert_eq!(huff_tree_to_bin(tree, &mut bin, &mut padding_bits), HuffStatus::Ok);
+        let bin = take(bin);
+        let mut read_tree = ptr::null_mut();
+        assert_eq!(huff_tree_from_bin(bin.as_ptr(), bin.len(), padding_bits, &mut read_tree), HuffStatus::Ok);
+        let mut counts = [0usize; 256];
+        for byte in b"edcba"{
+            counts[*byte as usize] = 1;
+        }
+        let mut weights_tree = ptr::null_mut();
+        assert_eq!(huff_tree_from_weights(counts.as_ptr(), &mut weights_tree), HuffStatus::Ok);
+        assert_eq!(huff_tree_letter_count(weights_tree), 5);
+
+        let mut comp = empty_buffer();
+        assert_eq!(huff_compress_with_tree(read_tree, bytes.as_ptr(), bytes.len(), &mut comp), HuffStatus::Ok);
+        let comp = take(comp);
+        let mut decomp = empty_buffer();
+        assert_eq!(huff_decompress(comp.as_ptr(), comp.len(), &mut decomp), HuffStatus::Ok);
+        assert_eq!(take(decomp), bytes);
+        huff_tree_free(tree);
+        huff_tree_free(read_tree);
+        huff_tree_free(weights_tree);
+
+        // streaming
+        let mut counts = [0usize; 256];
+        for byte in &bytes{
+            counts[*byte as usize] += 1;
+        }
+        let mut encoder = ptr::null_mut();
+        assert_eq!(huff_encoder_new(counts.as_ptr(), &mut encoder), HuffStatus::Ok);
+        let mut encoded = Vec::new();
+        for part in bytes.chunks(7){
+            let mut out = empty_buffer();
+            assert_eq!(huff_encoder_encode(encoder, part.as_ptr(), part.len(), &mut out), HuffStatus::Ok);
+            encoded.extend(take(out));
+        }
+        let mut out = empty_buffer();
+        assert_eq!(huff_encoder_finish(encoder, &mut out), HuffStatus::Ok);
+        encoded.extend(take(out));
+
+        let mut decoder = ptr::null_mut();
+        assert_eq!(huff_decoder_new(&mut decoder), HuffStatus::Ok);
+        let mut decoded = Vec::new();
+        for part in encoded.chunks(3){
+            let mut out = empty_buffer();
+            assert_eq!(huff_decoder_decode(decoder, part.as_ptr(), part.len(), &mut out), HuffStatus::Ok);
+            decoded.extend(take(out));
+        }
+        let mut out = empty_buffer();
+        assert_eq!(huff_decoder_finish(decoder, &mut out), HuffStatus::Ok);
+        decoded.extend(take(out));
+        assert_eq!(decoded, bytes);
+        // the whole stream can be decompressed at once too
+        let mut decomp = empty_buffer();
+        assert_eq!(huff_decompress(encoded.as_ptr(), encoded.len(), &mut decomp), HuffStatus::Ok);
+        assert_eq!(take(decomp), bytes);
+    }
+}
+
+#[test]
+fn ffi_errors(){
+    let bytes = b"abbcccddddeeeee".repeat(4);
+    unsafe{
+        let mut out = empty_buffer();
+        assert_eq!(huff_compress(ptr::null(), 1, &mut out), HuffStatus::NullPointer);
+        assert_eq!(huff_compress(bytes.as_ptr(), bytes.len(), ptr::null_mut()), HuffStatus::NullPointer);
+        assert_eq!(huff_compress(ptr::null(), 0, &mut out), HuffStatus::InvalidArgument);
+        assert_eq!(huff_decompress(b"not compressed".as_ptr(), 14, &mut out), HuffStatus::InvalidData);
+        assert!(out.data.is_null());
+
+        let mut tree = ptr::null_mut();
+        assert_eq!(huff_tree_from_weights([0; 256].as_ptr(), &mut tree), HuffStatus::InvalidArgument);
+        assert_eq!(huff_tree_from_bin([0xff].as_ptr(), 1, 8, &mut tree), HuffStatus::InvalidArgument);
+        assert_eq!(huff_tree_from_bin([0].as_ptr(), 1, 0, &mut tree), HuffStatus::InvalidData);
+        assert!(tree.is_null());
+        assert_eq!(huff_tree_letter_count(ptr::null()), 0);
+        assert_eq!(huff_tree_from_bytes(b"ab".as_ptr(), 2, &mut tree), HuffStatus::Ok);
+        assert_eq!(huff_compress_with_tree(tree, b"abc".as_ptr(), 3, &mut out), HuffStatus::InvalidArgument);
+        huff_tree_free(tree);
+
+        // fewer bytes than counted
+        let mut counts = [0usize; 256];
+        for byte in &bytes{
+            counts[*byte as usize] += 1;
+        }
+        let mut encoder = ptr::null_mut();
+        assert_eq!(huff_encoder_new(counts.as_ptr(), &mut encoder), HuffStatus::Ok);
+        assert_eq!(huff_encoder_encode(encoder, bytes.as_ptr(), bytes.len() - 1, &mut out), HuffStatus::Ok);
+        huff_buffer_free(out);
+        let mut out = empty_buffer();
+        assert_eq!(huff_encoder_finish(encoder, &mut out), HuffStatus::InvalidArgument);
+        huff_encoder_free(ptr::null_mut());
+
+        let mut decoder = ptr::null_mut();
+        assert_eq!(huff_decoder_new(&mut decoder), HuffStatus::Ok);
+        assert_eq!(huff_decoder_decode(decoder, [0x81, 0].as_ptr(), 2, &mut out), HuffStatus::InvalidData);
+        huff_decoder_free(decoder);
+        assert_eq!(huff_decoder_finish(ptr::null_mut(), &mut out), HuffStatus::NullPointer);
+
+        let message = CStr::from_ptr(huff_status_message(HuffStatus::InvalidData));
+        assert_eq!(message.to_str().unwrap(), "invalid compressed data or tree");
+    }
+}
+
+#[test]
+fn ffi_header(){
+    // every exported function has to be declared in the header,
+    // regenerate it with cbindgen if this fails
+    let src = include_str!("../src/lib.rs");
+    let header = include_str!("../include/huff_coding.h");
+    let mut fn_count = 0;
+    for line in src.lines(){
+        let name = line.strip_prefix("pub unsafe extern \"C\" fn ")
+            .or_else(|| line.strip_prefix("pub extern \"C\" fn "))
+            .and_then(|rest| rest.split('(').next());
+        if let Some(name) = name{
+            assert!(header.contains(&format!("{}(", name)), "{} is missing from the header", name);
+            fn_count += 1;
+        }
+    }
+    assert_eq!(fn_count, header.matches(");\n").count());
+}
@@ -0,0 +1,147 @@
+//! Python bindings for the [`huff_coding`] crate, built into
+//! the `huff_coding` extension module with [maturin](https://github.com/PyO3/maturin).
+//!
+//! The module exposes:
+//! * `compress(data)` and `decompress(data)`, reading and writing the format of `.hff` files
+//! * `read_hff(path)` and `write_hff(path, data)` for `.hff` files themselves
+//! * the `HuffTree` class, built from a `{byte: weight}` dict, or from bytes,
+//!   which returns its codes and compresses bytes with them
+//!
+//! Invalid arguments and data raise a `ValueError`, while failed file operations raise an `OSError`.
+
+use huff_coding::{
+    prelude::{
+        ByteWeights,
+        PackedData,
+        compress_or_store,
+        compress_with_tree,
+    },
+    tree,
+};
+use pyo3::{
+    prelude::*,
+    exceptions::PyValueError,
+    types::PyBytes,
+};
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+};
+
+/// Compress the bytes into the `.hff` format,
+/// storing them as they are if compressing them wouldn't make them shorter
+#[pyfunction]
+fn compress<'p>(py: Python<'p>, data: &[u8]) -> Bound<'p, PyBytes>{
+    PyBytes::new(py, &compress_or_store(data).to_bytes())
+}
+
+/// Decompress bytes stored in the `.hff` format
+/// (or returned by `HuffTree.compress`)
+///
+/// Raises a `ValueError` if the bytes can't be decompressed.
+#[pyfunction]
+fn decompress<'p>(py: Python<'p>, data: &[u8]) -> PyResult<Bound<'p, PyBytes>>{
+    Ok(PyBytes::new(py, &unpack(data)?))
+}
+
+/// Compress the bytes and write them into the `.hff` file at path
+#[pyfunction]
+fn write_hff(path: PathBuf, data: &[u8]) -> PyResult<()>{
+    fs::write(path, compress_or_store(data).to_bytes())?;
+    Ok(())
+}
+
+/// Read and decompress the `.hff` file at path
+///
+/// Raises a `ValueError` if the file's contents can't be decompressed.
+#[pyfunction]
+fn read_hff(py: Python<'_>, path: PathBuf) -> PyResult<Bound<'_, PyBytes>>{
+    let data = fs::read(path)?;
+    Ok(PyBytes::new(py, &unpack(&data)?))
+}
+
+/// Read the packed bytes, decompressing them if needed
+fn unpack(data: &[u8]) -> PyResult<Vec<u8>>{
+    PackedData::<u8>::try_from_bytes(data)
+        .map_err(value_error)?
+        .unpack()
+        .map_err(value_error)
+}
+
+fn value_error<E: ToString>(e: E) -> PyErr{
+    PyValueError::new_err(e.to_string())
+}
+
+/// Huffman tree of bytes
+#[pyclass(name = "HuffTree", frozen)]
+struct HuffTree{
+    tree: tree::HuffTree<u8>,
+}
+
+#[pymethods]
+impl HuffTree{
+    /// Build a tree from a `{byte: weight}` dict
+    ///
+    /// Raises a `ValueError` if every weight is 0.
+    #[staticmethod]
+    fn from_weights(weights: HashMap<u8, usize>) -> PyResult<Self>{
+        let mut counts = [0; 256];
+        for (byte, weight) in weights{
+            counts[byte as usize] = weight;
+        }
+        Self::from_byte_weights(ByteWeights::from_counts(counts))
+    }
+
+    /// Build a tree from the weights of the bytes
+    ///
+    /// Raises a `ValueError` if there are no bytes.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self>{
+        Self::from_byte_weights(ByteWeights::from_bytes(data))
+    }
+
+    /// Return a `{byte: code}` dict, where every code is a string of '0's and '1's
+    fn codes(&self) -> HashMap<u8, String>{
+        self.tree.read_codes()
+            .into_iter()
+            .map(|(byte, code)| (byte, code.iter().map(|bit| if *bit {'1'} else {'0'}).collect()))
+            .collect()
+    }
+
+    /// Compress the bytes with the tree, into a format `decompress` reads
+    ///
+    /// Raises a `ValueError` if the bytes store a byte not found in the tree.
+    fn compress<'p>(&self, py: Python<'p>, data: &[u8]) -> PyResult<Bound<'p, PyBytes>>{
+        let comp_data = compress_with_tree(data, self.tree.clone()).map_err(value_error)?;
+        Ok(PyBytes::new(py, &comp_data.to_bytes()))
+    }
+
+    /// Return the number of bytes stored in the tree
+    fn __len__(&self) -> usize{
+        self.tree.letter_count()
+    }
+
+    fn __repr__(&self) -> String{
+        format!("HuffTree(letter_count={})", self.tree.letter_count())
+    }
+}
+
+impl HuffTree{
+    fn from_byte_weights(weights: ByteWeights) -> PyResult<Self>{
+        let tree = tree::HuffTree::try_from_weights(weights).map_err(value_error)?;
+        Ok(Self{tree})
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "huff_coding")]
+fn huff_coding_py(m: &Bound<'_, PyModule>) -> PyResult<()>{
+    m.add_function(wrap_pyfunction!(compress, m)?)?;
+    m.add_function(wrap_pyfunction!(decompress, m)?)?;
+    m.add_function(wrap_pyfunction!(write_hff, m)?)?;
+    m.add_function(wrap_pyfunction!(read_hff, m)?)?;
+    m.add_class::<HuffTree>()?;
+    Ok(())
+}
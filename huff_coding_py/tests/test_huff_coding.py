@@ -0,0 +1,72 @@
+import pytest
+
+import huff_coding
+from huff_coding import HuffTree
+
+
+def test_compress_decompress():
+    inputs = [
+        b"Grimgor Ironhide... the biggest and meanest Orc warboss there ever was.",
+        b"abbcccddddeeeee" * 4,
+        b"a" * 20,
+        b"ab",
+        b"",
+        bytes(range(256)),
+    ]
+    for data in inputs:
+        comp = huff_coding.compress(data)
+        assert huff_coding.decompress(comp) == data
+    # skewed bytes get shorter, while random ones are stored with a 1 byte flag
+    assert len(huff_coding.compress(b"a" * 100 + b"bc")) < 102
+    assert huff_coding.compress(bytes(range(256))) == b"\x80" + bytes(range(256))
+
+
+def test_decompress_invalid():
+    comp = huff_coding.compress(b"abbcccddddeeeee" * 4)
+    with pytest.raises(ValueError):
+        huff_coding.decompress(b"")
+    with pytest.raises(ValueError):
+        huff_coding.decompress(comp[:10])
+    with pytest.raises(ValueError):
+        huff_coding.decompress(comp[:-1])
+
+
+def test_tree():
+    tree = HuffTree.from_weights({ord("a"): 1, ord("b"): 2, ord("c"): 4})
+    assert len(tree) == 3
+    codes = tree.codes()
+    assert set(codes) == {ord("a"), ord("b"), ord("c")}
+    assert len(codes[ord("c")]) == 1
+    assert len(codes[ord("a")]) == len(codes[ord("b")]) == 2
+    assert all(set(code) <= {"0", "1"} for code in codes.values())
+
+    data = b"abbcccc" * 4
+    assert HuffTree.from_bytes(data).codes() == codes
+    assert huff_coding.decompress(tree.compress(data)) == data
+
+
+def test_tree_invalid():
+    with pytest.raises(ValueError):
+        HuffTree.from_weights({})
+    with pytest.raises(ValueError):
+        HuffTree.from_weights({ord("a"): 0})
+    with pytest.raises(OverflowError):
+        HuffTree.from_weights({256: 1})
+    with pytest.raises(ValueError):
+        HuffTree.from_bytes(b"")
+    with pytest.raises(ValueError):
+        HuffTree.from_bytes(b"ab").compress(b"abc")
+
+
+def test_hff_files(tmp_path):
+    data = b"abbcccddddeeeee" * 4
+    path = tmp_path / "data.hff"
+    huff_coding.write_hff(path, data)
+    assert path.read_bytes() == huff_coding.compress(data)
+    assert huff_coding.read_hff(str(path)) == data
+
+    with pytest.raises(OSError):
+        huff_coding.read_hff(tmp_path / "missing.hff")
+    path.write_bytes(b"\x08not compressed")
+    with pytest.raises(ValueError):
+        huff_coding.read_hff(path)
@@ -0,0 +1,41 @@
+//! WebAssembly bindings for the [`huff_coding`] crate, made with
+//! [wasm-bindgen](https://github.com/rustwasm/wasm-bindgen).
+//!
+//! They expose [`compress`] and [`decompress`], which take and return `Uint8Array`s
+//! in the format of the `.hff` files created by the `huff` CLI, so they can be
+//! decompressed in the browser.
+
+use huff_coding::prelude::{
+    PackedData,
+    compress_or_store,
+};
+use wasm_bindgen::prelude::*;
+
+/// Compress the bytes into the `.hff` format,
+/// storing them as they are if compressing them wouldn't make them shorter
+///
+/// # Example
+/// ---
+/// ```
+/// use huff_coding_wasm::{compress, decompress};
+///
+/// let bytes = b"abbcccddddeeeee".repeat(4);
+/// let comp_bytes = compress(&bytes);
+///
+/// assert!(comp_bytes.len() < bytes.len());
+/// assert_eq!(decompress(&comp_bytes).unwrap(), bytes);
+/// ```
+#[wasm_bindgen]
+pub fn compress(bytes: &[u8]) -> Vec<u8>{
+    compress_or_store(bytes).to_bytes()
+}
+
+/// Decompress bytes stored in the `.hff` format
+///
+/// # Errors
+/// ---
+/// When the bytes can't be decompressed, which throws an `Error` in JavaScript.
+#[wasm_bindgen]
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, JsError>{
+    Ok(PackedData::<u8>::try_from_bytes(bytes)?.unpack()?)
+}
@@ -0,0 +1,37 @@
+//! Run with `wasm-pack test --headless --firefox` (or `--chrome`)
+#![cfg(target_arch = "wasm32")]
+
+use huff_coding_wasm::{
+    compress,
+    decompress,
+};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn wasm_compress_decompress(){
+    let all_bytes: Vec<u8> = (0..=255).collect();
+    let inputs: [&[u8]; 4] = [
+        b"abbcccddddeeeeeabbcccddddeeeee",
+        b"aaaaaaaaaaaaaaaaaaaa",
+        b"",
+        &all_bytes,
+    ];
+    for bytes in inputs.iter(){
+        let comp_bytes = compress(bytes);
+        assert_eq!(&decompress(&comp_bytes).unwrap(), bytes);
+    }
+    // every byte appears once, so they're stored
+    assert_eq!(compress(&all_bytes), [&[0x80], &all_bytes[..]].concat());
+}
+
+#[wasm_bindgen_test]
+fn wasm_decompress_invalid(){
+    let comp_bytes = compress(&b"abbcccddddeeeee".repeat(4));
+    assert!(decompress(&[]).is_err());
+    assert!(decompress(&comp_bytes[..10]).is_err());
+    assert!(decompress(&comp_bytes[..comp_bytes.len() - 1]).is_err());
+    // a tree length that doesn't fit with the header in a 32-bit usize
+    assert!(decompress(&[0, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 1, 0]).is_err());
+}